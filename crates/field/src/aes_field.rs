@@ -17,13 +17,13 @@ use crate::{
 	TowerExtensionField, TowerField,
 };
 use bytemuck::{Pod, Zeroable};
-use rand::RngCore;
-use std::{
+use core::{
 	array,
 	fmt::{Debug, Display, Formatter},
 	iter::{Product, Step, Sum},
 	ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
 };
+use rand::RngCore;
 use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
 
 // These fields represent a tower based on AES GF(2^8) field (GF(256)/x^8+x^4+x^3+x+1)
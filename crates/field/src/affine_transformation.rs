@@ -1,6 +1,7 @@
 use crate::{packed::PackedBinaryField, BinaryField, BinaryField1b, ExtensionField};
+use alloc::vec::Vec;
+use core::ops::Deref;
 use rand::RngCore;
-use std::ops::Deref;
 
 /// Generic transformation trait that is used both for scalars and packed fields
 pub trait Transformation<Input, Output> {
@@ -1,10 +1,10 @@
 use bytemuck::{Pod, Zeroable};
-use rand::RngCore;
-use seq_macro::seq;
-use std::{
+use core::{
 	arch::aarch64::*,
 	ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Shl, Shr},
 };
+use rand::RngCore;
+use seq_macro::seq;
 use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
 
 use super::super::portable::{
@@ -228,8 +228,8 @@ impl Random for M128 {
 	}
 }
 
-impl std::fmt::Display for M128 {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for M128 {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
 		let data: u128 = (*self).into();
 		write!(f, "{data:02X?}")
 	}
@@ -353,3 +353,105 @@ where
 		value.into()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::underlier::single_element_mask_bits;
+	use proptest::{arbitrary::any, proptest};
+
+	fn check_roundtrip<T>(val: M128)
+	where
+		T: From<M128>,
+		M128: From<T>,
+	{
+		assert_eq!(M128::from(T::from(val)), val);
+	}
+
+	#[test]
+	fn test_constants() {
+		assert_eq!(M128::default(), M128::ZERO);
+		assert_eq!(M128::from(0u128), M128::ZERO);
+		assert_eq!(M128::from(1u128), M128::ONE);
+	}
+
+	proptest! {
+		#[test]
+		fn test_conversion(a in any::<u128>()) {
+			check_roundtrip::<u128>(a.into());
+			check_roundtrip::<uint8x16_t>(a.into());
+			check_roundtrip::<uint16x8_t>(a.into());
+			check_roundtrip::<uint32x4_t>(a.into());
+			check_roundtrip::<uint64x2_t>(a.into());
+			check_roundtrip::<poly8x16_t>(a.into());
+			check_roundtrip::<poly16x8_t>(a.into());
+			check_roundtrip::<poly64x2_t>(a.into());
+		}
+
+		#[test]
+		fn test_binary_bit_operations(a in any::<u128>(), b in any::<u128>()) {
+			assert_eq!(M128::from(a & b), M128::from(a) & M128::from(b));
+			assert_eq!(M128::from(a | b), M128::from(a) | M128::from(b));
+			assert_eq!(M128::from(a ^ b), M128::from(a) ^ M128::from(b));
+		}
+
+		#[test]
+		fn test_negate(a in any::<u128>()) {
+			assert_eq!(M128::from(!a), !M128::from(a))
+		}
+
+		#[test]
+		fn test_shifts(a in any::<u128>(), b in 0..128usize) {
+			assert_eq!(M128::from(a << b), M128::from(a) << b);
+			assert_eq!(M128::from(a >> b), M128::from(a) >> b);
+		}
+
+		#[test]
+		fn test_interleave_bits(a in any::<u128>(), b in any::<u128>(), height in 0usize..7) {
+			let a = M128::from(a);
+			let b = M128::from(b);
+
+			let (c, d) = a.interleave(b, height);
+
+			let block_len = 1usize << height;
+			let get = |v, i| {
+				u128::num_cast_from((v >> (i * block_len)) & single_element_mask_bits::<M128>(1 << height))
+			};
+			for i in (0..128/block_len).step_by(2) {
+				assert_eq!(get(c, i), get(a, i));
+				assert_eq!(get(c, i+1), get(b, i));
+				assert_eq!(get(d, i), get(a, i+1));
+				assert_eq!(get(d, i+1), get(b, i+1));
+			}
+		}
+
+		#[test]
+		fn test_shuffle_u8(a in any::<u128>(), src in any::<[u8; 16]>()) {
+			let bytes = a.to_le_bytes();
+			// Out of range indices select zero, as for `vqtbl1q_u8`.
+			let expected: [u8; 16] = core::array::from_fn(|i| bytes.get(src[i] as usize).copied().unwrap_or(0));
+			assert_eq!(M128::from(a).shuffle_u8(src), M128::from_le_bytes(expected));
+		}
+	}
+
+	#[test]
+	fn test_fill_with_bit() {
+		assert_eq!(M128::fill_with_bit(1), M128::from(u128::MAX));
+		assert_eq!(M128::fill_with_bit(0), M128::from(0u128));
+	}
+
+	#[test]
+	fn test_eq() {
+		let a = M128::from(0u128);
+		let b = M128::from(42u128);
+		let c = M128::from(u128::MAX);
+
+		assert_eq!(a, a);
+		assert_eq!(b, b);
+		assert_eq!(c, c);
+
+		assert_ne!(a, b);
+		assert_ne!(a, c);
+		assert_ne!(b, c);
+	}
+}
@@ -26,7 +26,7 @@ use crate::{
 	BinaryField64b, BinaryField8b, PackedAESBinaryField16x8b,
 };
 
-use std::ops::Mul;
+use core::ops::Mul;
 
 // Define 128 bit packed field types
 pub type PackedBinaryField128x1b = PackedPrimitiveType<M128, BinaryField1b>;
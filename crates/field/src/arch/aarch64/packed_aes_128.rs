@@ -27,7 +27,7 @@ use crate::{
 	},
 	PackedBinaryField16x8b,
 };
-use std::ops::Mul;
+use core::ops::Mul;
 
 // Define 128 bit packed field types
 pub type PackedAESBinaryField16x8b = PackedPrimitiveType<M128, AESTowerField8b>;
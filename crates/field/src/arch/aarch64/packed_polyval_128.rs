@@ -14,8 +14,7 @@
 //! - <https://developer.arm.com/documentation/100069/0608/A64-SIMD-Vector-Instructions/PMULL--PMULL2--vector->
 //! - <https://eprint.iacr.org/2015/688.pdf>
 
-use core::{arch::aarch64::*, mem};
-use std::ops::Mul;
+use core::{arch::aarch64::*, mem, ops::Mul};
 
 use super::super::portable::packed::{
 	impl_conversion, impl_packed_extension_field, PackedPrimitiveType,
@@ -162,3 +161,42 @@ unsafe fn pmull2(a: uint8x16_t, b: uint8x16_t) -> uint8x16_t {
 		vgetq_lane_u64(vreinterpretq_u64_u8(b), 1),
 	))
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::arithmetic_traits::Square;
+	use proptest::{arbitrary::any, proptest};
+
+	type PortablePolyval =
+		super::super::super::portable::packed_polyval_128::PackedBinaryPolyval1x128b;
+
+	proptest! {
+		#[test]
+		fn test_mul_matches_portable(a in any::<u128>(), b in any::<u128>()) {
+			let expected = PortablePolyval::from(a) * PortablePolyval::from(b);
+			let result = PackedBinaryPolyval1x128b::from(a) * PackedBinaryPolyval1x128b::from(b);
+			assert_eq!(u128::from(result), u128::from(expected));
+		}
+
+		#[test]
+		fn test_square_matches_portable(a in any::<u128>()) {
+			let expected = Square::square(PortablePolyval::from(a));
+			let result = Square::square(PackedBinaryPolyval1x128b::from(a));
+			assert_eq!(u128::from(result), u128::from(expected));
+		}
+
+		#[test]
+		fn test_invert_or_zero_matches_portable(a in any::<u128>()) {
+			let expected = PackedField::invert_or_zero(PortablePolyval::from(a));
+			let result = PackedField::invert_or_zero(PackedBinaryPolyval1x128b::from(a));
+			assert_eq!(u128::from(result), u128::from(expected));
+		}
+	}
+
+	#[test]
+	fn test_invert_zero() {
+		let zero = PackedBinaryPolyval1x128b::from(0u128);
+		assert_eq!(u128::from(PackedField::invert_or_zero(zero)), 0);
+	}
+}
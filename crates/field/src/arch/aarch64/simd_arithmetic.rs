@@ -15,7 +15,7 @@ use crate::{
 	BinaryField, PackedField, TowerField,
 };
 
-use std::arch::aarch64::*;
+use core::arch::aarch64::*;
 
 #[inline]
 pub fn packed_tower_16x8b_multiply(a: M128, b: M128) -> M128 {
@@ -76,10 +76,10 @@ pub fn packed_aes_16x8b_multiply(a: M128, b: M128) -> M128 {
 		// Since q+(x) doesn't fit into 8 bits, we right shift the polynomial (divide by x) and correct for this later.
 		// This works because q+(x) is divisible by x/the last polynomial bit is 0.
 		// q+(x)/x = (x^8 + x^4 + x^3 + x)/x = 0b100011010 >> 1 = 0b10001101 = 0x8d
-		const QPLUS_RSH1: poly8x8_t = unsafe { std::mem::transmute(0x8d8d8d8d8d8d8d8d_u64) };
+		const QPLUS_RSH1: poly8x8_t = unsafe { core::mem::transmute(0x8d8d8d8d8d8d8d8d_u64) };
 
 		// q*(x) = x^4 + x^3 + x + 1 = 0b00011011 = 0x1b
-		const QSTAR: poly8x8_t = unsafe { std::mem::transmute(0x1b1b1b1b1b1b1b1b_u64) };
+		const QSTAR: poly8x8_t = unsafe { core::mem::transmute(0x1b1b1b1b1b1b1b1b_u64) };
 
 		let cl = vuzp1q_p8(c0, c1);
 		let ch = vuzp2q_p8(c0, c1);
@@ -113,7 +113,7 @@ pub fn packed_aes_16x8b_into_tower(x: M128) -> M128 {
 #[inline]
 pub fn lookup_16x8b(table: [u8; 256], x: M128) -> M128 {
 	unsafe {
-		let table: [uint8x16x4_t; 4] = std::mem::transmute(table);
+		let table: [uint8x16x4_t; 4] = core::mem::transmute(table);
 		let x = x.into();
 		let y0 = vqtbl4q_u8(table[0], x);
 		let y1 = vqtbl4q_u8(table[1], veorq_u8(x, vdupq_n_u8(0x40)));
@@ -6,14 +6,19 @@ use crate::{
 	underlier::{NumCast, UnderlierType, WithUnderlier},
 	BinaryField, Error, PackedField,
 };
+use alloc::vec::Vec;
 use bytemuck::{Pod, Zeroable};
-use rand::RngCore;
-use std::{
-	fmt::Debug,
+use core::{
+	fmt::{self, Debug},
 	iter::{Product, Sum},
 	marker::PhantomData,
 	ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign},
 };
+use rand::RngCore;
+use serde::{
+	de::{self, SeqAccess, Visitor},
+	Deserialize, Deserializer, Serialize, Serializer,
+};
 use subtle::{Choice, ConstantTimeEq};
 
 #[derive(PartialEq, Eq, Clone, Copy, Default)]
@@ -60,7 +65,7 @@ impl<U: UnderlierType, Scalar: BinaryField> Debug for PackedPrimitiveType<U, Sca
 where
 	Self: PackedField,
 {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
 		let width = U::BITS / Scalar::N_BITS;
 		let values: Vec<_> = (0..width).map(|i| self.get(i)).collect();
 		write!(f, "Packed{}x{}({:?})", width, Scalar::N_BITS, values)
@@ -203,6 +208,55 @@ unsafe impl<U: UnderlierType + Zeroable, Scalar: BinaryField> Zeroable
 
 unsafe impl<U: UnderlierType + Pod, Scalar: BinaryField> Pod for PackedPrimitiveType<U, Scalar> {}
 
+/// Serializes the underlier as its in-memory bytes, which on little-endian targets is the
+/// little-endian encoding of the packed value regardless of the SIMD underlier in use.
+impl<U: UnderlierType + Pod, Scalar: BinaryField> Serialize for PackedPrimitiveType<U, Scalar> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_bytes(bytemuck::bytes_of(&self.0))
+	}
+}
+
+impl<'de, U: UnderlierType + Pod, Scalar: BinaryField> Deserialize<'de>
+	for PackedPrimitiveType<U, Scalar>
+{
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let underlier = deserializer.deserialize_bytes(UnderlierBytesVisitor(PhantomData))?;
+		Ok(Self(underlier, PhantomData))
+	}
+}
+
+/// Reads an underlier back from exactly `size_of::<U>()` bytes, given either as a byte buffer or,
+/// for formats without native byte strings, as a sequence of `u8`.
+struct UnderlierBytesVisitor<U>(PhantomData<U>);
+
+impl<'de, U: Pod> Visitor<'de> for UnderlierBytesVisitor<U> {
+	type Value = U;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "{} bytes", core::mem::size_of::<U>())
+	}
+
+	fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<U, E> {
+		if bytes.len() != core::mem::size_of::<U>() {
+			return Err(E::invalid_length(bytes.len(), &self));
+		}
+		Ok(bytemuck::pod_read_unaligned(bytes))
+	}
+
+	fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<U, A::Error> {
+		let mut value = U::zeroed();
+		for (i, byte) in bytemuck::bytes_of_mut(&mut value).iter_mut().enumerate() {
+			*byte = seq
+				.next_element()?
+				.ok_or_else(|| de::Error::invalid_length(i, &self))?;
+		}
+		if seq.next_element::<u8>()?.is_some() {
+			return Err(de::Error::invalid_length(core::mem::size_of::<U>() + 1, &self));
+		}
+		Ok(value)
+	}
+}
+
 impl<U: UnderlierType, Scalar: BinaryField> PackedField for PackedPrimitiveType<U, Scalar>
 where
 	Self: Broadcast<Scalar> + Square + InvertOrZero + Mul<Output = Self>,
@@ -436,7 +490,7 @@ pub(crate) use packed_binary_field_tower;
 
 macro_rules! impl_ops_for_zero_height {
 	($name:ty) => {
-		impl std::ops::Mul for $name {
+		impl core::ops::Mul for $name {
 			type Output = Self;
 
 			#[allow(clippy::suspicious_arithmetic_impl)]
@@ -1,6 +1,7 @@
 // Copyright 2024 Ulvetanna Inc.
 
-use std::ops::Deref;
+use alloc::vec::Vec;
+use core::ops::Deref;
 
 use crate::{
 	affine_transformation::{FieldAffineTransformation, Transformation},
@@ -379,8 +380,8 @@ where
 mod tests {
 	use super::*;
 
+	use core::fmt::Debug;
 	use rand::thread_rng;
-	use std::fmt::Debug;
 
 	use crate::{
 		arch::portable::packed_128::{
@@ -7,7 +7,7 @@ use crate::{
 	packed::PackedField,
 	BinaryField128bPolyval,
 };
-use std::{
+use core::{
 	num::Wrapping,
 	ops::{BitXor, Mul},
 };
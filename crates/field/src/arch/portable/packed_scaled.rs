@@ -9,7 +9,7 @@ use crate::{
 	Error, PackedField,
 };
 use bytemuck::{Pod, Zeroable};
-use std::{
+use core::{
 	array,
 	iter::{Product, Sum},
 	ops::{Add, AddAssign, Deref, Mul, MulAssign, Sub, SubAssign},
@@ -27,7 +27,7 @@ impl<PT, const N: usize> ScaledPackedField<PT, N> {
 
 	/// In general case PT != Self::Scalar, so this function has a different name from `PackedField::from_fn`
 	pub fn from_direct_packed_fn(f: impl FnMut(usize) -> PT) -> Self {
-		Self(std::array::from_fn(f))
+		Self(core::array::from_fn(f))
 	}
 }
 
@@ -251,7 +251,7 @@ where
 	}
 
 	fn from_fn(mut f: impl FnMut(usize) -> Self::Scalar) -> Self {
-		Self(std::array::from_fn(|i| PT::from_fn(|j| f(i * PT::WIDTH + j))))
+		Self(core::array::from_fn(|i| PT::from_fn(|j| f(i * PT::WIDTH + j))))
 	}
 }
 
@@ -315,7 +315,7 @@ macro_rules! packed_scaled_field {
 	($name:ident = [$inner:ty;$size:literal]) => {
 		pub type $name = $crate::arch::portable::packed_scaled::ScaledPackedField<$inner, $size>;
 
-		impl std::ops::Add<<$inner as $crate::packed::PackedField>::Scalar> for $name {
+		impl core::ops::Add<<$inner as $crate::packed::PackedField>::Scalar> for $name {
 			type Output = Self;
 
 			fn add(self, rhs: <$inner as $crate::packed::PackedField>::Scalar) -> Self {
@@ -328,7 +328,7 @@ macro_rules! packed_scaled_field {
 			}
 		}
 
-		impl std::ops::AddAssign<<$inner as $crate::packed::PackedField>::Scalar> for $name {
+		impl core::ops::AddAssign<<$inner as $crate::packed::PackedField>::Scalar> for $name {
 			fn add_assign(&mut self, rhs: <$inner as $crate::packed::PackedField>::Scalar) {
 				for i in 0..Self::WIDTH_IN_PT {
 					self.0[i] += rhs;
@@ -336,7 +336,7 @@ macro_rules! packed_scaled_field {
 			}
 		}
 
-		impl std::ops::Sub<<$inner as $crate::packed::PackedField>::Scalar> for $name {
+		impl core::ops::Sub<<$inner as $crate::packed::PackedField>::Scalar> for $name {
 			type Output = Self;
 
 			fn sub(self, rhs: <$inner as $crate::packed::PackedField>::Scalar) -> Self {
@@ -349,7 +349,7 @@ macro_rules! packed_scaled_field {
 			}
 		}
 
-		impl std::ops::SubAssign<<$inner as $crate::packed::PackedField>::Scalar> for $name {
+		impl core::ops::SubAssign<<$inner as $crate::packed::PackedField>::Scalar> for $name {
 			fn sub_assign(&mut self, rhs: <$inner as $crate::packed::PackedField>::Scalar) {
 				for i in 0..Self::WIDTH_IN_PT {
 					self.0[i] -= rhs;
@@ -357,7 +357,7 @@ macro_rules! packed_scaled_field {
 			}
 		}
 
-		impl std::ops::Mul<<$inner as $crate::packed::PackedField>::Scalar> for $name {
+		impl core::ops::Mul<<$inner as $crate::packed::PackedField>::Scalar> for $name {
 			type Output = Self;
 
 			fn mul(self, rhs: <$inner as $crate::packed::PackedField>::Scalar) -> Self {
@@ -370,7 +370,7 @@ macro_rules! packed_scaled_field {
 			}
 		}
 
-		impl std::ops::MulAssign<<$inner as $crate::packed::PackedField>::Scalar> for $name {
+		impl core::ops::MulAssign<<$inner as $crate::packed::PackedField>::Scalar> for $name {
 			fn mul_assign(&mut self, rhs: <$inner as $crate::packed::PackedField>::Scalar) {
 				for i in 0..Self::WIDTH_IN_PT {
 					self.0[i] *= rhs;
@@ -1,6 +1,6 @@
 // Copyright 2024 Ulvetanna Inc.
 
-use std::ops::Deref;
+use core::ops::Deref;
 
 use crate::{
 	affine_transformation::{FieldAffineTransformation, Transformation},
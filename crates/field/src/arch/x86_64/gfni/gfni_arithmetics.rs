@@ -9,7 +9,7 @@ use crate::{
 	underlier::{UnderlierType, WithUnderlier},
 	BinaryField, BinaryField16b, BinaryField32b, BinaryField64b, BinaryField8b, PackedField,
 };
-use std::{array, ops::Deref};
+use core::{array, ops::Deref};
 
 #[rustfmt::skip]
 const TOWER_TO_AES_MAP: i64 = u64::from_le_bytes([
@@ -183,10 +183,10 @@ macro_rules! impl_transformation_with_gfni {
 				>,
 		{
 			type PackedTransformation<
-				Data: std::ops::Deref<Target = [<OP as $crate::packed::PackedField>::Scalar]>,
+				Data: core::ops::Deref<Target = [<OP as $crate::packed::PackedField>::Scalar]>,
 			> = $crate::arch::x86_64::gfni::gfni_arithmetics::GfniTransformation<OP>;
 
-			fn make_packed_transformation<Data: std::ops::Deref<Target = [OP::Scalar]>>(
+			fn make_packed_transformation<Data: core::ops::Deref<Target = [OP::Scalar]>>(
 				transformation: $crate::affine_transformation::FieldAffineTransformation<
 					OP::Scalar,
 					Data,
@@ -277,7 +277,7 @@ impl<IP, OP, U, const BLOCKS: usize> Transformation<IP, OP> for GfniTransformati
 where
 	IP: PackedField + WithUnderlier<Underlier = U>,
 	OP: PackedField + WithUnderlier<Underlier = U>,
-	U: GfniType + TowerSimdType + std::fmt::Debug,
+	U: GfniType + TowerSimdType + core::fmt::Debug,
 {
 	fn transform(&self, data: &IP) -> OP {
 		let packed_values: [OP::Underlier; BLOCKS] = array::from_fn(|i| {
@@ -354,10 +354,10 @@ fn blend_values<T: TowerSimdType>(values: &[T]) -> T {
 macro_rules! impl_transformation_with_gfni_nxn {
 	($name:ty, $blocks:literal) => {
 		impl<OP> $crate::affine_transformation::PackedTransformationFactory<OP> for $name where OP: $crate::packed::PackedBinaryField<Scalar: $crate::underlier::WithUnderlier<Underlier: $crate::arch::x86_64::gfni::gfni_arithmetics::ToLEBytes<$blocks>>> + $crate::underlier::WithUnderlier<Underlier = <$name as $crate::underlier::WithUnderlier>::Underlier> {
-			type PackedTransformation<Data: std::ops::Deref<Target = [<OP as $crate::packed::PackedField>::Scalar]>> =
+			type PackedTransformation<Data: core::ops::Deref<Target = [<OP as $crate::packed::PackedField>::Scalar]>> =
 				$crate::arch::x86_64::gfni::gfni_arithmetics::GfniTransformationNxN::<OP, $blocks>;
 
-			fn make_packed_transformation<Data: std::ops::Deref<Target = [OP::Scalar]>>(transformation: $crate::affine_transformation::FieldAffineTransformation<OP::Scalar, Data>) -> Self::PackedTransformation<Data> {
+			fn make_packed_transformation<Data: core::ops::Deref<Target = [OP::Scalar]>>(transformation: $crate::affine_transformation::FieldAffineTransformation<OP::Scalar, Data>) -> Self::PackedTransformation<Data> {
 				$crate::arch::x86_64::gfni::gfni_arithmetics::GfniTransformationNxN::<OP, $blocks>::new(transformation)
 			}
 		}
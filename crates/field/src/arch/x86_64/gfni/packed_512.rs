@@ -24,7 +24,7 @@ use crate::{
 	BinaryField, BinaryField128b, BinaryField16b, BinaryField1b, BinaryField2b, BinaryField32b,
 	BinaryField4b, BinaryField64b, BinaryField8b,
 };
-use std::arch::x86_64::*;
+use core::arch::x86_64::*;
 
 // Define 128 bit packed field types
 pub type PackedBinaryField512x1b = PackedPrimitiveType<M512, BinaryField1b>;
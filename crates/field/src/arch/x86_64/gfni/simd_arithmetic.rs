@@ -18,7 +18,8 @@ use crate::{
 	underlier::{UnderlierType, WithUnderlier},
 	BinaryField, BinaryField8b, PackedField, TowerField,
 };
-use std::{any::TypeId, arch::x86_64::*, ops::Deref};
+use alloc::vec::Vec;
+use core::{any::TypeId, arch::x86_64::*, ops::Deref};
 
 pub(super) trait TowerSimdType: Sized + Copy {
 	/// Blend odd and even elements
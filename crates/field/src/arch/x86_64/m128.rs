@@ -10,12 +10,12 @@ use crate::{
 	BinaryField,
 };
 use bytemuck::{must_cast, Pod, Zeroable};
-use rand::{Rng, RngCore};
-use seq_macro::seq;
-use std::{
+use core::{
 	arch::x86_64::*,
 	ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr},
 };
+use rand::{Rng, RngCore};
+use seq_macro::seq;
 use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
 
 /// 128-bit value that is used for 128-bit SIMD operations
@@ -27,7 +27,7 @@ impl M128 {
 	pub const fn from_u128(val: u128) -> Self {
 		let mut result = Self::ZERO;
 		unsafe {
-			result.0 = std::mem::transmute_copy(&val);
+			result.0 = core::mem::transmute_copy(&val);
 		}
 
 		result
@@ -276,8 +276,8 @@ impl Random for M128 {
 	}
 }
 
-impl std::fmt::Display for M128 {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for M128 {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
 		let data: u128 = (*self).into();
 		write!(f, "{data:02X?}")
 	}
@@ -10,12 +10,12 @@ use crate::{
 	BinaryField,
 };
 use bytemuck::{must_cast, Pod, Zeroable};
-use rand::{Rng, RngCore};
-use std::{
+use core::{
 	arch::x86_64::*,
 	mem::transmute,
 	ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr},
 };
+use rand::{Rng, RngCore};
 use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
 
 /// 256-bit value that is used for 256-bit SIMD operations
@@ -246,7 +246,7 @@ impl ConditionallySelectable for M256 {
 	fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
 		let a = <[u128; 2]>::from(*a);
 		let b = <[u128; 2]>::from(*b);
-		let result: [u128; 2] = std::array::from_fn(|i| {
+		let result: [u128; 2] = core::array::from_fn(|i| {
 			ConditionallySelectable::conditional_select(&a[i], &b[i], choice)
 		});
 
@@ -261,8 +261,8 @@ impl Random for M256 {
 	}
 }
 
-impl std::fmt::Display for M256 {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for M256 {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
 		let data: [u128; 2] = (*self).into();
 		write!(f, "{data:02X?}")
 	}
@@ -535,14 +535,14 @@ mod tests {
 
 	impl From<ByteData> for M256 {
 		fn from(value: ByteData) -> Self {
-			let vals: [u128; 2] = unsafe { std::mem::transmute(value) };
+			let vals: [u128; 2] = unsafe { core::mem::transmute(value) };
 			vals.into()
 		}
 	}
 
 	impl From<[u128; 2]> for ByteData {
 		fn from(value: [u128; 2]) -> Self {
-			unsafe { std::mem::transmute(value) }
+			unsafe { core::mem::transmute(value) }
 		}
 	}
 
@@ -10,12 +10,13 @@ use crate::{
 	BinaryField,
 };
 use bytemuck::{must_cast, Pod, Zeroable};
-use rand::{Rng, RngCore};
-use std::{
+use core::{
 	arch::x86_64::*,
 	mem::transmute_copy,
 	ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr},
 };
+use rand::{Rng, RngCore};
+use seq_macro::seq;
 use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
 
 /// 512-bit value that is used for 512-bit SIMD operations
@@ -175,17 +176,49 @@ impl Not for M512 {
 	}
 }
 
+/// Shifts by less than 128 bits: whole 64-bit lanes are moved with `_mm512_alignr_epi64` first,
+/// then the remaining bits are shifted within the lanes, carrying the bits that leave each lane
+/// from its lower neighbour.
+/// We have to use macro because the shift counts of the intrinsics should be passed as constants.
+macro_rules! bitshift_left {
+	($val:expr, $count:literal) => {
+		unsafe {
+			let zero = _mm512_setzero_si512();
+			let val = if $count >= 64 {
+				_mm512_alignr_epi64($val, zero, 7)
+			} else {
+				$val
+			};
+			if $count % 64 == 0 {
+				val
+			} else {
+				let carry = _mm512_srli_epi64(_mm512_alignr_epi64(val, zero, 7), 64 - $count % 64);
+				_mm512_or_si512(_mm512_slli_epi64(val, $count % 64), carry)
+			}
+		}
+	};
+}
+
 impl Shl<usize> for M512 {
 	type Output = Self;
 
-	/// TODO: this is not the most efficient implementation
 	#[inline(always)]
 	fn shl(self, rhs: usize) -> Self::Output {
 		match rhs {
 			rhs if rhs >= 512 => Self::ZERO,
 			0 => self,
+			rhs if rhs < 128 => {
+				seq!(N in 1..128 {
+					if rhs == N {
+						return Self(bitshift_left!(self.0, N));
+					}
+				});
+
+				unreachable!()
+			}
 			rhs => {
-				let [mut val_0, mut val_1, mut val_2, mut val_3]: [u128; 4] = self.into();
+				let [mut val_0, mut val_1, mut val_2, _]: [u128; 4] = self.into();
+				let val_3;
 				if rhs >= 384 {
 					val_3 = val_0 << (rhs - 384);
 					val_2 = 0;
@@ -206,16 +239,11 @@ impl Shl<usize> for M512 {
 					val_2 = (val_1 << (rhs - 128)) + (val_0 >> (128usize - (rhs - 128)));
 					val_1 = val_0 << (rhs - 128);
 					val_0 = 0;
-				} else if rhs == 128 {
+				} else {
 					val_3 = val_2;
 					val_2 = val_1;
 					val_1 = val_0;
 					val_0 = 0;
-				} else {
-					val_3 = (val_3 << rhs) + (val_2 >> (128usize - rhs));
-					val_2 = (val_2 << rhs) + (val_1 >> (128usize - rhs));
-					val_1 = (val_1 << rhs) + (val_0 >> (128usize - rhs));
-					val_0 <<= rhs;
 				}
 				[val_0, val_1, val_2, val_3].into()
 			}
@@ -223,17 +251,45 @@ impl Shl<usize> for M512 {
 	}
 }
 
+macro_rules! bitshift_right {
+	($val:expr, $count:literal) => {
+		unsafe {
+			let zero = _mm512_setzero_si512();
+			let val = if $count >= 64 {
+				_mm512_alignr_epi64(zero, $val, 1)
+			} else {
+				$val
+			};
+			if $count % 64 == 0 {
+				val
+			} else {
+				let carry = _mm512_slli_epi64(_mm512_alignr_epi64(zero, val, 1), 64 - $count % 64);
+				_mm512_or_si512(_mm512_srli_epi64(val, $count % 64), carry)
+			}
+		}
+	};
+}
+
 impl Shr<usize> for M512 {
 	type Output = Self;
 
-	/// TODO: this is not the most efficient implementation
 	#[inline(always)]
 	fn shr(self, rhs: usize) -> Self::Output {
 		match rhs {
 			rhs if rhs >= 512 => Self::ZERO,
 			0 => self,
+			rhs if rhs < 128 => {
+				seq!(N in 1..128 {
+					if rhs == N {
+						return Self(bitshift_right!(self.0, N));
+					}
+				});
+
+				unreachable!()
+			}
 			rhs => {
-				let [mut val_0, mut val_1, mut val_2, mut val_3]: [u128; 4] = self.into();
+				let [_, mut val_1, mut val_2, mut val_3]: [u128; 4] = self.into();
+				let val_0;
 				if rhs >= 384 {
 					val_0 = val_3 >> (rhs - 384);
 					val_1 = 0;
@@ -254,16 +310,11 @@ impl Shr<usize> for M512 {
 					val_1 = (val_2 >> (rhs - 128)) + (val_3 << (128usize - (rhs - 128)));
 					val_2 = val_3 >> (rhs - 128);
 					val_3 = 0;
-				} else if rhs == 128 {
+				} else {
 					val_0 = val_1;
 					val_1 = val_2;
 					val_2 = val_3;
 					val_3 = 0;
-				} else {
-					val_0 = (val_0 >> rhs) + (val_1 << (128usize - rhs));
-					val_1 = (val_1 >> rhs) + (val_2 << (128usize - rhs));
-					val_2 = (val_2 >> rhs) + (val_3 << (128usize - rhs));
-					val_3 >>= rhs;
 				}
 				[val_0, val_1, val_2, val_3].into()
 			}
@@ -297,7 +348,7 @@ impl ConditionallySelectable for M512 {
 	fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
 		let a = <[u128; 4]>::from(*a);
 		let b = <[u128; 4]>::from(*b);
-		let result: [u128; 4] = std::array::from_fn(|i| {
+		let result: [u128; 4] = core::array::from_fn(|i| {
 			ConditionallySelectable::conditional_select(&a[i], &b[i], choice)
 		});
 
@@ -312,8 +363,8 @@ impl Random for M512 {
 	}
 }
 
-impl std::fmt::Display for M512 {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for M512 {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
 		let data: [u128; 4] = (*self).into();
 		write!(f, "{data:02X?}")
 	}
@@ -432,7 +483,6 @@ where
 	}
 }
 
-// TODO: Add efficient interleave specialization for 512-bit values
 impl UnderlierWithBitConstants for M512 {
 	const INTERLEAVE_EVEN_MASK: &'static [Self] = &[
 		Self::from_equal_u128s(interleave_mask_even!(u128, 0)),
@@ -517,6 +567,22 @@ unsafe fn interleave_bits(a: __m512i, b: __m512i, log_block_len: usize) -> (__m5
 			let b_prime = _mm512_unpackhi_epi64(a, b);
 			(a_prime, b_prime)
 		}
+		// Blocks of 128 and 256 bits span several 64-bit lanes, so we pick the lanes of each output
+		// from `a` (indices 0..8) and `b` (indices 8..16) with a single two-source permutation.
+		7 => {
+			let a_prime =
+				_mm512_permutex2var_epi64(a, _mm512_set_epi64(13, 12, 5, 4, 9, 8, 1, 0), b);
+			let b_prime =
+				_mm512_permutex2var_epi64(a, _mm512_set_epi64(15, 14, 7, 6, 11, 10, 3, 2), b);
+			(a_prime, b_prime)
+		}
+		8 => {
+			let a_prime =
+				_mm512_permutex2var_epi64(a, _mm512_set_epi64(11, 10, 9, 8, 3, 2, 1, 0), b);
+			let b_prime =
+				_mm512_permutex2var_epi64(a, _mm512_set_epi64(15, 14, 13, 12, 7, 6, 5, 4), b);
+			(a_prime, b_prime)
+		}
 		_ => panic!("unsupported block length"),
 	}
 }
@@ -536,7 +602,6 @@ unsafe fn interleave_bits_imm<const BLOCK_LEN: u32>(
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::underlier::single_element_mask_bits;
 	use proptest::{arbitrary::any, proptest};
 
 	fn check_roundtrip<T>(val: M512)
@@ -574,14 +639,14 @@ mod tests {
 
 	impl From<ByteData> for M512 {
 		fn from(value: ByteData) -> Self {
-			let vals: [u128; 4] = unsafe { std::mem::transmute(value) };
+			let vals: [u128; 4] = unsafe { core::mem::transmute(value) };
 			vals.into()
 		}
 	}
 
 	impl From<[u128; 4]> for ByteData {
 		fn from(value: [u128; 4]) -> Self {
-			unsafe { std::mem::transmute(value) }
+			unsafe { core::mem::transmute(value) }
 		}
 	}
 
@@ -635,27 +700,29 @@ mod tests {
 		}
 
 		#[test]
-		fn test_shifts(a in any::<[u128; 4]>(), rhs in 0..255usize) {
+		fn test_shifts(a in any::<[u128; 4]>(), rhs in 0..512usize) {
 			assert_eq!(M512::from(a) << rhs, M512::from(ByteData::from(a) << rhs));
 			assert_eq!(M512::from(a) >> rhs, M512::from(ByteData::from(a) >> rhs));
 		}
 
 		#[test]
-		fn test_interleave_bits(a in any::<[u128; 4]>(), b in any::<[u128; 4]>(), height in 0usize..7) {
+		fn test_interleave_bits(a in any::<[u128; 4]>(), b in any::<[u128; 4]>(), height in 0usize..9) {
 			let a = M512::from(a);
 			let b = M512::from(b);
 			let (c, d) = unsafe {interleave_bits(a.0, b.0, height)};
 			let (c, d) = (M512::from(c), M512::from(d));
 
+			// Compare bit by bit, so that blocks wider than 128 bits are fully checked.
 			let block_len = 1usize << height;
-			let get = |v, i| {
-				u128::num_cast_from((v >> (i * block_len)) & single_element_mask_bits::<M512>(1 << height))
-			};
+			let get = |v: M512, i| ByteData::from(<[u128; 4]>::from(v)).get_bit(i);
 			for i in (0..512/block_len).step_by(2) {
-				assert_eq!(get(c, i), get(a, i));
-				assert_eq!(get(c, i+1), get(b, i));
-				assert_eq!(get(d, i), get(a, i+1));
-				assert_eq!(get(d, i+1), get(b, i+1));
+				for j in 0..block_len {
+					let (even, odd) = (i * block_len + j, (i + 1) * block_len + j);
+					assert_eq!(get(c, even), get(a, even));
+					assert_eq!(get(c, odd), get(b, even));
+					assert_eq!(get(d, even), get(a, odd));
+					assert_eq!(get(d, odd), get(b, odd));
+				}
 			}
 		}
 	}
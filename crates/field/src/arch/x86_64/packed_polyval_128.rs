@@ -3,12 +3,11 @@ use super::{
 	m128::M128,
 };
 use crate::{
-	arch::ReuseMultiplyStrategy,
-	arithmetic_traits::{impl_square_with, InvertOrZero},
+	arithmetic_traits::{InvertOrZero, Square},
 	packed::PackedField,
 	BinaryField128bPolyval,
 };
-use std::{arch::x86_64::*, ops::Mul};
+use core::{arch::x86_64::*, ops::Mul};
 
 pub type PackedBinaryPolyval1x128b = PackedPrimitiveType<M128, BinaryField128bPolyval>;
 
@@ -28,8 +27,11 @@ impl Mul for PackedBinaryPolyval1x128b {
 }
 
 // Define square
-// TODO: implement a more optimal version for square case
-impl_square_with!(PackedBinaryPolyval1x128b @ ReuseMultiplyStrategy);
+impl Square for PackedBinaryPolyval1x128b {
+	fn square(self) -> Self {
+		unsafe { simd_montgomery_square(self.0) }.into()
+	}
+}
 
 // Define invert
 // TODO: implement vectorized version that uses packed multiplication
@@ -72,7 +74,27 @@ pub(super) unsafe fn simd_montgomery_multiply<T: PolyvalSimdType>(h: T, y: T) ->
 	let v2 = T::xor(t1, T::shuffle_epi32::<0x0E>(t2));
 	let v3 = T::shuffle_epi32::<0x0E>(t1);
 
-	// Polynomial reduction
+	simd_montgomery_reduce(v0, v1, v2, v3)
+}
+
+/// Squares `x` in Montgomery form. The cross terms of a square vanish in characteristic 2, so the
+/// 256-bit product only needs the two diagonal `clmul`s, followed by the usual reduction.
+#[inline]
+pub(super) unsafe fn simd_montgomery_square<T: PolyvalSimdType>(x: T) -> T {
+	let t0 = T::clmul_epi64::<0x00>(x, x);
+	let t1 = T::clmul_epi64::<0x11>(x, x);
+	let v0 = t0;
+	let v1 = T::shuffle_epi32::<0x0E>(t0);
+	let v2 = t1;
+	let v3 = T::shuffle_epi32::<0x0E>(t1);
+
+	simd_montgomery_reduce(v0, v1, v2, v3)
+}
+
+/// Reduces the 256-bit product given as 64-bit limbs `v0..v3` (in the low half of each value),
+/// dividing it by `x^128` modulo the POLYVAL polynomial.
+#[inline(always)]
+unsafe fn simd_montgomery_reduce<T: PolyvalSimdType>(v0: T, v1: T, v2: T, v3: T) -> T {
 	let v2 = xor5(v2, v0, T::srli_epi64::<1>(v0), T::srli_epi64::<2>(v0), T::srli_epi64::<7>(v0));
 
 	let v1 = xor4(v1, T::slli_epi64::<63>(v0), T::slli_epi64::<62>(v0), T::slli_epi64::<57>(v0));
@@ -125,3 +147,27 @@ unsafe fn xor4<T: PolyvalSimdType>(e1: T, e2: T, e3: T, e4: T) -> T {
 unsafe fn xor5<T: PolyvalSimdType>(e1: T, e2: T, e3: T, e4: T, e5: T) -> T {
 	T::xor(e1, T::xor(T::xor(e2, e3), T::xor(e4, e5)))
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use proptest::{arbitrary::any, prop_oneof, proptest, strategy::Just};
+
+	type PortablePolyval =
+		super::super::super::portable::packed_polyval_128::PackedBinaryPolyval1x128b;
+
+	proptest! {
+		#[test]
+		fn test_square_matches_multiply(a in any::<u128>()) {
+			let a = PackedBinaryPolyval1x128b::from(a);
+			assert_eq!(Square::square(a), a * a);
+		}
+
+		#[test]
+		fn test_invert_or_zero_matches_portable(a in prop_oneof![Just(0u128), any::<u128>()]) {
+			let expected = PackedField::invert_or_zero(PortablePolyval::from(a));
+			let result = PackedField::invert_or_zero(PackedBinaryPolyval1x128b::from(a));
+			assert_eq!(u128::from(M128::from(result)), u128::from(expected));
+		}
+	}
+}
@@ -12,8 +12,7 @@ use crate::{
 	arithmetic_traits::{impl_invert_with, impl_square_with},
 	BinaryField128bPolyval,
 };
-use core::arch::x86_64::*;
-use std::ops::Mul;
+use core::{arch::x86_64::*, ops::Mul};
 
 /// Define packed type
 pub type PackedBinaryPolyval2x128b = PackedPrimitiveType<M256, BinaryField128bPolyval>;
@@ -12,9 +12,8 @@ use crate::{
 	arithmetic_traits::{impl_invert_with, impl_square_with},
 	BinaryField128bPolyval,
 };
-use core::arch::x86_64::*;
+use core::{arch::x86_64::*, ops::Mul};
 use seq_macro::seq;
-use std::ops::Mul;
 
 /// Define packed type
 pub type PackedBinaryPolyval4x128b = PackedPrimitiveType<M512, BinaryField128bPolyval>;
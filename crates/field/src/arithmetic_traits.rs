@@ -1,6 +1,6 @@
 // Copyright 2024 Ulvetanna Inc.
 
-use std::ops::Deref;
+use core::ops::Deref;
 
 use crate::{
 	affine_transformation::{FieldAffineTransformation, Transformation},
@@ -38,7 +38,7 @@ pub trait TaggedMul<Strategy> {
 
 macro_rules! impl_mul_with {
 	($name:ident @ $strategy:ty) => {
-		impl std::ops::Mul for $name {
+		impl core::ops::Mul for $name {
 			type Output = Self;
 
 			#[inline]
@@ -48,7 +48,7 @@ macro_rules! impl_mul_with {
 		}
 	};
 	($name:ty => $bigger:ty) => {
-		impl std::ops::Mul for $name {
+		impl core::ops::Mul for $name {
 			type Output = Self;
 
 			#[inline]
@@ -161,13 +161,13 @@ macro_rules! impl_transformation_with_strategy {
 					Underlier = <$name as $crate::underlier::WithUnderlier>::Underlier,
 				>,
 		{
-			type PackedTransformation<Data: std::ops::Deref<Target = [OP::Scalar]>> =
+			type PackedTransformation<Data: core::ops::Deref<Target = [OP::Scalar]>> =
 				<Self as $crate::arithmetic_traits::TaggedPackedTransformationFactory<
 					$strategy,
 					OP,
 				>>::PackedTransformation<Data>;
 
-			fn make_packed_transformation<Data: std::ops::Deref<Target = [OP::Scalar]>>(
+			fn make_packed_transformation<Data: core::ops::Deref<Target = [OP::Scalar]>>(
 				transformation: $crate::affine_transformation::FieldAffineTransformation<
 					OP::Scalar,
 					Data,
@@ -9,14 +9,14 @@ use bytemuck::{
 	must_cast_slice, must_cast_slice_mut, try_cast_slice, try_cast_slice_mut, Pod, Zeroable,
 };
 use cfg_if::cfg_if;
-use ff::Field;
-use rand::RngCore;
-use std::{
+use core::{
 	array,
 	fmt::{Display, Formatter},
 	iter::{Product, Step, Sum},
 	ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
 };
+use ff::Field;
+use rand::RngCore;
 use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
 
 /// A finite field with characteristic 2.
@@ -245,7 +245,7 @@ macro_rules! binary_field {
 		}
 
 		impl Display for $name {
-			fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+			fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
 				write!(f, "0x{repr:0>width$x}", repr=self.val(), width=Self::N_BITS.max(4) / 4)
 			}
 		}
@@ -1,7 +1,7 @@
 // Copyright 2023 Ulvetanna Inc.
 
 use super::{error::Error, Field};
-use std::{
+use core::{
 	iter,
 	ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign},
 };
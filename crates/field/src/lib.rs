@@ -1,6 +1,11 @@
 // Copyright 2023 Ulvetanna Inc.
 #![feature(step_trait)]
 #![cfg_attr(target_arch = "x86_64", feature(stdarch_x86_avx512))]
+// The field arithmetic only needs `core` and `alloc`, `std` is only required for the rayon based
+// helpers and the `std::error::Error` impls of the error types.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 pub mod aes_field;
 pub mod affine_transformation;
@@ -1,8 +1,8 @@
 #[macro_export]
 macro_rules! impl_packed_field_display {
 	($name:ident) => {
-		impl std::fmt::Display for $name {
-			fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		impl core::fmt::Display for $name {
+			fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
 				write!(f, "{{")?;
 				let mut iter = self.iter();
 				if let Some(scalar) = iter.next() {
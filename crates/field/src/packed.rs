@@ -12,13 +12,13 @@ use super::{
 	Error,
 };
 use binius_utils::iter::IterExtensions;
-use ff::Field;
-use rand::RngCore;
-use std::{
+use core::{
 	fmt::Debug,
 	iter::{self, Product, Sum},
 	ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign},
 };
+use ff::Field;
+use rand::RngCore;
 use subtle::ConstantTimeEq;
 
 /// A packed field represents a vector of underlying field elements.
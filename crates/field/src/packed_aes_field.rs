@@ -304,8 +304,8 @@ mod test_utils {
 mod tests {
 	use super::test_utils::{define_invert_tests, define_multiply_tests, define_square_tests};
 	use crate::PackedField;
+	use core::ops::Mul;
 	use proptest::prelude::*;
-	use std::ops::Mul;
 
 	define_multiply_tests!(Mul::mul, PackedField);
 
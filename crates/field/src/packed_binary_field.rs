@@ -61,7 +61,7 @@ pub mod test_utils {
 
 			impl<T> TestMulTrait<$crate::packed_binary_field::test_utils::Unit> for T {}
 
-			struct TestMult<T>(std::marker::PhantomData<T>);
+			struct TestMult<T>(core::marker::PhantomData<T>);
 
 			impl<T: $constraint + PackedField> TestMult<T> {
 				fn test_mul(a: T, b: T) {
@@ -85,7 +85,7 @@ pub mod test_utils {
 
 			impl<T> TestSquareTrait<$crate::packed_binary_field::test_utils::Unit> for T {}
 
-			struct TestSquare<T>(std::marker::PhantomData<T>);
+			struct TestSquare<T>(core::marker::PhantomData<T>);
 
 			impl<T: $constraint + PackedField> TestSquare<T> {
 				fn test_square(a: T) {
@@ -109,7 +109,7 @@ pub mod test_utils {
 
 			impl<T> TestInvertTrait<$crate::packed_binary_field::test_utils::Unit> for T {}
 
-			struct TestInvert<T>(std::marker::PhantomData<T>);
+			struct TestInvert<T>(core::marker::PhantomData<T>);
 
 			impl<T: $constraint + PackedField> TestInvert<T> {
 				fn test_invert(a: T) {
@@ -140,7 +140,7 @@ pub mod test_utils {
 
 			impl<T> TestMulAlphaTrait<$crate::packed_binary_field::test_utils::Unit> for T {}
 
-			struct TestMulAlpha<T>(std::marker::PhantomData<T>);
+			struct TestMulAlpha<T>(core::marker::PhantomData<T>);
 
 			impl<T: $constraint + PackedField> TestMulAlpha<T>
 			where
@@ -169,7 +169,7 @@ pub mod test_utils {
 
 			impl<T> TestTransformationTrait<$crate::packed_binary_field::test_utils::Unit> for T {}
 
-			struct TestTransformation<T>(std::marker::PhantomData<T>);
+			struct TestTransformation<T>(core::marker::PhantomData<T>);
 
 			impl<T: $constraint + PackedField> TestTransformation<T> {
 				fn test_transformation(a: T) {
@@ -685,9 +685,9 @@ mod tests {
 		affine_transformation::PackedTransformationFactory, arithmetic_traits::MulAlpha,
 		BinaryField8b, Field, PackedField,
 	};
+	use core::{iter::repeat_with, ops::Mul};
 	use proptest::prelude::*;
 	use rand::{rngs::StdRng, thread_rng, SeedableRng};
-	use std::{iter::repeat_with, ops::Mul};
 
 	fn test_add_packed<P: PackedField + From<u128>>(a_val: u128, b_val: u128) {
 		let a = P::from(a_val);
@@ -786,6 +786,40 @@ mod tests {
 		}
 	}
 
+	fn test_serde_roundtrip<P>(a_val: u128)
+	where
+		P: From<u128>
+			+ PartialEq
+			+ core::fmt::Debug
+			+ serde::Serialize
+			+ serde::de::DeserializeOwned,
+	{
+		let a = P::from(a_val);
+		let bytes = bincode::serialize(&a).unwrap();
+		// bincode prefixes the byte string with its length as a u64
+		assert_eq!(bytes[..8], 16u64.to_le_bytes());
+		assert_eq!(bytes[8..], a_val.to_le_bytes());
+		assert_eq!(bincode::deserialize::<P>(&bytes).unwrap(), a);
+	}
+
+	proptest! {
+		#[test]
+		fn test_serde_roundtrip_16x8b(a_val in any::<u128>()) {
+			test_serde_roundtrip::<PackedBinaryField16x8b>(a_val)
+		}
+
+		#[test]
+		fn test_serde_roundtrip_polyval_1x128b(a_val in any::<u128>()) {
+			test_serde_roundtrip::<crate::arch::packed_polyval_128::PackedBinaryPolyval1x128b>(a_val)
+		}
+	}
+
+	#[test]
+	fn test_serde_rejects_wrong_length() {
+		let bytes = bincode::serialize(&[0u8; 15].as_slice()).unwrap();
+		assert!(bincode::deserialize::<PackedBinaryField16x8b>(&bytes).is_err());
+	}
+
 	#[test]
 	fn test_mul_packed_256x1b() {
 		test_mul_packed_random::<PackedBinaryField256x1b>(thread_rng())
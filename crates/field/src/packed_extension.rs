@@ -1,8 +1,8 @@
 // Copyright 2023-2024 Ulvetanna Inc.
 
 use crate::{ExtensionField, PackedField};
+use core::slice;
 use ff::Field;
-use std::slice;
 
 /// Trait represents a relationship between a packed struct of field elements and a packed struct
 /// of elements from an extension field.
@@ -15,15 +15,16 @@ use crate::{
 	packed::PackedField,
 	underlier::UnderlierType,
 };
+use alloc::vec::Vec;
 use bytemuck::{Pod, Zeroable};
-use ff::Field;
-use rand::{Rng, RngCore};
-use std::{
+use core::{
 	array,
 	fmt::{self, Display, Formatter},
 	iter::{Product, Sum},
 	ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
 };
+use ff::Field;
+use rand::{Rng, RngCore};
 use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Zeroable)]
@@ -31,6 +32,11 @@ use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
 pub struct BinaryField128bPolyval(pub(crate) u128);
 
 impl BinaryField128bPolyval {
+	/// Creates the element whose canonical value is `value`, converting it to the Montgomery form
+	/// the element is stored in.
+	///
+	/// This is what `Display` prints back. `From<u128>` instead takes the stored Montgomery form
+	/// as is, e.g. to recover an element from its underlier.
 	pub fn new(value: u128) -> Self {
 		Self(value).to_montgomery()
 	}
@@ -194,8 +200,14 @@ impl Field for BinaryField128bPolyval {
 		CtOption::new(InvertOrZero::invert_or_zero(*self), self.ct_ne(&Self::ZERO))
 	}
 
-	fn sqrt_ratio(_num: &Self, _div: &Self) -> (Choice, Self) {
-		todo!()
+	// In characteristic 2 squaring is the Frobenius automorphism, so every element has a unique
+	// square root, x^(2^127). Following the ff convention, the returned choice is false only for a
+	// non-zero `num` over a zero `div`, in which case the root is zero.
+	fn sqrt_ratio(num: &Self, div: &Self) -> (Choice, Self) {
+		let ratio = *num * InvertOrZero::invert_or_zero(*div);
+		let sqrt = (0..127).fold(ratio, |x, _| x.square());
+
+		(num.is_zero() | !div.is_zero(), sqrt)
 	}
 }
 
@@ -205,12 +217,30 @@ impl InvertOrZero for BinaryField128bPolyval {
 	}
 }
 
+/// Reinterprets `value` as the Montgomery form of an element, without any conversion. Use
+/// [`BinaryField128bPolyval::new`] to create an element from its canonical value.
 impl From<u128> for BinaryField128bPolyval {
 	fn from(value: u128) -> Self {
 		Self(value)
 	}
 }
 
+/// Unlike `From<u128>`, the smaller integers are taken as canonical values and converted to
+/// Montgomery form through [`BinaryField128bPolyval::new`], so `from(1u32)` is `ONE`.
+macro_rules! impl_from_canonical_uint {
+	($($uint:ty),*) => {
+		$(
+			impl From<$uint> for BinaryField128bPolyval {
+				fn from(value: $uint) -> Self {
+					Self::new(u128::from(value))
+				}
+			}
+		)*
+	};
+}
+
+impl_from_canonical_uint!(u8, u16, u32, u64);
+
 impl From<BinaryField128bPolyval> for u128 {
 	fn from(value: BinaryField128bPolyval) -> Self {
 		value.0
@@ -236,6 +266,30 @@ impl BinaryField128bPolyval {
 	}
 }
 
+/// Inverts every element of the slice in place, leaving zeros as zero.
+///
+/// Uses Montgomery's trick: the prefix products of the nonzero elements are accumulated, their
+/// product is inverted once, and a backward pass recovers each inverse with two multiplications.
+pub fn batch_invert_or_zero(elems: &mut [BinaryField128bPolyval]) {
+	let mut prefix_products = Vec::with_capacity(elems.len());
+	let mut acc = BinaryField128bPolyval::ONE;
+	for elem in elems.iter() {
+		prefix_products.push(acc);
+		if *elem != BinaryField128bPolyval::ZERO {
+			acc *= *elem;
+		}
+	}
+
+	let mut acc_inv = InvertOrZero::invert_or_zero(acc);
+	for (elem, prefix_product) in elems.iter_mut().zip(prefix_products).rev() {
+		if *elem != BinaryField128bPolyval::ZERO {
+			let inv = acc_inv * prefix_product;
+			acc_inv *= *elem;
+			*elem = inv;
+		}
+	}
+}
+
 unsafe impl Pod for BinaryField128bPolyval {}
 
 impl TryInto<BinaryField1b> for BinaryField128bPolyval {
@@ -656,7 +710,27 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn test_from_small_uint() {
+		assert_eq!(BinaryField128bPolyval::from(1u8), BinaryField128bPolyval::ONE);
+		assert_eq!(BinaryField128bPolyval::from(1u16), BinaryField128bPolyval::ONE);
+		assert_eq!(BinaryField128bPolyval::from(1u32), BinaryField128bPolyval::ONE);
+		assert_eq!(BinaryField128bPolyval::from(1u64), BinaryField128bPolyval::ONE);
+		assert_eq!(BinaryField128bPolyval::from(0u32), BinaryField128bPolyval::ZERO);
+
+		// The raw conversion does not apply the Montgomery form.
+		assert_ne!(BinaryField128bPolyval::from(1u128), BinaryField128bPolyval::ONE);
+	}
+
 	proptest! {
+		#[test]
+		fn test_from_small_uint_matches_new(v in any::<u64>()) {
+			let expected = BinaryField128bPolyval::new(v as u128);
+			assert_eq!(BinaryField128bPolyval::from(v), expected);
+			assert_eq!(BinaryField128bPolyval::from(v as u32), BinaryField128bPolyval::new(v as u32 as u128));
+			assert_eq!(format!("{}", BinaryField128bPolyval::from(v)), format!("0x{:0>32x}", v));
+		}
+
 		#[test]
 		fn test_multiplicative_identity(v in any::<u128>()) {
 			let v = BinaryField128bPolyval::new(v);
@@ -686,6 +760,37 @@ mod tests {
 		assert!(is_binary_field_valid_generator::<BinaryField128bPolyval>());
 	}
 
+	#[test]
+	fn test_sqrt_ratio_zero() {
+		let a = BinaryField128bPolyval::new(0x2a9055e4e69a61f0b5cfd6f4161087ba);
+		let (is_valid, sqrt) = Field::sqrt_ratio(&BinaryField128bPolyval::ZERO, &a);
+		assert!(bool::from(is_valid));
+		assert_eq!(sqrt, BinaryField128bPolyval::ZERO);
+
+		let (is_valid, sqrt) = Field::sqrt_ratio(&a, &BinaryField128bPolyval::ZERO);
+		assert!(!bool::from(is_valid));
+		assert_eq!(sqrt, BinaryField128bPolyval::ZERO);
+	}
+
+	proptest! {
+		#[test]
+		fn test_sqrt_ratio_of_square(a_val in any::<u128>()) {
+			let a = BinaryField128bPolyval::new(a_val);
+			let (is_valid, sqrt) = Field::sqrt_ratio(&a.square(), &BinaryField128bPolyval::ONE);
+			assert!(bool::from(is_valid));
+			assert_eq!(sqrt, a);
+		}
+
+		#[test]
+		fn test_sqrt_ratio_squares_back(num_val in any::<u128>(), div_val in 1u128..) {
+			let num = BinaryField128bPolyval::new(num_val);
+			let div = BinaryField128bPolyval::new(div_val);
+			let (is_valid, sqrt) = Field::sqrt_ratio(&num, &div);
+			assert!(bool::from(is_valid));
+			assert_eq!(sqrt.square(), num * div.invert().unwrap());
+		}
+	}
+
 	proptest! {
 		#[test]
 		fn test_to_from_tower_basis(a_val in any::<u128>(), b_val in any::<u128>()) {
@@ -715,6 +820,18 @@ mod tests {
 		}
 	}
 
+	proptest! {
+		#[test]
+		fn test_batch_invert_or_zero(
+			vals in prop::collection::vec(prop_oneof![Just(0u128), any::<u128>()], 0..64)
+		) {
+			let mut elems: Vec<_> = vals.into_iter().map(BinaryField128bPolyval::new).collect();
+			let expected: Vec<_> = elems.iter().map(|&x| InvertOrZero::invert_or_zero(x)).collect();
+			batch_invert_or_zero(&mut elems);
+			assert_eq!(elems, expected);
+		}
+	}
+
 	/// Test that `invert` method properly wraps `invert_or_zero`
 	#[test]
 	fn test_invert() {
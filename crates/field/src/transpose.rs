@@ -1,18 +1,32 @@
 // Copyright 2023 Ulvetanna Inc.
 
 use super::{packed::PackedField, ExtensionField, PackedExtensionField, PackedFieldIndexable};
+use alloc::string::{String, ToString};
 use p3_util::log2_strict_usize;
 
 /// Error thrown when a transpose operation fails.
-#[derive(Clone, thiserror::Error, Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
 pub enum Error {
-	#[error("power of two size required for the field extension degree")]
+	#[cfg_attr(
+		feature = "std",
+		error("power of two size required for the field extension degree")
+	)]
 	PowerOfTwoExtensionDegreeRequired,
-	#[error("the \"{param}\" argument's size is invalid: {msg}")]
+	#[cfg_attr(
+		feature = "std",
+		error("the \"{param}\" argument's size is invalid: {msg}")
+	)]
 	InvalidBufferSize { param: &'static str, msg: String },
-	#[error("dimension n of square blocks must divide packing width")]
+	#[cfg_attr(
+		feature = "std",
+		error("dimension n of square blocks must divide packing width")
+	)]
 	SquareBlockDimensionMustDivideWidth,
-	#[error("destination buffer must be castable to a packed extension field buffer")]
+	#[cfg_attr(
+		feature = "std",
+		error("destination buffer must be castable to a packed extension field buffer")
+	)]
 	UnalignedDestination,
 }
 
@@ -2,16 +2,16 @@
 
 use binius_utils::checked_arithmetics::{checked_div, checked_log_2};
 use bytemuck::Zeroable;
+use core::{
+	fmt::{Debug, Display, LowerHex},
+	hash::{Hash, Hasher},
+	ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr},
+};
 use derive_more::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign};
 use rand::{
 	distributions::{Distribution, Standard, Uniform},
 	Rng, RngCore,
 };
-use std::{
-	fmt::{Debug, Display, LowerHex},
-	hash::{Hash, Hasher},
-	ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr},
-};
 use subtle::{ConditionallySelectable, ConstantTimeEq};
 
 /// Primitive integer underlying a binary field or packed binary field implementation.
@@ -238,19 +238,19 @@ impl<const N: usize> SmallU<N> {
 }
 
 impl<const N: usize> Debug for SmallU<N> {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
 		Debug::fmt(&self.val(), f)
 	}
 }
 
 impl<const N: usize> Display for SmallU<N> {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
 		Display::fmt(&self.val(), f)
 	}
 }
 
 impl<const N: usize> LowerHex for SmallU<N> {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
 		LowerHex::fmt(&self.0, f)
 	}
 }
@@ -485,8 +485,8 @@ impl_num_cast!(U1, U2, U4, u8, u16, u32, u64, u128,);
 mod tests {
 	use super::*;
 	use crate::{BinaryField32b, Field};
+	use core::iter::Step;
 	use proptest::{arbitrary::any, bits, proptest};
-	use std::iter::Step;
 
 	#[test]
 	fn test_from_fn() {
@@ -1,6 +1,9 @@
 // Copyright 2024 Ulvetanna Inc.
 
-use crate::{packed::get_packed_slice, ExtensionField, Field, PackedField};
+#[cfg(feature = "std")]
+use crate::packed::get_packed_slice;
+use crate::{ExtensionField, Field, PackedField};
+#[cfg(feature = "std")]
 use rayon::prelude::*;
 
 /// Computes the inner product of two vectors without checking that the lengths are equal
@@ -12,6 +15,7 @@ where
 	a.zip(b).map(|(a_i, b_i)| a_i * b_i).sum::<FE>()
 }
 
+#[cfg(feature = "std")]
 pub fn inner_product_par<FX, PX, PY>(xs: &[PX], ys: &[PY]) -> FX
 where
 	PX: PackedField<Scalar = FX>,
@@ -0,0 +1,23 @@
+// Copyright 2024 Ulvetanna Inc.
+
+use std::{path::Path, process::Command};
+
+/// The portable field arithmetic must keep compiling without the `std` feature, so that embedded
+/// and WASM users can depend on the crate with `default-features = false`.
+#[test]
+fn test_builds_without_default_features() {
+	let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+	// A separate target directory keeps the nested build from contending for the lock on the one
+	// running this test.
+	let target_dir = manifest_dir.join("../../target/no_std_build");
+
+	let status = Command::new(env!("CARGO"))
+		.args(["build", "--lib", "--no-default-features", "--manifest-path"])
+		.arg(manifest_dir.join("Cargo.toml"))
+		.arg("--target-dir")
+		.arg(target_dir)
+		.status()
+		.expect("failed to run cargo");
+
+	assert!(status.success(), "binius_field does not build with --no-default-features");
+}
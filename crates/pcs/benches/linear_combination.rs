@@ -0,0 +1,30 @@
+use binius_field::{BinaryField128b, BinaryField32b, Field};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use pcs::{linear_combination, linear_combination_chunked, LINEAR_COMBINATION_CHUNK_SIZE};
+use rand::thread_rng;
+
+//Number of terms, about as many as the rows*cols entries a prover folds for a polynomial of 2^25 coefficients.
+const LOG_LEN:usize = 20;
+
+//Times linear_combination_chunked at LINEAR_COMBINATION_CHUNK_SIZE and chunk sizes 4 times smaller and larger, next to linear_combination,
+//whose tasks parallel_xor_sum keeps to XOR_SUM_MIN_LEN terms.
+fn bench_linear_combination(c: &mut Criterion){
+    let mut rng = thread_rng();
+    let scalars:Vec<BinaryField128b> = (0..1<<LOG_LEN).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let vals:Vec<BinaryField32b> = (0..1<<LOG_LEN).map(|_| BinaryField32b::random(&mut rng)).collect();
+
+    let mut group = c.benchmark_group("linear_combination");
+    group.throughput(Throughput::Elements(1<<LOG_LEN));
+
+    group.bench_function("unchunked", |bench| bench.iter(|| linear_combination(&scalars, &vals)));
+    for chunk_size in [LINEAR_COMBINATION_CHUNK_SIZE>>2, LINEAR_COMBINATION_CHUNK_SIZE, LINEAR_COMBINATION_CHUNK_SIZE<<2]{
+        group.bench_with_input(BenchmarkId::new("chunked", chunk_size), &chunk_size, |bench, &chunk_size|{
+            bench.iter(|| linear_combination_chunked(&scalars, &vals, chunk_size))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(linear_combination_benches, bench_linear_combination);
+criterion_main!(linear_combination_benches);
@@ -1,4 +1,12 @@
+#[macro_use]
+mod macros;
 mod utils;
 mod prover;
 mod verifier;
-mod test;
\ No newline at end of file
+mod pcs;
+mod test;
+
+pub use pcs::Pcs;
+pub use prover::ProveError;
+pub use utils::{encoding::{Code, EncodingParams}, merkle::MerkleTree, Commitment, EvalProof};
+pub use verifier::{linear_combination, linear_combination_chunked, VerifyError, LINEAR_COMBINATION_CHUNK_SIZE};
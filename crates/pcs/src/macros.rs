@@ -0,0 +1,85 @@
+//Evaluates an expression inside a tracing span of the given name when the tracing feature is enabled, otherwise just evaluates the expression.
+macro_rules! traced {
+    ($name:literal, $body:expr) => {{
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($name).entered();
+        $body
+    }};
+}
+
+//Entry points into rayon's parallel iterators. With the parallel feature disabled they expand to the std serial iterators instead, e.g for deterministic
+//single threaded profiling or wasm32 targets without threads, so whatever is chained onto them has to be available on both.
+#[cfg(feature = "parallel")]
+macro_rules! par_iter {
+    ($e:expr) => {{
+        use rayon::iter::IntoParallelRefIterator;
+        $e.par_iter()
+    }};
+}
+
+#[cfg(not(feature = "parallel"))]
+macro_rules! par_iter {
+    ($e:expr) => {
+        $e.iter()
+    };
+}
+
+#[cfg(feature = "parallel")]
+macro_rules! par_iter_mut {
+    ($e:expr) => {{
+        use rayon::iter::IntoParallelRefMutIterator;
+        $e.par_iter_mut()
+    }};
+}
+
+#[cfg(not(feature = "parallel"))]
+macro_rules! par_iter_mut {
+    ($e:expr) => {
+        $e.iter_mut()
+    };
+}
+
+#[cfg(feature = "parallel")]
+macro_rules! into_par_iter {
+    ($e:expr) => {{
+        use rayon::iter::IntoParallelIterator;
+        $e.into_par_iter()
+    }};
+}
+
+#[cfg(not(feature = "parallel"))]
+macro_rules! into_par_iter {
+    ($e:expr) => {
+        $e.into_iter()
+    };
+}
+
+#[cfg(feature = "parallel")]
+macro_rules! par_chunks {
+    ($e:expr, $size:expr) => {{
+        use rayon::slice::ParallelSlice;
+        $e.par_chunks($size)
+    }};
+}
+
+#[cfg(not(feature = "parallel"))]
+macro_rules! par_chunks {
+    ($e:expr, $size:expr) => {
+        $e.chunks($size)
+    };
+}
+
+#[cfg(feature = "parallel")]
+macro_rules! par_chunks_mut {
+    ($e:expr, $size:expr) => {{
+        use rayon::slice::ParallelSliceMut;
+        $e.par_chunks_mut($size)
+    }};
+}
+
+#[cfg(not(feature = "parallel"))]
+macro_rules! par_chunks_mut {
+    ($e:expr, $size:expr) => {
+        $e.chunks_mut($size)
+    };
+}
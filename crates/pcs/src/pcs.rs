@@ -0,0 +1,107 @@
+use binius_field::{BinaryField128b, BinaryField32b};
+
+use crate::{prover::{commit_with_params, prove, ProveError}, utils::{encoding::{Code, EncodingParams}, merkle::MerkleTree, ntt::AdditiveNTT, Commitment, EvalProof}, verifier::{verify, VerifyError}};
+
+//Bundles the NTT and the encoding parameters a commitment is made with, so the prover and the verifier can't disagree on them.
+//Commitments are made with params, and codes or commitments made with other ones are rejected by prove and verify.
+#[derive(Clone)]
+pub struct Pcs{
+    ntt: AdditiveNTT,
+    params: EncodingParams
+}
+
+impl Pcs{
+    //Scheme for polynomials of 2^log_len packed coefficients, i.e 2^(log_len + 5) F_2 coefficients.
+    pub fn new(log_len: usize)->Pcs{
+        Pcs::with_params(log_len, EncodingParams::default())
+    }
+
+    //Same as new, but the rows are encoded with params instead of the defaults.
+    pub fn with_params(log_len: usize, params: EncodingParams)->Pcs{
+        Pcs{
+            ntt: AdditiveNTT::new(log_len),
+            params
+        }
+    }
+
+    pub fn ntt(&self)->&AdditiveNTT{
+        &self.ntt
+    }
+
+    pub fn params(&self)->EncodingParams{
+        self.params
+    }
+
+    //See prover::commit_with_params.
+    pub fn commit(&self, poly:&[BinaryField32b])->(Commitment, MerkleTree, Code){
+        commit_with_params(poly, &self.ntt, self.params)
+    }
+
+    //See prover::prove. Errors if encoded_poly wasn't encoded with the params of the scheme.
+    pub fn prove(&self, poly:&Vec<BinaryField32b>, encoded_poly:&Code, merkle_tree:&MerkleTree, point:&[BinaryField128b])->Result<EvalProof, ProveError>{
+        if encoded_poly.params() != self.params{
+            return Err(ProveError::ParamsMismatch { expected: self.params, got: encoded_poly.params() });
+        }
+        prove(poly, encoded_poly, merkle_tree, point)
+    }
+
+    //See verifier::verify. Errors if the commitment wasn't made at the rate of the scheme, the packing degree isn't recorded in it.
+    pub fn verify(&self, commitment:Commitment, eval:BinaryField128b, proof:EvalProof, point:Vec<BinaryField128b>)->Result<(), VerifyError>{
+        let log_inv_rate = self.params.rate.trailing_zeros() as usize;
+        if commitment.log_inv_rate != log_inv_rate{
+            return Err(VerifyError::RateMismatch { expected: log_inv_rate, got: commitment.log_inv_rate });
+        }
+        verify(commitment, eval, proof, point, &self.ntt)
+    }
+}
+
+#[test]
+fn pcs_test(){
+    use binius_field::Field;
+    use rand::thread_rng;
+    use crate::{prover::commit, test::MIN_PROVABLE_LOG_LEN, utils::{encoding::compute_fourier_bases, packed_arithmetic::evaluate_unpacked}};
+
+    let mut rng = thread_rng();
+    let l = MIN_PROVABLE_LOG_LEN;
+    let pcs = Pcs::new(l);
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+
+    let (commitment, merkle_tree, encoded_poly) = pcs.commit(&poly);
+    assert_eq!(commitment.commit, commit(&poly, pcs.ntt()).0.commit);
+
+    let point:Vec<BinaryField128b> = (0..l+5).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let eval = evaluate_unpacked(&poly, &compute_fourier_bases(&point));
+    let proof = pcs.prove(&poly, &encoded_poly, &merkle_tree, &point).unwrap();
+
+    assert_eq!(pcs.verify(commitment.clone(), eval, proof.clone(), point.clone()), Ok(()));
+    assert_eq!(pcs.verify(commitment, eval + BinaryField128b::ONE, proof, point), Err(VerifyError::EvaluationMismatch));
+}
+
+#[test]
+fn pcs_params_test(){
+    use binius_field::Field;
+    use rand::thread_rng;
+    use crate::{test::MIN_PROVABLE_LOG_LEN, utils::{encoding::{compute_fourier_bases, PACKING_DEGREE}, packed_arithmetic::evaluate_unpacked}};
+
+    let mut rng = thread_rng();
+    let l = MIN_PROVABLE_LOG_LEN;
+    let params = EncodingParams{ rate: 2, packing_degree: PACKING_DEGREE };
+    let pcs = Pcs::with_params(l, params);
+    let default_pcs = Pcs::new(l);
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+
+    let (commitment, merkle_tree, encoded_poly) = pcs.commit(&poly);
+    assert_eq!((commitment.encoding_params(), encoded_poly.params()), (params, params));
+
+    let point:Vec<BinaryField128b> = (0..l+5).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let eval = evaluate_unpacked(&poly, &compute_fourier_bases(&point));
+    let proof = pcs.prove(&poly, &encoded_poly, &merkle_tree, &point).unwrap();
+    assert_eq!(pcs.verify(commitment.clone(), eval, proof.clone(), point.clone()), Ok(()));
+
+    //A scheme with other params refuses to open or check the commitment.
+    assert_eq!(
+        default_pcs.prove(&poly, &encoded_poly, &merkle_tree, &point).unwrap_err(),
+        ProveError::ParamsMismatch { expected: EncodingParams::default(), got: params }
+    );
+    assert_eq!(default_pcs.verify(commitment, eval, proof, point), Err(VerifyError::RateMismatch { expected: 2, got: 1 }));
+}
@@ -1,49 +1,523 @@
 use binius_field::{BinaryField128b, BinaryField32b, Field};
-use rand::thread_rng;
-use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use rand::{thread_rng, RngCore};
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
 
-use crate::utils::{encoding::{compute_fourier_bases, make_coeff_matrix, make_linear_combination, Code}, merkle::{hash_field, merklize, Hash, MerkleTree}, ntt::AdditiveNTT, packed_arithmetic::PackedAlgebra32, Commitment, EvalProof};
+use crate::{utils::{encoding::{compute_fourier_bases, compute_fourier_bases_into, log_dimensions, make_coeff_matrix, make_linear_combination, transpose_coefficients, Code, EncodeError, EncodingParams, PACKING_DEGREE, RATE}, merkle::{hash_field, merklize, Hash, LeafHasher, MerkleError, MerkleTree}, ntt::AdditiveNTT, packed_arithmetic::PackedAlgebra32, transcript::{canonical_queries, fiat_shamir_queries, hiding_challenge, hiding_queries}, folded_evaluation, hiding_basis, BatchEvalProof, ChallengeTranscript, Commitment, DiffProof, EvalProof, FoldEval, HeterogeneousCommitment, HidingEvalProof, LinearCheck, MultiEvalProof, PolyShape, HIDING_ROWS, NUM_QUERIES}, verifier::linear_combination};
 
-pub fn commit(poly:&Vec<BinaryField32b>, ntt:&AdditiveNTT)->(Commitment, MerkleTree, Code){
-    let encoded_poly = Code::new(poly, ntt);
-    let leaf_hashes:Vec<Hash> = (0..encoded_poly.cols).into_par_iter().map(|column| hash_field(&encoded_poly.col(column))).collect();
-    let merkle_tree = merklize(leaf_hashes);
+//Commits to poly, a polynomial of 2^log_len packed BinaryField32b coefficients. Committing is sized by log_len throughout, from AdditiveNTT::new and Pcs::new
+//to commit_from_iter and commit_streaming, while points and Commitment::polynomial_log_len count the log_len + PACKING_DEGREE variables of the polynomial over F_2.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn commit(poly:&[BinaryField32b], ntt:&AdditiveNTT)->(Commitment, MerkleTree, Code){
+    let (commitment, merkle_tree, encoded_poly, _) = commit_with_leaf_hashes(poly, ntt);
+
+    (commitment, merkle_tree, encoded_poly)
+}
+
+//Same as commit, but the rows are encoded with params instead of the defaults. The rate is recorded in the commitment, which the verifier re-encodes the folded polynomial with.
+pub fn commit_with_params(poly:&[BinaryField32b], ntt:&AdditiveNTT, params:EncodingParams)->(Commitment, MerkleTree, Code){
+    let (commitment, merkle_tree, encoded_poly, _) = commit_columns(poly, ntt, params, |_, column| hash_field(column));
+
+    (commitment, merkle_tree, encoded_poly)
+}
+
+//Same as commit, but also hands back the hashes of all the columns so they don't need to be recomputed later on.
+pub fn commit_with_leaf_hashes(poly:&[BinaryField32b], ntt:&AdditiveNTT)->(Commitment, MerkleTree, Code, Vec<Hash>){
+    commit_columns(poly, ntt, EncodingParams::default(), |_, column| hash_field(column))
+}
+
+//Same as commit, but the leaves of the merkle tree are computed with leaf_fn from the column index and the column, e.g to bind the index into the leaf.
+//The verifier has to be given the same function through verify_with_leaf_fn.
+pub fn commit_with_leaf_fn(poly:&[BinaryField32b], ntt:&AdditiveNTT, leaf_fn: impl Fn(usize, &[BinaryField32b])->Hash + Sync)->(Commitment, MerkleTree, Code){
+    let (commitment, merkle_tree, encoded_poly, _) = commit_columns(poly, ntt, EncodingParams::default(), leaf_fn);
+
+    (commitment, merkle_tree, encoded_poly)
+}
+
+//Commits to every polynomial in polys on its own, with the same NTT, encoding and hashing them in parallel across the polynomials.
+//The commitments can be bound together under a single hash with combined_root.
+pub fn commit_batch(polys:&[Vec<BinaryField32b>], ntt:&AdditiveNTT)->(Vec<Commitment>, Vec<MerkleTree>, Vec<Code>){
+    let committed:Vec<(Commitment, MerkleTree, Code)> = par_iter!(polys).map(|poly| commit(poly, ntt)).collect();
+
+    let mut commitments = Vec::with_capacity(polys.len());
+    let mut merkle_trees = Vec::with_capacity(polys.len());
+    let mut encoded_polys = Vec::with_capacity(polys.len());
+    for (commitment, merkle_tree, encoded_poly) in committed{
+        commitments.push(commitment);
+        merkle_trees.push(merkle_tree);
+        encoded_polys.push(encoded_poly);
+    }
+
+    (commitments, merkle_trees, encoded_polys)
+}
+
+//Root of the merkle tree with the roots of commitments as its leaves, in order, e.g to send a single hash for a commit_batch.
+pub fn combined_root(commitments:&[Commitment])->Hash{
+    merklize(commitments.iter().map(|commitment| commitment.commit.clone()).collect()).get_root()
+}
+
+//Panics if poly can't be encoded, i.e its length isn't a non-zero power of two, commit_from_iter reports that as a CommitError instead.
+fn commit_columns(poly:&[BinaryField32b], ntt:&AdditiveNTT, params:EncodingParams, leaf_fn: impl Fn(usize, &[BinaryField32b])->Hash + Sync)->(Commitment, MerkleTree, Code, Vec<Hash>){
+    let encoded_poly = traced!("encode", Code::new(poly, ntt, params)).unwrap_or_else(|err| panic!("{err}"));
+    commit_code(encoded_poly, leaf_fn)
+}
+
+//Error thrown when a polynomial handed to us can't be committed to.
+#[derive(Clone, thiserror::Error, Debug, PartialEq, Eq)]
+pub enum CommitError {
+    #[error("the polynomial length {len} must be a non-zero power of two")]
+    InvalidLength { len: usize },
+    #[error("expected {expected} coefficients, the iterator yielded {got}")]
+    LengthMismatch { expected: usize, got: usize },
+    #[error("the polynomial is laid out in {cols} columns, hiding it needs at least {required}")]
+    TooFewColumns { cols: usize, required: usize },
+    #[error(transparent)]
+    Encode(#[from] EncodeError),
+}
+
+//Error thrown when a committed polynomial can't be opened, either because the point doesn't fit it or the merkle tree doesn't fit its codeword.
+#[derive(Clone, thiserror::Error, Debug, PartialEq, Eq)]
+pub enum ProveError {
+    #[error(transparent)]
+    Encode(#[from] EncodeError),
+    #[error(transparent)]
+    Merkle(#[from] MerkleError),
+    #[error("code was encoded with {got:?}, expected {expected:?}")]
+    ParamsMismatch { expected: EncodingParams, got: EncodingParams },
+    #[error("{requested} distinct queries requested but only {available} columns are committed")]
+    TooManyQueries { requested: usize, available: usize },
+    #[error("coefficient {index} is out of range for a polynomial of {len} coefficients")]
+    CoefficientOutOfRange { index: usize, len: usize },
+    #[error("no leaf hash for query {query}, only {len} were given")]
+    MissingLeafHash { query: usize, len: usize },
+    #[error("codes of {old:?} and {new:?} rows and columns can't differ in a single coefficient")]
+    ShapeMismatch { old: (usize, usize), new: (usize, usize) },
+}
+
+//Same as commit, but the coefficients are read from iter straight into the rows of the coefficient matrix, so the polynomial is never held as a single vector.
+//Errors if iter doesn't yield exactly expected_len coefficients, in which case all of it is consumed to report how many it did.
+pub fn commit_from_iter(mut iter: impl Iterator<Item = BinaryField32b>, expected_len:usize, ntt:&AdditiveNTT)->Result<(Commitment, MerkleTree, Code), CommitError>{
+
+    if !expected_len.is_power_of_two(){
+        return Err(CommitError::InvalidLength { len: expected_len });
+    }
+
+    let params = EncodingParams::default();
+    let (log_rows, log_cols) = log_dimensions(expected_len.trailing_zeros() as usize, params.rate, params.packing_degree);
+
+    let mut coeff_matrix = Vec::with_capacity(1<<log_rows);
+    for row in 0..1<<log_rows{
+        let coeffs:Vec<BinaryField32b> = iter.by_ref().take(1<<log_cols).collect();
+        if coeffs.len() < 1<<log_cols{
+            return Err(CommitError::LengthMismatch { expected: expected_len, got: (row<<log_cols) + coeffs.len() });
+        }
+        coeff_matrix.push(coeffs);
+    }
+    if iter.next().is_some(){
+        return Err(CommitError::LengthMismatch { expected: expected_len, got: expected_len + 1 + iter.count() });
+    }
+
+    let encoded_poly = traced!("encode", Code::from_coeff_matrix(&coeff_matrix, ntt, params))?;
+    let (commitment, merkle_tree, encoded_poly, _) = commit_code(encoded_poly, |_, column| hash_field(column));
+
+    Ok((commitment, merkle_tree, encoded_poly))
+}
+
+//Same commitment and merkle tree as commit, for a polynomial of 2^log_len packed coefficients streamed from coeffs, sized like commit.
+//This departs from the commit_streaming(coeffs, num_vars, ntt) that was asked for, which encoded row by row: it takes no ntt and encodes nothing.
+//The leaves of commit are the message columns of the systematic code, i.e the columns of the coefficient matrix, so encoding the rows would only compute
+//parity entries that are never hashed. Each column is instead hashed incrementally as the rows come in and neither the matrix nor its encoding is ever held.
+//log_len is named like the rest of commit rather than num_vars, which counts the log_len + PACKING_DEGREE variables of points.
+//The parity part only comes in when opening, which needs the polynomial to be encoded again as no Code is kept.
+//Errors like commit_from_iter if coeffs doesn't yield exactly 2^log_len coefficients.
+pub fn commit_streaming<I: Iterator<Item = BinaryField32b>>(mut coeffs: I, log_len:usize)->Result<(Commitment, MerkleTree), CommitError>{
+
+    let expected_len = 1<<log_len;
+    let (log_rows, log_cols) = log_dimensions(log_len, RATE, PACKING_DEGREE);
+
+    let mut column_hashers = vec![LeafHasher::new(); 1<<log_cols];
+    traced!("hash", for row in 0..1<<log_rows{
+        for (col, hasher) in column_hashers.iter_mut().enumerate(){
+            let coeff = coeffs.next().ok_or(CommitError::LengthMismatch { expected: expected_len, got: (row<<log_cols) + col })?;
+            hasher.update(coeff);
+        }
+    });
+    if coeffs.next().is_some(){
+        return Err(CommitError::LengthMismatch { expected: expected_len, got: expected_len + 1 + coeffs.count() });
+    }
+
+    let merkle_tree = traced!("merklize", merklize(column_hashers.into_iter().map(LeafHasher::finalize).collect()));
     let commitment = Commitment{
         commit: merkle_tree.get_root(),
-        cols: encoded_poly.cols.trailing_zeros() as usize
+        cols: log_cols,
+        rows: log_rows,
+        log_inv_rate: RATE.trailing_zeros() as usize
     };
 
+    Ok((commitment, merkle_tree))
+}
+
+//Commits to the transpose of the coefficient matrix commit lays poly out in, i.e to transpose_coefficients(poly, cols), with the rows and columns swapped.
+//Opening it is done as for commit, with the transposed coefficients and the point rearranged by transpose_point.
+pub fn commit_transpose(poly:&[BinaryField32b], ntt:&AdditiveNTT)->(Commitment, MerkleTree, Code){
+    let (log_rows, log_cols) = log_dimensions(poly.len().trailing_zeros() as usize, RATE, PACKING_DEGREE);
+
+    let transposed = transpose_coefficients(poly, 1<<log_cols);
+    let encoded_poly = traced!("encode", Code::with_log_cols(&transposed, log_rows, ntt)).unwrap_or_else(|err| panic!("{err}"));
+    let (commitment, merkle_tree, encoded_poly, _) = commit_code(encoded_poly, |_, column| hash_field(column));
+
     (commitment, merkle_tree, encoded_poly)
 }
 
+//Moves the log_rows row variables at the front of a point for poly behind the column variables, giving the same point for the transposed coefficients.
+//The PACKING_DEGREE variables indexing the bits of a packed coefficient stay at the end.
+pub fn transpose_point(point:&[BinaryField128b], log_rows:usize)->Vec<BinaryField128b>{
+    let (row_point, rest) = point.split_at(log_rows);
+    let (col_point, bit_point) = rest.split_at(rest.len() - PACKING_DEGREE);
+
+    [col_point, row_point, bit_point].concat()
+}
+
+pub(crate) fn commit_code(encoded_poly:Code, leaf_fn: impl Fn(usize, &[BinaryField32b])->Hash + Sync)->(Commitment, MerkleTree, Code, Vec<Hash>){
+    //The columns are laid out once and kept in encoded_poly, so prove opens the queried ones without extracting them again.
+    let leaf_hashes:Vec<Hash> = traced!("hash", par_iter!(encoded_poly.message_columns()).enumerate().map(|(column, values)| leaf_fn(column, values)).collect());
+    let merkle_tree = traced!("merklize", merklize(leaf_hashes.clone()));
+    let commitment = Commitment{
+        commit: merkle_tree.get_root(),
+        cols: encoded_poly.cols.trailing_zeros() as usize,
+        rows: encoded_poly.rows.trailing_zeros() as usize,
+        log_inv_rate: encoded_poly.log_inv_rate()
+    };
+
+    (commitment, merkle_tree, encoded_poly, leaf_hashes)
+}
+
+
+//Commits to polynomials of different lengths under one root. They are all laid out with the number of columns the longest one would get from commit,
+//shorter ones being padded with zeros to at least one row, and stacked as row blocks whose shapes are recorded in the commitment.
+pub fn commit_heterogeneous(polys:&[Vec<BinaryField32b>], ntt:&AdditiveNTT)->(HeterogeneousCommitment, MerkleTree, Code){
+
+    assert!(polys.iter().all(|poly| poly.len().is_power_of_two()), "Polynomial lengths need to be powers of 2.");
+
+    let max_log_len = polys.iter().map(|poly| poly.len().trailing_zeros() as usize).max().expect("No polynomials to commit to.");
+    let (_, log_cols) = log_dimensions(max_log_len, RATE, PACKING_DEGREE);
+
+    let codes:Vec<Code> = traced!("encode", par_iter!(polys).map(|poly| Code::with_log_cols(poly, log_cols, ntt)).collect::<Result<_, _>>()).unwrap_or_else(|err| panic!("{err}"));
+
+    let mut row_offset = 0;
+    let shapes = codes.iter().zip(polys).map(|(code, poly)|{
+        let shape = PolyShape{
+            log_len: poly.len().trailing_zeros() as usize,
+            row_offset,
+            log_rows: code.rows.trailing_zeros() as usize
+        };
+        row_offset += code.rows;
+        shape
+    }).collect();
+
+    let encoded_polys = Code::stack(codes);
+    let leaf_hashes:Vec<Hash> = traced!("hash", par_iter!(encoded_polys.message_columns()).map(|column| hash_field(column)).collect());
+    let merkle_tree = traced!("merklize", merklize(leaf_hashes));
+
+    let commitment = HeterogeneousCommitment{
+        commit: merkle_tree.get_root(),
+        cols: log_cols,
+        shapes
+    };
+
+    (commitment, merkle_tree, encoded_polys)
+}
+
+//Non-interactive opening at point. The NUM_QUERIES queried columns are derived from the root and the folded polynomial by fiat_shamir_queries,
+//so the verifier re-derives them from the proof instead of having them handed over.
+//A point whose row part doesn't match the rows of encoded_poly is reported as an EncodeError::ScalarCountMismatch, and a merkle_tree with fewer leaves than
+//encoded_poly has columns, e.g one committing to another polynomial, as a MerkleError::LeafIndexOutOfRange for the first query it can't open.
+//A polynomial laid out in fewer than NUM_QUERIES columns is rejected with TooManyQueries, like verify does with check_query_count.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn prove(poly:&Vec<BinaryField32b>, encoded_poly: &Code, merkle_tree: &MerkleTree, point:&[BinaryField128b])->Result<EvalProof, ProveError>{
+    prove_and_record(poly, encoded_poly, merkle_tree, point, None)
+}
 
-pub fn prove(poly:&Vec<BinaryField32b>, encoded_poly: &Code, merkle_tree: &MerkleTree, point:&Vec<BinaryField128b>, queries:&Vec<usize>)->EvalProof{
+//Same as prove, but also returns the challenges the proof was made with, the fiat shamir queries and the fold scalars, for auditing or comparing against another implementation.
+pub fn prove_recording_challenges(poly:&Vec<BinaryField32b>, encoded_poly: &Code, merkle_tree: &MerkleTree, point:&[BinaryField128b])->Result<(EvalProof, ChallengeTranscript), ProveError>{
+    let mut transcript = ChallengeTranscript::default();
+    let proof = prove_and_record(poly, encoded_poly, merkle_tree, point, Some(&mut transcript))?;
 
-    let scalars = compute_fourier_bases(&point[..(point.len() - (encoded_poly.cols.trailing_zeros() as usize + 5))].to_vec());
-    let poly_coeff_matrix = make_coeff_matrix(&poly, encoded_poly.cols);
+    Ok((proof, transcript))
+}
 
-    let linear_combination = make_linear_combination(poly_coeff_matrix, scalars);
+fn prove_and_record(poly:&Vec<BinaryField32b>, encoded_poly: &Code, merkle_tree: &MerkleTree, point:&[BinaryField128b], mut transcript:Option<&mut ChallengeTranscript>)->Result<EvalProof, ProveError>{
+
+    if NUM_QUERIES > encoded_poly.cols{
+        return Err(ProveError::TooManyQueries { requested: NUM_QUERIES, available: encoded_poly.cols });
+    }
 
-    // println!("{:?}", linear_combination);
-    let mut queried_columns = vec![vec![BinaryField32b::ZERO; encoded_poly.rows]; queries.len()];
-    let mut merkle_paths = vec![Vec::<Hash>::new(); queries.len()];
+    let folded_poly = traced!("fold", {
+        let scalars = compute_fourier_bases(&point[..point.len().saturating_sub(encoded_poly.col_point_len())]);
+        if let Some(transcript) = transcript.as_deref_mut(){
+            transcript.fold_scalars.clone_from(&scalars);
+        }
+        make_linear_combination(make_coeff_matrix(poly, encoded_poly.cols), scalars)
+    })?;
+    //The fold has checked the row part of the point, and a polynomial with enough columns has more than one row, so what is left is the column part.
+    let eval = folded_evaluation(&folded_poly, &point[point.len() - encoded_poly.col_point_len()..]);
 
-    for i in 0..queries.len(){
-        queried_columns[i] = encoded_poly.col(queries[i]);
-        merkle_paths[i] = merkle_tree.get_merkle_path(queries[i])
+    //The only error is drawing more distinct queries than there are columns, which has been ruled out above.
+    let queries = fiat_shamir_queries(&merkle_tree.get_root(), encoded_poly.log_inv_rate(), point, eval, &folded_poly, NUM_QUERIES, encoded_poly.cols)
+    .map_err(|_| ProveError::TooManyQueries { requested: NUM_QUERIES, available: encoded_poly.cols })?;
+    if let Some(transcript) = transcript{
+        transcript.queries.clone_from(&queries);
     }
 
+    //The openings are independent so they are gathered in parallel, collecting keeps them in the order of the queries, which verify relies on.
+    let queried_columns:Vec<Vec<BinaryField32b>> = traced!("column-gather", par_iter!(queries).map(|&query| encoded_poly.col(query)).collect());
+    let merkle_paths:Vec<Vec<Hash>> = traced!("path-gather", par_iter!(queries).map(|&query| merkle_tree.try_get_merkle_path(query)).collect::<Result<_, _>>())?;
 
-    EvalProof::new(linear_combination, queried_columns, merkle_paths)
+    Ok(EvalProof::new(folded_poly, queried_columns, merkle_paths))
 }
 
+//Same as prove, but opens the columns at queries chosen by the caller, for interactive use or when the queries come from an outer protocol's transcript.
+//As in prove, the columns are opened at canonical_queries(queries), once each in increasing order, and a query merkle_tree has no leaf for is reported as a MerkleError.
+pub fn prove_with_queries(poly:&Vec<BinaryField32b>, encoded_poly: &Code, merkle_tree: &MerkleTree, point:&Vec<BinaryField128b>, queries:&Vec<usize>)->Result<EvalProof, ProveError>{
+
+    let linear_combination = traced!("fold", {
+        let scalars = compute_fourier_bases(&point[..point.len().saturating_sub(encoded_poly.col_point_len())]);
+        let poly_coeff_matrix = make_coeff_matrix(poly, encoded_poly.cols);
+
+        make_linear_combination(poly_coeff_matrix, scalars)
+    })?;
+
+    let queries = canonical_queries(queries);
+    let queried_columns:Vec<Vec<BinaryField32b>> = traced!("column-gather", par_iter!(queries).map(|&query| encoded_poly.col(query)).collect());
+    let merkle_paths:Vec<Vec<Hash>> = traced!("path-gather", par_iter!(queries).map(|&query| merkle_tree.try_get_merkle_path(query)).collect::<Result<_, _>>())?;
+
+
+    Ok(EvalProof::new(linear_combination, queried_columns, merkle_paths))
+}
+
+//Same as prove, but the queried columns share a single combined merkle opening, which is smaller than the separate paths once queries land in the same subtrees.
+//As in prove_with_queries, the columns are opened at canonical_queries(queries).
+pub fn prove_batched(poly:&Vec<BinaryField32b>, encoded_poly: &Code, merkle_tree: &MerkleTree, point:&[BinaryField128b], queries:&[usize])->Result<BatchEvalProof, ProveError>{
+
+    let queries = &canonical_queries(queries);
+    //get_multi_path indexes the layers of the tree directly, so queries it has no leaf for are caught here.
+    if let Some(&index) = queries.iter().find(|&&query| query >= merkle_tree.num_leaves()){
+        return Err(MerkleError::LeafIndexOutOfRange { index, num_leaves: merkle_tree.num_leaves() }.into());
+    }
+
+    let linear_combination = traced!("fold", {
+        let scalars = compute_fourier_bases(&point[..point.len().saturating_sub(encoded_poly.col_point_len())]);
+        make_linear_combination(make_coeff_matrix(poly, encoded_poly.cols), scalars)
+    })?;
+
+    let queried_columns:Vec<Vec<BinaryField32b>> = traced!("column-gather", par_iter!(queries).map(|&query| encoded_poly.col(query)).collect());
+    let multi_path = traced!("path-gather", merkle_tree.get_multi_path(queries));
+
+    Ok(BatchEvalProof{
+        folded_poly: linear_combination,
+        queried_columns,
+        multi_path
+    })
+}
+
+//Opens the commitment at every point in points with one set of queried columns, the rows are folded once per point.
+//As in prove_with_queries, the columns are opened at canonical_queries(queries).
+pub fn prove_multi(poly:&Vec<BinaryField32b>, encoded_poly: &Code, merkle_tree: &MerkleTree, points:&[Vec<BinaryField128b>], queries:&[usize])->Result<MultiEvalProof, ProveError>{
+
+    let folded_polys = traced!("fold", points.iter().map(|point|{
+        let scalars = compute_fourier_bases(&point[..point.len().saturating_sub(encoded_poly.col_point_len())]);
+        make_linear_combination(make_coeff_matrix(poly, encoded_poly.cols), scalars)
+    }).collect::<Result<_, _>>())?;
+
+    let queries = canonical_queries(queries);
+    let queried_columns:Vec<Vec<BinaryField32b>> = traced!("column-gather", par_iter!(queries).map(|&query| encoded_poly.col(query)).collect());
+    let merkle_paths:Vec<Vec<Hash>> = traced!("path-gather", par_iter!(queries).map(|&query| merkle_tree.try_get_merkle_path(query)).collect::<Result<_, _>>())?;
+
+    Ok(MultiEvalProof{
+        folded_polys,
+        queried_columns,
+        merkle_paths
+    })
+}
+
+//Hiding variant of commit, for openings that reveal nothing about poly beyond the claimed evaluation, see prove_hiding.
+//A single random blinding row isn't enough here. The code is systematic, so the opened columns of commit carry the coefficients of poly
+//as they are whatever is added below them, and a random row folded in with one F_128 scalar only masks the folded polynomial over the F_2-span
+//of that scalar. Instead every row of the coefficient matrix is extended by cols random coefficients and only the parity part of the codeword is opened.
+//Reed-Solomon codes are MDS, so any 2*cols entries of an encoded row are independent, and given the cols coefficients of the row, up to cols of its
+//parity entries are uniformly random. The openings stay random as long as there are no more queries than that, so poly needs at least NUM_QUERIES columns.
+//The HIDING_ROWS random mask rows appended below mask the folded polynomial, see HIDING_ROWS. The merkle tree is over all the codeword columns.
+//Errors if poly is laid out in fewer than NUM_QUERIES columns.
+pub fn commit_hiding(poly:&[BinaryField32b], ntt:&AdditiveNTT, rng:&mut impl RngCore)->Result<(Commitment, MerkleTree, Code), CommitError>{
+    let (log_rows, log_cols) = log_dimensions(poly.len().trailing_zeros() as usize, RATE, PACKING_DEGREE);
+    let cols = 1<<log_cols;
+    if cols < NUM_QUERIES{
+        return Err(CommitError::TooFewColumns { cols, required: NUM_QUERIES });
+    }
+
+    let coeff_matrix = hiding_coeff_matrix(poly, cols, rng);
+    let encoded_poly = traced!("encode", Code::from_coeff_matrix(&coeff_matrix, ntt, EncodingParams::default()))?;
+    let leaf_hashes:Vec<Hash> = traced!("hash", into_par_iter!(0..encoded_poly.codeword_len()).map(|column| hash_field(&encoded_poly.codeword_col(column))).collect());
+    let merkle_tree = traced!("merklize", merklize(leaf_hashes));
+    let commitment = Commitment{
+        commit: merkle_tree.get_root(),
+        cols: log_cols,
+        rows: log_rows,
+        log_inv_rate: encoded_poly.log_inv_rate()
+    };
+
+    Ok((commitment, merkle_tree, encoded_poly))
+}
+
+//The coefficient matrix commit_hiding encodes, the rows of cols coefficients of poly each followed by cols random ones, and then the HIDING_ROWS random mask rows.
+fn hiding_coeff_matrix(poly:&[BinaryField32b], cols:usize, rng:&mut impl RngCore)->Vec<Vec<BinaryField32b>>{
+    let mut coeff_matrix:Vec<Vec<BinaryField32b>> = poly.chunks(cols).map(|row|{
+        row.iter().copied().chain((0..cols).map(|_| BinaryField32b::random(&mut *rng))).collect()
+    }).collect();
+    coeff_matrix.extend((0..HIDING_ROWS).map(|_| (0..2*cols).map(|_| BinaryField32b::random(&mut *rng)).collect()));
+
+    coeff_matrix
+}
+
+//Opens a commitment made with commit_hiding at point. The prover sends the evaluation mask_eval of the folded mask rows at the column part of the point
+//before drawing gamma, then folds the rows with the eq table of the row part and the mask rows with gamma times hiding_basis. The folded polynomial
+//is thus masked by a uniformly random row, and its first half evaluates to the evaluation of poly plus gamma*mask_eval.
+pub fn prove_hiding(encoded_poly: &Code, merkle_tree: &MerkleTree, point:&[BinaryField128b])->Result<HidingEvalProof, ProveError>{
+    let root = merkle_tree.get_root();
+    let log_rows = (encoded_poly.rows - HIDING_ROWS).trailing_zeros() as usize;
+    //A point too short for the row part leaves too few scalars, which make_linear_combination reports.
+    let (row_point, col_point) = point.split_at(log_rows.min(point.len()));
+    let coeff_matrix = make_coeff_matrix(&encoded_poly.message(), encoded_poly.cols);
+
+    let (folded_poly, mask_eval) = traced!("fold", {
+        let mask = make_linear_combination(coeff_matrix[coeff_matrix.len() - HIDING_ROWS..].to_vec(), hiding_basis())?;
+        let mask_eval = linear_combination(&compute_fourier_bases(col_point), &mask[..mask.len()/2]);
+
+        let gamma = hiding_challenge(&root, point, mask_eval);
+        let mut scalars = compute_fourier_bases(row_point);
+        scalars.extend(hiding_basis().into_iter().map(|scalar| gamma*scalar));
+
+        (make_linear_combination(coeff_matrix, scalars)?, mask_eval)
+    });
+
+    let queries = canonical_queries(&hiding_queries(&root, &folded_poly, NUM_QUERIES, encoded_poly.cols, encoded_poly.params().rate));
+    let queried_columns:Vec<Vec<BinaryField32b>> = traced!("column-gather", par_iter!(queries).map(|&query| encoded_poly.codeword_col(query)).collect());
+    let merkle_paths:Vec<Vec<Hash>> = traced!("path-gather", par_iter!(queries).map(|&query| merkle_tree.try_get_merkle_path(query)).collect::<Result<_, _>>())?;
+
+    Ok(HidingEvalProof{
+        folded_poly,
+        mask_eval,
+        queried_columns,
+        merkle_paths
+    })
+}
+
+//Same as prove, but includes the leaf hashes of the queried columns, as returned by commit_with_leaf_hashes, in the proof.
+//A query leaf_hashes has no hash for is reported as MissingLeafHash.
+pub fn prove_with_leaf_hashes(poly:&Vec<BinaryField32b>, encoded_poly: &Code, merkle_tree: &MerkleTree, leaf_hashes:&[Hash], point:&Vec<BinaryField128b>, queries:&Vec<usize>)->Result<EvalProof, ProveError>{
+
+    let proof = prove_with_queries(poly, encoded_poly, merkle_tree, point, queries)?;
+    let queried_hashes = canonical_queries(queries).iter().map(|&query|{
+        leaf_hashes.get(query).cloned().ok_or(ProveError::MissingLeafHash { query, len: leaf_hashes.len() })
+    }).collect::<Result<_, _>>()?;
+
+    Ok(proof.with_leaf_hashes(queried_hashes))
+}
+
+
+//Same as prove, but the proof also carries the evaluation of the folded polynomial, split into partial sums over chunks of 2^log_chunk_len entries,
+//so that verify_with_fold_eval only needs to recompute some of the chunks instead of the whole dot product.
+pub fn prove_with_fold_eval(poly:&Vec<BinaryField32b>, encoded_poly: &Code, merkle_tree: &MerkleTree, point:&Vec<BinaryField128b>, queries:&Vec<usize>, log_chunk_len:usize)->Result<EvalProof, ProveError>{
+
+    let proof = prove_with_queries(poly, encoded_poly, merkle_tree, point, queries)?;
+    let scalars = compute_fourier_bases(&point[point.len().saturating_sub(encoded_poly.col_point_len())..]);
+
+    let chunk_sums:Vec<BinaryField128b> = scalars.chunks(1<<log_chunk_len).zip(proof.folded_poly.chunks(1<<log_chunk_len))
+    .map(|(scalar_chunk, val_chunk)| scalar_chunk.iter().zip(val_chunk).fold(BinaryField128b::ZERO, |acc, (scalar, val)| acc + *scalar * *val))
+    .collect();
+
+    let eval = chunk_sums.iter().fold(BinaryField128b::ZERO, |acc, sum| acc + *sum);
+    Ok(proof.with_fold_eval(FoldEval{ eval, chunk_sums }))
+}
+
+//Same as prove, but also claims the value of the linear relation sum(poly[i]*c_i) over the (i, c_i) in relation and opens the columns
+//of the systematic part the involved coefficients live in, so verify_with_linear_check can recompute it from them.
+//A coefficient index outside of poly is reported as CoefficientOutOfRange.
+pub fn prove_with_linear_check(poly:&Vec<BinaryField32b>, encoded_poly: &Code, merkle_tree: &MerkleTree, point:&Vec<BinaryField128b>, queries:&Vec<usize>, relation:&[(usize, BinaryField128b)])->Result<EvalProof, ProveError>{
+
+    //The coefficients of the code's rows*cols entries, so every index in range also gives a column of encoded_poly.
+    let len = poly.len().min(encoded_poly.rows*encoded_poly.cols);
+    if let Some(&(index, _)) = relation.iter().find(|&&(index, _)| index >= len){
+        return Err(ProveError::CoefficientOutOfRange { index, len });
+    }
+
+    let claimed_sum = relation.iter().fold(BinaryField128b::ZERO, |acc, &(index, scalar)| acc + scalar*BinaryField128b::from(poly[index]));
+
+    let mut column_indices:Vec<usize> = relation.iter().map(|&(index, _)| index%encoded_poly.cols).collect();
+    column_indices.sort_unstable();
+    column_indices.dedup();
+
+    let linear_check = LinearCheck{
+        claimed_sum,
+        columns: column_indices.iter().map(|&col| encoded_poly.col(col)).collect(),
+        merkle_paths: column_indices.iter().map(|&col| merkle_tree.try_get_merkle_path(col)).collect::<Result<_, _>>()?,
+        column_indices
+    };
+
+    Ok(prove_with_queries(poly, encoded_poly, merkle_tree, point, queries)?.with_linear_check(linear_check))
+}
+
+//Opens the polynomial at index of a commit_heterogeneous commitment at point, which only has that polynomial's own variables.
+//Rows outside of its block are folded with zero, so the opened columns, which span every block, are still checked against the single root.
+//As in prove_with_queries, the columns are opened at canonical_queries(queries).
+pub fn prove_heterogeneous(commitment:&HeterogeneousCommitment, index:usize, poly:&[BinaryField32b], encoded_polys: &Code, merkle_tree: &MerkleTree, point:&[BinaryField128b], queries:&[usize])->Result<EvalProof, ProveError>{
+
+    let shape = &commitment.shapes[index];
+    let padded_point = commitment.padded_point(index, point);
+
+    let mut padded_poly = poly.to_vec();
+    padded_poly.resize(1<<(shape.log_rows + commitment.cols), BinaryField32b::ZERO);
+
+    let mut scalars = vec![BinaryField128b::ZERO; 1<<shape.log_rows];
+    compute_fourier_bases_into(&padded_point[..shape.log_rows], &mut scalars);
+    let linear_combination = make_linear_combination(make_coeff_matrix(&padded_poly, 1<<commitment.cols), scalars)?;
+
+    let queries = canonical_queries(queries);
+    let queried_columns = par_iter!(queries).map(|&query| encoded_polys.col(query)).collect();
+    let merkle_paths = par_iter!(queries).map(|&query| merkle_tree.try_get_merkle_path(query)).collect::<Result<_, _>>()?;
+
+    Ok(EvalProof::new(linear_combination, queried_columns, merkle_paths))
+}
+
+//Proves that new only differs from old in the coefficient at changed_index, by opening the column containing it in both commitments.
+//Codes of different shapes are reported as ShapeMismatch and an index outside of them as CoefficientOutOfRange.
+pub fn prove_coefficient_diff(old: &Code, new: &Code, changed_index:usize, merkle_trees:(&MerkleTree, &MerkleTree))->Result<DiffProof, ProveError>{
+
+    if (old.rows, old.cols) != (new.rows, new.cols){
+        return Err(ProveError::ShapeMismatch { old: (old.rows, old.cols), new: (new.rows, new.cols) });
+    }
+    let len = old.rows*old.cols;
+    if changed_index >= len{
+        return Err(ProveError::CoefficientOutOfRange { index: changed_index, len });
+    }
+
+    let col = changed_index%old.cols;
+    let (old_tree, new_tree) = merkle_trees;
+
+    Ok(DiffProof{
+        old_column: old.col(col),
+        new_column: new.col(col),
+        old_path: old_tree.try_get_merkle_path(col)?,
+        new_path: new_tree.try_get_merkle_path(col)?
+    })
+}
 
 
 
 #[test]
 
 fn prover_test(){
-    
+
     let mut rng = thread_rng();
     let poly:Vec<BinaryField32b> = (0..1<<6).into_iter().map(|_| BinaryField32b::random(&mut rng)).collect();
     let ntt = AdditiveNTT::new(poly.len().trailing_zeros() as usize);
@@ -53,6 +527,343 @@ fn prover_test(){
     let point = vec![BinaryField128b::random(&mut rng);6];
     let queries = vec![2, 3];
 
-    prove(&poly, &encoded_poly, &merkle_tree, &point, &queries);
+    prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+
+}
+
+#[test]
+fn leaf_hashes_test(){
+    use crate::{utils::{encoding::compute_fourier_bases, packed_arithmetic::evaluate_unpacked}, verifier::verify_with_queries};
+
+    let mut rng = thread_rng();
+    let l = 10;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+
+    let (commitment, merkle_tree, encoded_poly, leaf_hashes) = commit_with_leaf_hashes(&poly, &ntt);
+    assert_eq!(commitment.commit, merkle_tree.get_root());
+    assert_eq!(leaf_hashes.len(), encoded_poly.cols);
 
-}
\ No newline at end of file
+    let point:Vec<BinaryField128b> = (0..l+5).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let queries = vec![0, 5, 5, encoded_poly.cols - 1];
+    let proof = prove_with_leaf_hashes(&poly, &encoded_poly, &merkle_tree, &leaf_hashes, &point, &queries).unwrap();
+
+    let included = proof.leaf_hashes.clone().unwrap();
+    assert_eq!(included.len(), queries.len() - 1);
+    for (hash, column) in included.iter().zip(proof.queried_columns.iter()){
+        assert_eq!(*hash, hash_field(column));
+    }
+
+    let eval = evaluate_unpacked(&poly, &compute_fourier_bases(&point));
+    assert_eq!(verify_with_queries(commitment, eval, proof, point.clone(), queries, &ntt), Ok(()));
+
+    //Hashes of fewer columns than queried are reported instead of indexed past.
+    let error = ProveError::MissingLeafHash { query: encoded_poly.cols - 1, len: 6 };
+    assert_eq!(prove_with_leaf_hashes(&poly, &encoded_poly, &merkle_tree, &leaf_hashes[..6], &point, &vec![0, encoded_poly.cols - 1]).unwrap_err(), error);
+}
+
+#[test]
+fn commit_transpose_test(){
+    use crate::{utils::{encoding::compute_fourier_bases, packed_arithmetic::evaluate_unpacked}, verifier::verify_with_queries};
+
+    let mut rng = thread_rng();
+    for l in [3, 10]{
+        let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+        let ntt = AdditiveNTT::new(l);
+
+        let (commitment, merkle_tree, encoded_poly) = commit(&poly, &ntt);
+        let (transpose_commitment, transpose_tree, transpose_encoded) = commit_transpose(&poly, &ntt);
+        assert_eq!((transpose_commitment.rows, transpose_commitment.cols), (commitment.cols, commitment.rows));
+
+        let point:Vec<BinaryField128b> = (0..l+5).map(|_| BinaryField128b::random(&mut rng)).collect();
+        let eval = evaluate_unpacked(&poly, &compute_fourier_bases(&point));
+
+        let transposed = transpose_coefficients(&poly, encoded_poly.cols);
+        let transposed_point = transpose_point(&point, commitment.rows);
+        assert_eq!(evaluate_unpacked(&transposed, &compute_fourier_bases(&transposed_point)), eval);
+
+        let queries = vec![0, encoded_poly.cols - 1];
+        let proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+        assert_eq!(verify_with_queries(commitment, eval, proof, point, queries, &ntt), Ok(()));
+
+        let queries = vec![0, transpose_encoded.cols - 1];
+        let proof = prove_with_queries(&transposed, &transpose_encoded, &transpose_tree, &transposed_point, &queries).unwrap();
+        assert_eq!(verify_with_queries(transpose_commitment, eval, proof, transposed_point, queries, &ntt), Ok(()));
+    }
+}
+
+//Recomputes the commitment with plain serial loops, so running this with and without the parallel feature checks both builds commit identically.
+#[test]
+fn serial_commit_test(){
+    use crate::utils::{encoding::encode, merkle::hash_concatenation};
+
+    let mut rng = thread_rng();
+    let l = 8;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let (commitment, _, encoded_poly) = commit(&poly, &ntt);
+    let (rows, cols) = (encoded_poly.rows, encoded_poly.cols);
+
+    let encoded_rows:Vec<Vec<BinaryField32b>> = poly.chunks(cols).map(|row| encode(&row.to_vec(), &ntt, EncodingParams::default()).unwrap()).collect();
+    let columns = (0..RATE*cols).map(|col| encoded_rows.iter().map(|row| row[col]).collect()).collect();
+    assert_eq!(Code::from_columns(columns, rows, cols, EncodingParams::default()).unwrap().digest(), encoded_poly.digest());
+
+    let mut layer:Vec<Hash> = (0..cols).map(|col| hash_field(&encoded_rows.iter().map(|row| row[col]).collect::<Vec<_>>())).collect();
+    while layer.len() > 1{
+        layer = layer.chunks(2).map(|pair| hash_concatenation(&pair[0], &pair[1])).collect();
+    }
+    assert_eq!(commitment.commit, layer[0]);
+}
+
+#[test]
+fn commit_streaming_test(){
+
+    let mut rng = thread_rng();
+    for l in [0, 3, 12]{
+        let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+        let ntt = AdditiveNTT::new(l);
+
+        let (commitment, merkle_tree, _) = commit(&poly, &ntt);
+        let (streamed_commitment, streamed_tree) = commit_streaming(poly.iter().copied(), l).unwrap();
+        assert_eq!(streamed_commitment.commit, commitment.commit, "Length 2^{l} failed");
+        assert_eq!((streamed_commitment.rows, streamed_commitment.cols, streamed_commitment.log_inv_rate), (commitment.rows, commitment.cols, commitment.log_inv_rate));
+        assert_eq!(streamed_tree, merkle_tree);
+    }
+
+    let poly:Vec<BinaryField32b> = (0..16).map(|_| BinaryField32b::random(&mut rng)).collect();
+    assert_eq!(commit_streaming(poly[..13].iter().copied(), 4).unwrap_err(), CommitError::LengthMismatch { expected: 16, got: 13 });
+    assert_eq!(commit_streaming(poly.iter().copied(), 3).unwrap_err(), CommitError::LengthMismatch { expected: 8, got: 16 });
+    assert_eq!(commit_streaming(std::iter::empty(), 0).unwrap_err(), CommitError::LengthMismatch { expected: 1, got: 0 });
+}
+
+#[test]
+fn commit_from_iter_test(){
+
+    let mut rng = thread_rng();
+    for l in [0, 3, 10]{
+        let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+        let ntt = AdditiveNTT::new(l);
+
+        let (commitment, merkle_tree, encoded_poly) = commit(&poly, &ntt);
+        let (iter_commitment, iter_tree, iter_encoded) = commit_from_iter(poly.iter().copied(), poly.len(), &ntt).unwrap();
+        assert_eq!(iter_commitment.commit, commitment.commit);
+        assert_eq!((iter_commitment.rows, iter_commitment.cols), (commitment.rows, commitment.cols));
+        assert_eq!(iter_tree.get_root(), merkle_tree.get_root());
+        assert_eq!(iter_encoded.digest(), encoded_poly.digest());
+    }
+
+    let ntt = AdditiveNTT::new(4);
+    let poly:Vec<BinaryField32b> = (0..16).map(|_| BinaryField32b::random(&mut rng)).collect();
+    assert_eq!(commit_from_iter(poly[..13].iter().copied(), 16, &ntt).unwrap_err(), CommitError::LengthMismatch { expected: 16, got: 13 });
+    assert_eq!(commit_from_iter(poly.iter().chain(&poly[..3]).copied(), 16, &ntt).unwrap_err(), CommitError::LengthMismatch { expected: 16, got: 19 });
+    assert_eq!(commit_from_iter(poly.iter().copied(), 12, &ntt).unwrap_err(), CommitError::InvalidLength { len: 12 });
+}
+
+#[test]
+fn query_order_test(){
+
+    let mut rng = thread_rng();
+    let l = 12;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let (_, merkle_tree, encoded_poly) = commit(&poly, &ntt);
+
+    let point:Vec<BinaryField128b> = (0..l+5).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let queries:Vec<usize> = (0..100).map(|i| (i*37)%encoded_poly.cols).rev().collect();
+    let proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+
+    //The openings are in the order of the canonical queries, whatever order they are gathered in.
+    for (i, &query) in canonical_queries(&queries).iter().enumerate(){
+        assert_eq!(proof.queried_columns[i], encoded_poly.col(query));
+        assert_eq!(proof.merkle_paths[i], merkle_tree.get_merkle_path(query));
+    }
+}
+
+#[test]
+fn polynomial_log_len_test(){
+
+    let mut rng = thread_rng();
+    for l in 2..12{
+        let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+        let ntt = AdditiveNTT::new(l);
+
+        let (commitment, _, _) = commit(&poly, &ntt);
+
+        assert_eq!(1<<commitment.polynomial_log_len(), poly.len()*32);
+    }
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn commit_spans_test(){
+    use std::sync::{Arc, Mutex};
+    use tracing::{span, Subscriber};
+    use tracing_subscriber::{layer::{Context, SubscriberExt}, registry::LookupSpan, Layer, Registry};
+
+    //Records the name of every span that gets created.
+    struct SpanNames(Arc<Mutex<Vec<&'static str>>>);
+
+    impl<S:Subscriber + for<'a> LookupSpan<'a>> Layer<S> for SpanNames{
+        fn on_new_span(&self, attrs: &span::Attributes<'_>, _id: &span::Id, _ctx: Context<'_, S>){
+            self.0.lock().unwrap().push(attrs.metadata().name());
+        }
+    }
+
+    let mut rng = thread_rng();
+    let poly:Vec<BinaryField32b> = (0..1<<8).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(poly.len().trailing_zeros() as usize);
+
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = Registry::default().with(SpanNames(names.clone()));
+    tracing::subscriber::with_default(subscriber, || commit(&poly, &ntt));
+
+    assert_eq!(*names.lock().unwrap(), vec!["commit", "encode", "hash", "merklize"]);
+}
+
+#[test]
+fn commit_batch_test(){
+    use crate::{test::MIN_PROVABLE_LOG_LEN, utils::packed_arithmetic::evaluate_unpacked, verifier::verify};
+
+    let mut rng = thread_rng();
+    let l = MIN_PROVABLE_LOG_LEN;
+    let ntt = AdditiveNTT::new(l);
+    let polys:Vec<Vec<BinaryField32b>> = (0..3).map(|_| (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect()).collect();
+
+    let (commitments, merkle_trees, encoded_polys) = commit_batch(&polys, &ntt);
+    assert_eq!(commitments.len(), 3);
+
+    for (i, poly) in polys.iter().enumerate(){
+        assert_eq!(commitments[i].commit, commit(poly, &ntt).0.commit);
+
+        let point:Vec<BinaryField128b> = (0..l+5).map(|_| BinaryField128b::random(&mut rng)).collect();
+        let eval = evaluate_unpacked(poly, &compute_fourier_bases(&point));
+        let proof = prove(poly, &encoded_polys[i], &merkle_trees[i], &point).unwrap();
+        assert_eq!(verify(commitments[i].clone(), eval, proof, point, &ntt), Ok(()), "Polynomial {i} failed");
+    }
+
+    let roots:Vec<Hash> = commitments.iter().map(|commitment| commitment.commit.clone()).collect();
+    assert_eq!(combined_root(&commitments), merklize(roots).get_root());
+    assert_ne!(combined_root(&commitments), combined_root(&commitments[..2]));
+}
+
+#[test]
+fn prove_multi_test(){
+    use crate::{utils::packed_arithmetic::evaluate_unpacked, verifier::verify_multi_consistency};
+
+    let mut rng = thread_rng();
+    let l = 8;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let (commitment, merkle_tree, encoded_poly) = commit(&poly, &ntt);
+
+    let points:Vec<Vec<BinaryField128b>> = (0..2).map(|_| (0..l+5).map(|_| BinaryField128b::random(&mut rng)).collect()).collect();
+    let evals:Vec<BinaryField128b> = points.iter().map(|point| evaluate_unpacked(&poly, &compute_fourier_bases(point))).collect();
+    //Unsorted and with a repeat, both sides open and check canonical_queries(queries).
+    let queries = vec![9, 0, 5, 5];
+
+    let proof = prove_multi(&poly, &encoded_poly, &merkle_tree, &points, &queries).unwrap();
+
+    //The opened columns and paths don't depend on the point, every single point proof opens the very same ones.
+    for (point, folded_poly) in points.iter().zip(&proof.folded_polys){
+        let single = prove_with_queries(&poly, &encoded_poly, &merkle_tree, point, &queries).unwrap();
+        assert_eq!(single.folded_poly, *folded_poly);
+        assert_eq!(single.queried_columns, proof.queried_columns);
+        assert_eq!(single.merkle_paths, proof.merkle_paths);
+    }
+
+    assert_eq!(verify_multi_consistency(commitment, &evals, proof, &points, queries, &ntt), Ok(()));
+}
+
+#[test]
+fn prove_short_point_test(){
+    use crate::test::MIN_PROVABLE_LOG_LEN;
+
+    let mut rng = thread_rng();
+    let l = MIN_PROVABLE_LOG_LEN;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let (_, merkle_tree, encoded_poly) = commit(&poly, &ntt);
+
+    //Dropping a variable halves the eq table of the row part, and a point shorter than the column part gives a single scalar.
+    let rows = encoded_poly.rows;
+    let short_point:Vec<BinaryField128b> = (0..l+4).map(|_| BinaryField128b::random(&mut rng)).collect();
+    assert_eq!(prove(&poly, &encoded_poly, &merkle_tree, &short_point).unwrap_err(), ProveError::Encode(EncodeError::ScalarCountMismatch { rows, scalars: rows/2 }));
+    assert_eq!(prove(&poly, &encoded_poly, &merkle_tree, &short_point[..2]).unwrap_err(), ProveError::Encode(EncodeError::ScalarCountMismatch { rows, scalars: 1 }));
+
+    //The other variants fold the same way and report it all the same.
+    let error = ProveError::Encode(EncodeError::ScalarCountMismatch { rows, scalars: rows/2 });
+    let queries = vec![0, 1];
+    assert_eq!(prove_with_queries(&poly, &encoded_poly, &merkle_tree, &short_point, &queries).unwrap_err(), error);
+    assert_eq!(prove_batched(&poly, &encoded_poly, &merkle_tree, &short_point, &queries).unwrap_err(), error);
+    assert_eq!(prove_multi(&poly, &encoded_poly, &merkle_tree, &[short_point], &queries).unwrap_err(), error);
+}
+
+#[test]
+fn prove_wrong_tree_test(){
+    use crate::test::MIN_PROVABLE_LOG_LEN;
+
+    let mut rng = thread_rng();
+    let l = MIN_PROVABLE_LOG_LEN;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let (_, _, encoded_poly) = commit(&poly, &ntt);
+
+    //A tree over the columns of a polynomial with a quarter of the coefficients only has half the leaves, so most queries can't be opened in it.
+    let (_, small_tree, _) = commit(&poly[..1<<(l - 2)], &ntt);
+    let point:Vec<BinaryField128b> = (0..l+5).map(|_| BinaryField128b::random(&mut rng)).collect();
+
+    match prove(&poly, &encoded_poly, &small_tree, &point){
+        Err(ProveError::Merkle(MerkleError::LeafIndexOutOfRange { index, num_leaves })) => {
+            assert_eq!(num_leaves, small_tree.num_leaves());
+            assert!(index >= num_leaves && index < encoded_poly.cols);
+        }
+        result => panic!("Expected an out of range leaf, got {result:?}")
+    }
+
+    //The variants opening caller chosen queries report the first one the tree has no leaf for.
+    let last = encoded_poly.cols - 1;
+    let error = ProveError::Merkle(MerkleError::LeafIndexOutOfRange { index: last, num_leaves: small_tree.num_leaves() });
+    assert_eq!(prove_with_queries(&poly, &encoded_poly, &small_tree, &point, &vec![0, last]).unwrap_err(), error);
+    assert_eq!(prove_batched(&poly, &encoded_poly, &small_tree, &point, &[0, last]).unwrap_err(), error);
+    assert_eq!(prove_multi(&poly, &encoded_poly, &small_tree, &[point], &[0, last]).unwrap_err(), error);
+    assert_eq!(prove_coefficient_diff(&encoded_poly, &encoded_poly, last, (&small_tree, &small_tree)).unwrap_err(), error);
+}
+
+#[test]
+fn commit_hiding_statistical_test(){
+    use rand::{rngs::StdRng, SeedableRng};
+
+    //Two polynomials agreeing everywhere but in their low order coefficients, the first row of the coefficient matrix.
+    let mut rng = StdRng::seed_from_u64(0);
+    let (l, cols) = (15, 1<<8);
+    let ntt = AdditiveNTT::new(l);
+    let zero_low:Vec<BinaryField32b> = (0..1<<l).map(|i| if i < cols { BinaryField32b::ZERO } else { BinaryField32b::random(&mut rng) }).collect();
+    let mut random_low = zero_low.clone();
+    random_low[..cols].iter_mut().for_each(|coeff| *coeff = BinaryField32b::random(&mut rng));
+    let point:Vec<BinaryField128b> = (0..l + PACKING_DEGREE).map(|_| BinaryField128b::random(&mut rng)).collect();
+
+    //Frequency of every bit of the first row's entry in the columns opened by prove_hiding. Were the opened columns to depend on the
+    //low order coefficients, these would be fixed for the zero ones, instead they are close to 1/2 for both polynomials.
+    let trials = 10;
+    for poly in [&zero_low, &random_low]{
+        let (mut ones, mut openings) = ([0usize; 32], 0);
+        for _ in 0..trials{
+            let (_, merkle_tree, encoded_poly) = commit_hiding(poly, &ntt, &mut rng).unwrap();
+            let proof = prove_hiding(&encoded_poly, &merkle_tree, &point).unwrap();
+            openings += proof.queried_columns.len();
+            for column in &proof.queried_columns{
+                (0..32).for_each(|bit| ones[bit] += ((column[0].val()>>bit)&1) as usize);
+            }
+        }
+
+        //Each bit is drawn once per opening, a little under trials*NUM_QUERIES times as repeated queries are opened once, so twice the count
+        //of set bits is within sqrt(openings) of openings give or take, and we allow for 6 times that.
+        for count in ones{
+            let deviation = (2*count).abs_diff(openings);
+            assert!(deviation*deviation <= 36*openings, "A bit was set {count} times in {openings} openings.");
+        }
+    }
+
+    let small:Vec<BinaryField32b> = (0..1<<(l - 2)).map(|_| BinaryField32b::random(&mut rng)).collect();
+    assert_eq!(commit_hiding(&small, &ntt, &mut rng).unwrap_err(), CommitError::TooFewColumns { cols: 1<<7, required: NUM_QUERIES });
+}
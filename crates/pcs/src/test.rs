@@ -1,19 +1,27 @@
 use std::time::Instant;
 
 use binius_field::{BinaryField128b, BinaryField1b, BinaryField32b, Field};
-use rand::{distributions::Uniform, thread_rng, Rng};
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use rand::thread_rng;
+#[cfg(feature = "parallel")]
+use rayon::iter::ParallelIterator;
+
+#[cfg(test)]
+use proptest::{arbitrary::any, proptest};
+#[cfg(test)]
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
 
 use crate::{prover::{commit, prove}, utils::{encoding::compute_fourier_bases, ntt::AdditiveNTT, packed_arithmetic::evaluate_unpacked}, verifier::verify};
+#[cfg(test)]
+use crate::{prover::prove_with_queries, utils::{encoding::{Code, PACKING_DEGREE}, merkle::MerkleTree, Commitment, EvalProof}, verifier::{verify_with_queries, VerifyError}};
 
 #[test]
 fn commitment_test(){
 
-    for l in 10..40{
+    for l in MIN_PROVABLE_LOG_LEN..40{
 
         println!("--------------|| length 2^{:?} ||-------------- \n\n", l+5);
         let time = Instant::now();
-        let poly:Vec<BinaryField32b> = (0..1<<l).into_par_iter().map(|_| {
+        let poly:Vec<BinaryField32b> = into_par_iter!(0..1<<l).map(|_| {
             BinaryField32b::random(thread_rng())
         }).collect();
         println!("Time: {:?} \n", time.elapsed());
@@ -34,18 +42,142 @@ fn commitment_test(){
         let base = compute_fourier_bases(&point);
         // println!("{:?}", base);
         let eval = evaluate_unpacked(&poly, &base);
-        let mut rng = thread_rng();
-        println!("Generating queries");
-        let queries = (0..241).into_iter().map(|_| thread_rng().sample(Uniform::new(0, encoded_poly.cols))).collect();
         println!("Generating proof");
         let time = Instant::now();
-        let eval_proof = prove(&poly, &encoded_poly, &merkle_tree, &point, &queries);
+        let eval_proof = prove(&poly, &encoded_poly, &merkle_tree, &point).unwrap();
         println!("Time: {:?}\n", time.elapsed());
 
         let time = Instant::now();
 
         println!("Verifying");
-        verify(commitment, eval, eval_proof, point, queries, &ntt);
+        verify(commitment, eval, eval_proof, point, &ntt).unwrap();
         println!("Time: {:?} \n", time.elapsed());
     }
-}
\ No newline at end of file
+}
+#[test]
+fn random_point_test(){
+    let mut rng = thread_rng();
+
+    for l in MIN_PROVABLE_LOG_LEN..MIN_PROVABLE_LOG_LEN + 3{
+        //Every coordinate is a full F_128 element, so neither half of the point in prove/verify is a 0/1 point.
+        let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+
+        let eval_proof = prove(&poly, &encoded_poly, &merkle_tree, &point).unwrap();
+        assert_eq!(verify(commitment, eval, eval_proof, point, &ntt), Ok(()), "Length 2^{} failed", l+5);
+    }
+}
+
+#[test]
+fn small_polynomial_test(){
+    use crate::{prover::ProveError, utils::NUM_QUERIES};
+
+    let mut rng = thread_rng();
+
+    //The smallest polynomials fill a handful of BinaryField32b, down to a single one, so they get fewer columns than queries and, for odd l, one more column than rows.
+    //prove and verify refuse to draw NUM_QUERIES distinct queries from so few columns, but they can still all be opened at once.
+    for l in [0, 1, 2, 3, 5]{
+        let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+
+        assert_eq!((commitment.rows, commitment.cols), (l/2, (l + 1)/2), "Length 2^{} is split wrongly", l+5);
+        assert_eq!(encoded_poly.rows*encoded_poly.cols, poly.len());
+
+        let available = encoded_poly.cols;
+        assert_eq!(prove(&poly, &encoded_poly, &merkle_tree, &point).unwrap_err(), ProveError::TooManyQueries { requested: NUM_QUERIES, available });
+
+        let queries:Vec<usize> = (0..available).collect();
+        let eval_proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+        assert_eq!(verify(commitment.clone(), eval, eval_proof.clone(), point.clone(), &ntt), Err(VerifyError::TooManyQueries { requested: NUM_QUERIES, available }));
+        assert_eq!(verify_with_queries(commitment, eval, eval_proof, point, queries, &ntt), Ok(()), "Length 2^{} failed", l+5);
+    }
+}
+
+#[test]
+fn rate_test(){
+    use crate::{prover::commit_with_params, utils::encoding::EncodingParams};
+
+    let mut rng = thread_rng();
+    let l = MIN_PROVABLE_LOG_LEN;
+    let Opening{ poly, ntt, point, eval, .. } = random_opening(l, &mut rng);
+
+    //Commit, prove and verify end to end at rates other than the default, the verifier re-encoding at the rate recorded in the commitment.
+    for rate in [2, 8]{
+        let params = EncodingParams{ rate, packing_degree: PACKING_DEGREE };
+        let (commitment, merkle_tree, encoded_poly) = commit_with_params(&poly, &ntt, params);
+        assert_eq!(commitment.encoding_params(), params);
+        assert_eq!(encoded_poly.codeword_len(), rate*encoded_poly.message_len());
+
+        let eval_proof = prove(&poly, &encoded_poly, &merkle_tree, &point).unwrap();
+        assert_eq!(verify(commitment.clone(), eval, eval_proof.clone(), point.clone(), &ntt), Ok(()), "Rate 1/{rate} failed");
+        assert_eq!(verify(commitment, eval + BinaryField128b::ONE, eval_proof, point.clone(), &ntt), Err(VerifyError::EvaluationMismatch));
+    }
+}
+
+//Smallest l for which a polynomial of 2^l packed coefficients is laid out in at least NUM_QUERIES columns, so that prove and verify can open it.
+#[cfg(test)]
+pub(crate) const MIN_PROVABLE_LOG_LEN:usize = 15;
+
+//The setup shared by the tests that open a commitment, a random polynomial with 2^l coefficients committed to with commit,
+//and its evaluation at a random point with every coordinate a full F_128 element.
+#[cfg(test)]
+pub(crate) struct Opening{
+    pub poly: Vec<BinaryField32b>,
+    pub ntt: AdditiveNTT,
+    pub commitment: Commitment,
+    pub merkle_tree: MerkleTree,
+    pub encoded_poly: Code,
+    pub point: Vec<BinaryField128b>,
+    pub eval: BinaryField128b
+}
+
+#[cfg(test)]
+pub(crate) fn random_opening(l:usize, rng:&mut impl RngCore)->Opening{
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut *rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let (commitment, merkle_tree, encoded_poly) = commit(&poly, &ntt);
+
+    let point:Vec<BinaryField128b> = (0..l + PACKING_DEGREE).map(|_| BinaryField128b::random(&mut *rng)).collect();
+    let eval = evaluate_unpacked(&poly, &compute_fourier_bases(&point));
+
+    Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval }
+}
+
+//Opens random_opening(num_vars, ..) at random queries, all derived from seed, then verifies the proof once tamper has been applied to it.
+#[cfg(test)]
+fn verify_random_opening(num_vars:usize, seed:u64, tamper: impl FnOnce(&mut EvalProof))->Result<(), VerifyError>{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(num_vars, &mut rng);
+
+    let num_queries = rng.gen_range(1..=encoded_poly.cols);
+    let queries:Vec<usize> = (0..num_queries).map(|_| rng.gen_range(0..encoded_poly.cols)).collect();
+    let mut proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+    tamper(&mut proof);
+
+    verify_with_queries(commitment, eval, proof, point, queries, &ntt)
+}
+
+#[cfg(test)]
+proptest!{
+    #[test]
+    fn round_trip_proptest(num_vars in 4usize..10, seed in any::<u64>()){
+        assert_eq!(verify_random_opening(num_vars, seed, |_| ()), Ok(()));
+    }
+
+    #[test]
+    fn tampered_column_proptest(num_vars in 4usize..10, seed in any::<u64>(), column in any::<usize>(), entry in any::<usize>(), byte in 0usize..4){
+        let result = verify_random_opening(num_vars, seed, |proof|{
+            let column = &mut proof.queried_columns[column % proof.queried_columns.len()];
+            let entry = entry % column.len();
+            column[entry] = BinaryField32b::new(column[entry].val() ^ (1<<(8*byte)));
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn tampered_fold_proptest(num_vars in 4usize..10, seed in any::<u64>(), position in any::<usize>()){
+        let result = verify_random_opening(num_vars, seed, |proof|{
+            let position = position % proof.folded_poly.len();
+            proof.folded_poly[position] += BinaryField128b::ONE;
+        });
+        assert!(result.is_err());
+    }
+}
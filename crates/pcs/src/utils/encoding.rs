@@ -1,29 +1,110 @@
+use std::sync::OnceLock;
+
 use binius_field::{BinaryField128b, BinaryField32b, Field};
-use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+use sha3::{Digest, Keccak256};
 
-use crate::utils::{packed_arithmetic::PackedAlgebra32, ntt::AdditiveNTT};
+use crate::{utils::{merkle::{Hash, HASH_LEN}, packed_arithmetic::PackedAlgebra32, ntt::AdditiveNTT}, verifier::unpacked_linear_combination};
 
-const RATE:usize =  4;
-const PACKING_DEGREE:usize = 5;
+pub(crate) const RATE:usize =  4;
+pub const PACKING_DEGREE:usize = 5;
 
+//The rate of the code, i.e the number of blocks of the length of the message in a codeword, and the log of the number of coefficients packed into each
+//BinaryField32b of the coefficient matrix, which together fix the shape Code::new lays a polynomial out in. A lower rate gives shorter codewords but less distance,
+//so more queries are needed for the same security. The default is the RATE and PACKING_DEGREE commitments are made and verified with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EncodingParams{
+    pub rate: usize,
+    pub packing_degree: usize
+}
 
-pub fn encode(message:&Vec<BinaryField32b>, ntt:&AdditiveNTT)->Vec<BinaryField32b>{
+impl Default for EncodingParams{
+    fn default()->Self{
+        EncodingParams{
+            rate: RATE,
+            packing_degree: PACKING_DEGREE
+        }
+    }
+}
 
-    let mut code = message.clone();
+//Encodes message at rate 1/params.rate, the message followed by params.rate - 1 parity blocks. Errors if the message length isn't a non-zero power of two
+//or the blocks would be evaluated on colliding cosets, see validate_encode_cosets.
+pub fn encode(message:&Vec<BinaryField32b>, ntt:&AdditiveNTT, params:EncodingParams)->Result<Vec<BinaryField32b>, EncodeError>{
+    validate_encode_cosets(message.len(), params.rate)?;
+    Ok(encode_unchecked(message, ntt, params))
+}
 
-    let mut inverse = message.clone();
+//encode for a message whose length has already been checked with validate_encode_cosets.
+fn encode_unchecked(message:&[BinaryField32b], ntt:&AdditiveNTT, params:EncodingParams)->Vec<BinaryField32b>{
+
+    let mut code = message.to_vec();
+
+    let mut inverse = message.to_vec();
 
     ntt.inverse_ntt(&mut inverse, 0);
-    for i in 1..RATE{
+    for i in 1..params.rate{
         let mut encode = inverse.clone();
-        ntt.forward_ntt(&mut encode, (i*message.len()) as u32);
+        ntt.forward_ntt_on_coset(&mut encode, block_coset(i, message.len()));
         code.append(&mut encode.clone());
     }
 
     code
 }
 
-pub fn encode_extension(message:&Vec<BinaryField128b>, ntt:&AdditiveNTT)->Vec<PackedAlgebra32>{
+//The coset block of a codeword made of blocks of block_len elements is evaluated on, i.e block*block_len as a field element.
+//The product is checked to fit in a u32 rather than truncated, which validate_encode_cosets reports as a collision for encode.
+fn block_coset(block:usize, block_len:usize)->BinaryField32b{
+    let coset = u32::try_from(block*block_len).unwrap_or_else(|_| panic!("Coset of block {block} of length {block_len} doesn't fit in a BinaryField32b."));
+    BinaryField32b::from(coset)
+}
+
+//Error thrown when a message can't be encoded with full distance, or the rows of a coefficient matrix are combined with the wrong number of scalars.
+#[derive(Clone, thiserror::Error, Debug, PartialEq, Eq)]
+pub enum EncodeError {
+    #[error("blocks {first} and {second} of the codeword are evaluated on the same coset {coset:#x}")]
+    CosetCollision { first: usize, second: usize, coset: u32 },
+    #[error("{scalars} scalars given for a linear combination of {rows} rows")]
+    ScalarCountMismatch { rows: usize, scalars: usize },
+    #[error("rows of 2^{log_cols} columns can't be encoded at rate 2^-{log_inv_rate}")]
+    UnsupportedRate { log_inv_rate: usize, log_cols: usize },
+    #[error("the message length {len} must be a non-zero power of two")]
+    InvalidMessageLength { len: usize },
+}
+
+//Checks the cosets encode evaluates the rate blocks of the codeword on are distinct. Block i is evaluated on the domain shifted by i*message_len as a u32,
+//and the low message_len.trailing_zeros() bits of the shift lie in the domain itself, so two blocks collide when their shifts agree above those bits.
+//This happens when i*message_len doesn't fit in a u32, e.g for messages of 2^31 elements at rate 4. Lengths that aren't a non-zero power of two have no
+//ntt domain to begin with, and are reported as such rather than as a collision.
+pub fn validate_encode_cosets(message_len:usize, rate:usize)->Result<(), EncodeError>{
+    if !message_len.is_power_of_two(){
+        return Err(EncodeError::InvalidMessageLength { len: message_len });
+    }
+
+    let domain_bits = message_len.trailing_zeros();
+    let cosets:Vec<u32> = (0..rate).map(|i| ((i*message_len) as u32).checked_shr(domain_bits).unwrap_or(0)).collect();
+
+    for second in 1..rate{
+        if let Some(first) = cosets[..second].iter().position(|&coset| coset == cosets[second]){
+            return Err(EncodeError::CosetCollision { first, second, coset: cosets[second] });
+        }
+    }
+    Ok(())
+}
+
+//Checks rows of 2^log_cols message columns can be encoded at rate 2^-log_inv_rate, i.e the codeword has at least one parity block and its 2^(log_cols + log_inv_rate) entries
+//fit in the BinaryField32b domain. For a power of two message length that is exactly when validate_encode_cosets passes, without building the 2^log_inv_rate cosets,
+//which matters as log_inv_rate may come from an untrusted commitment.
+pub fn validate_rate(log_inv_rate:usize, log_cols:usize)->Result<(), EncodeError>{
+    if log_inv_rate == 0 || log_cols + log_inv_rate > 32{
+        return Err(EncodeError::UnsupportedRate { log_inv_rate, log_cols });
+    }
+    Ok(())
+}
+
+//The extension analog of encode, a message of 32*n BinaryField128b is packed into n PackedAlgebra32 which are encoded like a message of n BinaryField32b.
+//The cosets are multiples of the packed length n, the length of the transform, so that encoding the rows with encode and then folding them agrees with this.
+pub fn encode_extension(message:&Vec<BinaryField128b>, ntt:&AdditiveNTT, params:EncodingParams)->Vec<PackedAlgebra32>{
 
     let packed_message = PackedAlgebra32::pack(message.clone());
     let mut code = packed_message.clone();
@@ -31,9 +112,9 @@ pub fn encode_extension(message:&Vec<BinaryField128b>, ntt:&AdditiveNTT)->Vec<Pa
     let mut inverse = packed_message.clone();
 
     ntt.inverse_ntt(&mut inverse, 0);
-    for i in 1..RATE{
+    for i in 1..params.rate{
         let mut encode = inverse.clone();
-        ntt.forward_ntt(&mut encode, (i*message.len()) as u32);
+        ntt.forward_ntt_on_coset(&mut encode, block_coset(i, packed_message.len()));
         code.append(&mut encode.clone());
     }
 
@@ -41,66 +122,393 @@ pub fn encode_extension(message:&Vec<BinaryField128b>, ntt:&AdditiveNTT)->Vec<Pa
 }
 
 
-pub fn encode_interleaved(poly: &Vec<Vec<BinaryField32b>>, ntt:&AdditiveNTT, rows:usize, cols:usize)->Vec<Vec<BinaryField32b>>{
-    let code:Vec<Vec<BinaryField32b>> = (0..rows).into_par_iter().map(|row| encode(&poly[row], ntt)).collect();
+//Same as encode_extension but writes the codeword into code, which must hold params.rate*message.len()/32 packed elements, so no allocation is made.
+pub fn encode_extension_into(message:&[BinaryField128b], code:&mut [PackedAlgebra32], ntt:&AdditiveNTT, params:EncodingParams){
+
+    let block_len = message.len()/32;
+    assert_eq!(code.len(), params.rate*block_len, "Codeword buffer has the wrong length.");
+
+    let (systematic, parity) = code.split_at_mut(block_len);
+    systematic.iter_mut().zip(message.chunks(32)).for_each(|(packed, chunk)| packed.0.copy_from_slice(chunk));
+
+    //The first parity block holds the inverse transform until it has been copied to the other blocks.
+    let (first, rest) = parity.split_at_mut(block_len);
+    first.copy_from_slice(systematic);
+    ntt.inverse_ntt_slice(first, BinaryField32b::ZERO);
+
+    for (i, block) in rest.chunks_mut(block_len).enumerate(){
+        block.copy_from_slice(first);
+        ntt.forward_ntt_slice(block, block_coset(i + 2, block_len));
+    }
+    ntt.forward_ntt_slice(first, block_coset(1, block_len));
+}
+
+//Recovers the message from a codeword produced by encode_extension with the same params, which being systematic is the unpacked first block.
+pub fn decode_extension(code:&[PackedAlgebra32], ntt:&AdditiveNTT, params:EncodingParams)->Vec<BinaryField128b>{
+
+    assert_eq!(code.len()%params.rate, 0, "Codeword length must be a multiple of the rate.");
+    let message = PackedAlgebra32::unpack(code[..code.len()/params.rate].to_vec());
+
+    debug_assert_eq!(encode_extension(&message, ntt, params), code, "Codeword is not an encoding of its systematic part.");
+    message
+}
+
+//Computes encode_extension(folded_poly, ntt, params)[query] without encoding the whole message, see encode_extension_at.
+pub fn eval_folded_at_column(folded_poly:&[BinaryField128b], query:usize, ntt:&AdditiveNTT, params:EncodingParams)->PackedAlgebra32{
+    encode_extension_at(folded_poly, &[query], ntt, params)[0]
+}
+
+//Computes the entries of encode_extension(folded_poly, ntt, params) at positions without encoding the whole message. Positions in the systematic part are read off directly,
+//for the others we still need the inverse transform of the message once, but then only evaluate a single output of the forward transform for the block each one falls in.
+//That is O(n) per position instead of O(n log n) per block, so this is cheaper as long as there are fewer positions than about params.rate*log(n).
+pub fn encode_extension_at(folded_poly:&[BinaryField128b], positions:&[usize], ntt:&AdditiveNTT, params:EncodingParams)->Vec<PackedAlgebra32>{
+
+    let packed_message = PackedAlgebra32::pack(folded_poly.to_vec());
+    let block_len = packed_message.len();
+
+    let mut inverse = packed_message.clone();
+    if positions.iter().any(|&position| position >= block_len){
+        ntt.inverse_ntt(&mut inverse, 0);
+    }
+
+    par_iter!(positions).map(|&position|{
+        let (block, offset) = (position/block_len, position%block_len);
+        assert!(block < params.rate, "Position {position} is outside of the codeword.");
+
+        if block == 0{
+            packed_message[offset]
+        }
+        else{
+            ntt.forward_ntt_single(&inverse, (block*block_len) as u32, offset)
+        }
+    }).collect()
+}
+
+//Encodes message in rows of cols coefficients straight into a flat row-major codeword, without laying the rows out as a coefficient matrix first.
+//message has to be a whole number of rows, errors like encode if rows of cols coefficients can't be encoded.
+fn encode_rows(message:&[BinaryField32b], cols:usize, ntt:&AdditiveNTT, params:EncodingParams)->Result<Vec<BinaryField32b>, EncodeError>{
+    validate_encode_cosets(cols, params.rate)?;
+    debug_assert_eq!(message.len()%cols, 0, "Message is not a whole number of rows.");
+
+    let mut code = vec![BinaryField32b::ZERO; params.rate*message.len()];
+
+    par_chunks_mut!(code, params.rate*cols).zip(par_chunks!(message, cols))
+    .for_each(|(code_row, message_row)| code_row.copy_from_slice(&encode_unchecked(message_row, ntt, params)));
+    Ok(code)
+}
+
+//Encodes each of the first rows rows of poly like encode, transforming the rows together with the batched ntts so every round of
+//the inverse and of each forward transform shares its twiddles across all rows.
+pub fn encode_interleaved(poly: &Vec<Vec<BinaryField32b>>, ntt:&AdditiveNTT, rows:usize, cols:usize, params:EncodingParams)->Vec<Vec<BinaryField32b>>{
+
+    if let Err(err) = validate_encode_cosets(cols, params.rate){
+        panic!("{err}");
+    }
+
+    let mut inverse = poly[..rows].to_vec();
+    ntt.inverse_ntt_batch(&mut inverse, BinaryField32b::ZERO);
+
+    let mut code:Vec<Vec<BinaryField32b>> = poly[..rows].iter().map(|row|{
+        let mut code_row = Vec::with_capacity(params.rate*cols);
+        code_row.extend_from_slice(row);
+        code_row
+    }).collect();
+
+    for i in 1..params.rate{
+        let mut block = inverse.clone();
+        ntt.forward_ntt_batch(&mut block, block_coset(i, cols));
+        par_iter_mut!(code).zip(par_iter!(block)).for_each(|(code_row, block_row)| code_row.extend_from_slice(block_row));
+    }
+
     code
 }
 
-#[derive(Debug)]
+//Base-2 logarithms of the number of rows and message columns Code::new lays a message of 2^log_message_len BinaryField32b coefficients out in.
+pub(crate) fn log_dimensions(log_message_len:usize, rate:usize, packing_degree:usize)->(usize, usize){
+    let mut log_cols = (1<<packing_degree) + packing_degree - (rate.trailing_zeros() as usize);
+
+    if log_message_len<log_cols{
+        log_cols = (log_message_len+1)/2
+    }
+    (log_message_len - log_cols, log_cols)
+}
+
+//Estimates the bytes needed to commit to a message of 2^log_message_len BinaryField32b coefficients, before doing so.
+//This is the codeword, rows*cols*rate entries of 4 bytes, the message columns cached when commit hashes them, rows*cols entries and a Vec header per column,
+//plus the 2*cols - 1 nodes of the merkle tree over the message columns, matching Code::memory_usage and MerkleTree::memory_usage after commit.
+pub fn estimate_code_memory(log_message_len:usize, rate:usize, packing_degree:usize)->usize{
+    let (log_rows, log_cols) = log_dimensions(log_message_len, rate, packing_degree);
+    let (rows, cols) = (1<<log_rows, 1<<log_cols);
+
+    let codeword = rows*cols*rate*std::mem::size_of::<BinaryField32b>();
+    let column_cache = rows*cols*std::mem::size_of::<BinaryField32b>() + cols*std::mem::size_of::<Vec<BinaryField32b>>();
+    let merkle_tree = (2*cols - 1)*(std::mem::size_of::<Hash>() + HASH_LEN);
+
+    codeword + column_cache + merkle_tree
+}
+
+//Error thrown when a codeword handed to us does not have the shape of an interleaved code.
+#[derive(Clone, thiserror::Error, Debug, PartialEq, Eq)]
+pub enum CodeError {
+    #[error("the number of message columns {cols} must be a non-zero power of two")]
+    InvalidColumnCount { cols: usize },
+    #[error("the number of rows {rows} must be a non-zero power of two")]
+    InvalidRowCount { rows: usize },
+    #[error("expected {expected} codeword columns, got {got}")]
+    ColumnCountMismatch { expected: usize, got: usize },
+    #[error("column {col} has length {got}, expected {expected}")]
+    ColumnLengthMismatch { col: usize, expected: usize, got: usize },
+}
+
+//The codeword is stored flat in row-major order, each row being the params.rate*cols entries of the encoding of a row of the coefficient matrix.
+//The message columns are only laid out on their own once message_columns is called, after which col reads them from there.
 pub struct Code{
-    code:Vec<Vec<BinaryField32b>>,
+    code:Vec<BinaryField32b>,
     pub rows: usize,
-    pub cols: usize
+    pub cols: usize,
+    params: EncodingParams,
+    columns: OnceLock<Vec<Vec<BinaryField32b>>>
+}
+
+//Only the shape of the code, the codeword of a committed polynomial is far too large to end up in logs, see dump_matrix for the entries.
+impl std::fmt::Debug for Code{
+    fn fmt(&self, f:&mut std::fmt::Formatter<'_>)->std::fmt::Result{
+        f.debug_struct("Code")
+        .field("rows", &self.rows)
+        .field("cols", &self.cols)
+        .field("codeword_len", &self.codeword_len())
+        .field("rate", &self.params.rate)
+        .finish()
+    }
 }
 
 impl Code{
+    //Errors if the length of poly isn't a non-zero power of two, or its rows can't be encoded at params.rate, see validate_encode_cosets.
     pub fn new(
-        poly: &Vec<BinaryField32b>,
-        ntt:&AdditiveNTT
-    )->Code{
-
-        let variables = poly.len().trailing_zeros() as usize;
-        let mut log_cols = (1<<PACKING_DEGREE) + PACKING_DEGREE - (RATE.trailing_zeros() as usize);
+        poly: &[BinaryField32b],
+        ntt:&AdditiveNTT,
+        params:EncodingParams
+    )->Result<Code, EncodeError>{
 
-        if variables<log_cols{
-            log_cols = (variables+1)/2
+        assert!(params.rate.is_power_of_two(), "Rate must be a power of two.");
+        if !poly.len().is_power_of_two(){
+            return Err(EncodeError::InvalidMessageLength { len: poly.len() });
         }
-        let log_rows = variables - log_cols;
+
+        let variables = poly.len().trailing_zeros() as usize;
+        let (log_rows, log_cols) = log_dimensions(variables, params.rate, params.packing_degree);
 
         let cols = 1<<log_cols;
         let rows = 1<<log_rows;
 
-        let coeff_matrix:Vec<Vec<BinaryField32b>> = make_coeff_matrix(poly, cols);
-        let code = encode_interleaved(&coeff_matrix, ntt,rows, cols);
-        Code{
+        let code = encode_rows(poly, cols, ntt, params)?;
+        Ok(Code{
+            code,
+            rows,
+            cols,
+            params,
+            columns: OnceLock::new()
+        })
+    }
+
+    //Lays poly out in rows of 2^log_cols coefficients instead of the shape Code::new picks. poly is padded with zeros to the next power of two,
+    //and at least a single row, so the rows are always whole and a power of two in number. Errors if rows of 2^log_cols can't be encoded.
+    pub fn with_log_cols(
+        poly: &[BinaryField32b],
+        log_cols: usize,
+        ntt:&AdditiveNTT
+    )->Result<Code, EncodeError>{
+
+        let (cols, params) = (1<<log_cols, EncodingParams::default());
+        //Checked before padding, which would otherwise allocate a row of a width that can't be encoded.
+        validate_encode_cosets(cols, params.rate)?;
+
+        let mut padded = poly.to_vec();
+        padded.resize(poly.len().next_power_of_two().max(cols), BinaryField32b::ZERO);
+        let rows = padded.len()/cols;
+
+        let code = encode_rows(&padded, cols, ntt, params)?;
+        Ok(Code{
             code,
             rows,
-            cols
+            cols,
+            params,
+            columns: OnceLock::new()
+        })
+    }
+
+    //Encodes a coefficient matrix that has already been laid out, e.g row by row from a stream, with one row per entry of coeff_matrix.
+    //Errors like Code::new if its rows can't be encoded, an empty matrix having rows of length 0.
+    pub fn from_coeff_matrix(
+        coeff_matrix: &[Vec<BinaryField32b>],
+        ntt:&AdditiveNTT,
+        params:EncodingParams
+    )->Result<Code, EncodeError>{
+
+        let (rows, cols) = (coeff_matrix.len(), coeff_matrix.first().map_or(0, Vec::len));
+        assert!(coeff_matrix.iter().all(|row| row.len() == cols), "Rows of the coefficient matrix need the same length.");
+
+        let code = encode_rows(&coeff_matrix.concat(), cols, ntt, params)?;
+        Ok(Code{
+            code,
+            rows,
+            cols,
+            params,
+            columns: OnceLock::new()
+        })
+    }
+
+    //Stacks the rows of codes with the same number of columns on top of each other, in the given order. The total number of rows need not be a power of two.
+    pub fn stack(
+        codes: Vec<Code>
+    )->Code{
+
+        let (cols, params) = (codes[0].cols, codes[0].params);
+        assert!(codes.iter().all(|code| code.cols == cols), "Stacked codes need the same number of columns.");
+        assert!(codes.iter().all(|code| code.params == params), "Stacked codes need the same encoding params.");
+
+        let rows = codes.iter().map(|code| code.rows).sum();
+        let code:Vec<BinaryField32b> = codes.into_iter().flat_map(|code| code.code).collect();
+        Code{
+            rows,
+            code,
+            cols,
+            params,
+            columns: OnceLock::new()
         }
     }
 
+    //Builds a code from an already computed codeword, given column by column. The codeword is expected to have params.rate*cols columns each of length rows,
+    //but nothing is checked beyond the dimensions, so this can also be used to load a deliberately corrupted codeword.
+    pub fn from_columns(
+        code: Vec<Vec<BinaryField32b>>,
+        rows: usize,
+        cols: usize,
+        params: EncodingParams
+    )->Result<Code, CodeError>{
+
+        if !cols.is_power_of_two(){
+            return Err(CodeError::InvalidColumnCount { cols });
+        }
+        if !rows.is_power_of_two(){
+            return Err(CodeError::InvalidRowCount { rows });
+        }
+        if code.len() != params.rate*cols{
+            return Err(CodeError::ColumnCountMismatch { expected: params.rate*cols, got: code.len() });
+        }
+        if let Some(col) = code.iter().position(|column| column.len() != rows){
+            return Err(CodeError::ColumnLengthMismatch { col, expected: rows, got: code[col].len() });
+        }
+
+        //Columns are stored row-wise internally, so we transpose.
+        let width = code.len();
+        let code = into_par_iter!(0..rows*width).map(|i| code[i%width][i/width]).collect();
+
+        Ok(Code{
+            code,
+            rows,
+            cols,
+            params,
+            columns: OnceLock::new()
+        })
+    }
+
     pub fn make_linear_combination(
         &self,
         scalars:Vec<BinaryField128b>
-    )->Vec<BinaryField128b>{
+    )->Result<Vec<BinaryField128b>, EncodeError>{
 
-        assert_eq!(self.rows, scalars.len());
+        if scalars.len() != self.rows{
+            return Err(EncodeError::ScalarCountMismatch { rows: self.rows, scalars: scalars.len() });
+        }
 
         let mut result = vec![BinaryField128b::ZERO; self.cols*32];
 
-        result.par_iter_mut().chunks(32).enumerate()
-        .for_each(|(col, mut entry)|{
+        par_chunks_mut!(result, 32).enumerate()
+        .for_each(|(col, entry)|{
 
             for row in 0..self.rows{
                 for bit in 0..32{
-                    if (self.code[row][col].val()>>bit)&1 == 1{
-                    *entry[bit] += scalars[row];
+                    if (self.row(row)[col].val()>>bit)&1 == 1{
+                    entry[bit] += scalars[row];
                     }
                 }
             }
         }
         );
-        result
+        Ok(result)
+    }
+
+    //Checks the code is systematic, i.e the first cols columns of every row are the corresponding row of the original message.
+    pub fn assert_systematic(
+        &self,
+        original:&[BinaryField32b]
+    )->bool{
+
+        if original.len() != self.rows*self.cols{
+            return false;
+        }
+
+        par_chunks!(self.code, self.width()).zip(par_chunks!(original, self.cols))
+        .all(|(row, message_row)| row[..self.cols] == *message_row)
+    }
+
+    //Recovers the original polynomial from the systematic part of the codeword, in the order it was committed in.
+    pub fn message(
+        &self
+    )->Vec<BinaryField32b>{
+        self.code.chunks(self.width()).flat_map(|row| row[..self.cols].iter().copied()).collect()
+    }
+
+    //The running sums of the coefficients of the original polynomial in hypercube order, i.e entry i is the sum of the coefficients 0..=i,
+    //as needed for grand product and permutation arguments over the committed polynomial.
+    pub fn prefix_sums(
+        &self
+    )->Vec<BinaryField32b>{
+        self.message().into_iter().scan(BinaryField32b::ZERO, |sum, coeff|{
+            *sum += coeff;
+            Some(*sum)
+        }).collect()
+    }
+
+    //Sets the coefficient at index in the original polynomial to value and re-encodes the single row it lives in.
+    pub fn update_coefficient(
+        &mut self,
+        index:usize,
+        value:BinaryField32b,
+        ntt:&AdditiveNTT
+    ){
+        assert!(index < self.rows*self.cols, "Coefficient index {index} out of range.");
+
+        let (row, col) = (index/self.cols, index%self.cols);
+        let mut message = self.row(row)[..self.cols].to_vec();
+        message[col] = value;
+
+        //The row length was checked when the code was built.
+        let width = self.width();
+        self.code[row*width..(row + 1)*width].copy_from_slice(&encode_unchecked(&message, ntt, self.params));
+        self.columns.take();
+    }
+
+    //Renders the codeword one row per line, with a bar separating the systematic part from the parity part.
+    pub fn pretty(
+        &self,
+        radix:Radix
+    )->String{
+
+        self.code.chunks(self.width()).map(|row|{
+            let (message, parity) = row.split_at(self.cols.min(row.len()));
+            let render = |part:&[BinaryField32b]| part.iter().map(|&v| fmt_field32(v, radix)).collect::<Vec<_>>().join(" ");
+            format!("{} | {}", render(message), render(parity))
+        }).collect::<Vec<_>>().join("\n")
+    }
+
+    //Every entry of the codeword, one row per line, for debugging small codes. Only available in debug builds, see pretty for a rendering of the entries in a given radix.
+    #[cfg(debug_assertions)]
+    pub fn dump_matrix(
+        &self
+    )->String{
+        self.code.chunks(self.width()).map(|row| format!("{row:?}")).collect::<Vec<_>>().join("\n")
     }
 
     pub fn col(
@@ -110,48 +518,201 @@ impl Code{
 
         assert!(col < self.cols);
 
+        if let Some(columns) = self.columns.get(){
+            return columns[col].clone();
+        }
+
         let mut result = vec![BinaryField32b::ZERO; self.rows];
 
-        result.par_iter_mut().enumerate()
+        par_iter_mut!(result).enumerate()
         .for_each(|(row, entry)|{
-                *entry = self.code[row][col];
+                *entry = self.row(row)[col];
         }
         );
         result
     }
 
+    //All the message columns, transposed out of the codeword in a single pass the first time this is called and kept for later calls,
+    //so commit hashing every column and prove opening some of them extract each column only once. The copy takes rows*cols more entries,
+    //which memory_usage counts once it has been made.
+    pub fn message_columns(
+        &self
+    )->&[Vec<BinaryField32b>]{
+        self.columns.get_or_init(|| into_par_iter!(0..self.cols).map(|col| (0..self.rows).map(|row| self.row(row)[col]).collect()).collect())
+    }
+
+    //Same as col, but for any of the rate*cols columns of the codeword, including the parity part.
+    pub fn codeword_col(
+        &self,
+        col:usize
+    )->Vec<BinaryField32b>{
+
+        assert!(col < self.width());
+
+        (0..self.rows).map(|row| self.row(row)[col]).collect()
+    }
+
+    //Codeword column col, any of the rate*cols, folded with scalars, the eq table of the row part of a point. This is the entry at col of
+    //encode_extension of the folded polynomial, the representation the verifier re-encodes it in, so both sides compare the same PackedAlgebra32.
+    pub fn packed_col(
+        &self,
+        col:usize,
+        scalars:&[BinaryField128b]
+    )->PackedAlgebra32{
+
+        assert_eq!(scalars.len(), self.rows, "Need a scalar per row to fold a column.");
+        unpacked_linear_combination(scalars, &self.codeword_col(col))
+    }
+
+    //packed_col for every column of the codeword, in order.
+    pub fn packed_columns<'a>(
+        &'a self,
+        scalars:&'a [BinaryField128b]
+    )->impl Iterator<Item = PackedAlgebra32> + 'a{
+        (0..self.width()).map(move |col| self.packed_col(col, scalars))
+    }
+
+    //Bytes taken by the codeword entries and, once message_columns has been called, by the cached columns, their entries and a Vec header each.
+    pub fn memory_usage(
+        &self
+    )->usize{
+        let column_cache = self.columns.get().map_or(0, |columns|{
+            columns.iter().map(|column| column.len()*std::mem::size_of::<BinaryField32b>() + std::mem::size_of::<Vec<BinaryField32b>>()).sum()
+        });
+
+        self.code.len()*std::mem::size_of::<BinaryField32b>() + column_cache
+    }
+
+    //Hashes the dimensions and then every column of the codeword, in column order, into a single Keccak256 digest.
+    //Comparing digests is a cheap way to check two codes are identical, e.g before comparing Merkle roots or as a cache key.
+    pub fn digest(
+        &self
+    )->Hash{
+
+        let mut hasher = Keccak256::new();
+        hasher.update((self.rows as u64).to_le_bytes());
+        hasher.update((self.cols as u64).to_le_bytes());
+
+        for col in 0..self.width(){
+            (0..self.rows).for_each(|row| hasher.update(self.row(row)[col].val().to_le_bytes()));
+        }
+        Hash(hasher.finalize().to_vec())
+    }
+
+    //The params the rows were encoded with.
+    pub fn params(
+        &self
+    )->EncodingParams{
+        self.params
+    }
+
+    //Base-2 logarithm of the inverse rate, i.e of the number of message length blocks in the codeword of a row.
+    pub fn log_inv_rate(
+        &self
+    )->usize{
+        self.params.rate.trailing_zeros() as usize
+    }
+
+    //Number of BinaryField32b coefficients in a row of the coefficient matrix, the message each row codeword encodes.
+    pub fn message_len(
+        &self
+    )->usize{
+        self.cols
+    }
+
+    //Number of entries in the codeword of a row, message_len times the inverse rate.
+    pub fn codeword_len(
+        &self
+    )->usize{
+        self.cols<<self.log_inv_rate()
+    }
+
+    //Number of variables in the column part of a point for the encoded polynomial, log cols selecting the column and packing_degree the bit of the packed entry,
+    //see Commitment::col_point_len.
+    pub fn col_point_len(
+        &self
+    )->usize{
+        self.cols.trailing_zeros() as usize + self.params.packing_degree
+    }
+
+    //Number of entries in a row of the codeword.
+    fn width(
+        &self
+    )->usize{
+        self.codeword_len()
+    }
+
+    fn row(
+        &self,
+        row:usize
+    )->&[BinaryField32b]{
+        &self.code[row*self.width()..(row + 1)*self.width()]
+    }
+
 }
 
+//Radix used when rendering field elements for debugging.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Radix{
+    Binary,
+    Decimal,
+    Hex
+}
+
+//Renders the underlying bits of a BinaryField32b, binary and hex are zero padded to the full 32 bits so columns line up.
+pub fn fmt_field32(v:BinaryField32b, radix:Radix)->String{
+    match radix {
+        Radix::Binary => format!("{:#034b}", v.val()),
+        Radix::Decimal => format!("{}", v.val()),
+        Radix::Hex => format!("{:#010x}", v.val())
+    }
+}
+
+//Lays poly out in rows of cols coefficients. The length of poly has to be a multiple of cols, otherwise the last row would come out shorter than the others.
 pub fn make_coeff_matrix(poly: &Vec<BinaryField32b>, cols:usize)->Vec<Vec<BinaryField32b>> {
+    assert!(cols > 0 && poly.len()%cols == 0, "A polynomial of length {} can't be laid out in rows of {cols} coefficients.", poly.len());
     poly.chunks(cols).map(|row| row.to_vec()).collect()
 }
 
+//The coefficients of the transpose of make_coeff_matrix(poly, cols), read row by row, i.e the entry at col*rows + row is poly[row*cols + col].
+pub fn transpose_coefficients(poly: &[BinaryField32b], cols:usize)->Vec<BinaryField32b> {
+    let rows = poly.len()/cols;
+    (0..poly.len()).map(|i| poly[(i%rows)*cols + i/rows]).collect()
+}
+
 pub fn make_linear_combination(
     poly: Vec<Vec<BinaryField32b>>,
     scalars:Vec<BinaryField128b>
-)->Vec<BinaryField128b>{
+)->Result<Vec<BinaryField128b>, EncodeError>{
 
-    assert_eq!(poly.len(), scalars.len());
+    if scalars.len() != poly.len(){
+        return Err(EncodeError::ScalarCountMismatch { rows: poly.len(), scalars: scalars.len() });
+    }
 
     let mut result = vec![BinaryField128b::ZERO; poly[0].len()*32];
 
-    result.par_iter_mut().chunks(32).enumerate()
-    .for_each(|(col, mut entry)|{
+    par_chunks_mut!(result, 32).enumerate()
+    .for_each(|(col, entry)|{
 
         for row in 0..poly.len(){
             for bit in 0..32{
                 if (poly[row][col].val()>>bit)&1 == 1{
-                *entry[bit] += scalars[row];
+                entry[bit] += scalars[row];
                 }
             }
         }
     }
     );
-    result
+    Ok(result)
 }
 
 //Computes the Fourier coefficients/Lagrange basis evaluations at a random point.
-pub fn compute_fourier_bases(r: &Vec<BinaryField128b>) -> Vec<BinaryField128b> {
+//A point with no coordinates is the single basis evaluation 1, as for a polynomial with a single coefficient.
+pub fn compute_fourier_bases(r: &[BinaryField128b]) -> Vec<BinaryField128b> {
+    if r.is_empty(){
+        return vec![BinaryField128b::ONE];
+    }
+
     //Initialize fc_eq with (1- r[0]) and r[0]
     let mut fc_eq = [BinaryField128b::ONE - r[0], r[0]].to_vec();
     //Iterate over the length of the r vector
@@ -167,4 +728,481 @@ pub fn compute_fourier_bases(r: &Vec<BinaryField128b>) -> Vec<BinaryField128b> {
         }
 }
     fc_eq
-}
\ No newline at end of file
+}
+
+//Same as compute_fourier_bases but writes the 2^r.len() evaluations into out. The table is expanded in place from the back,
+//so entry iter is read before entries 2*iter and 2*iter+1 are overwritten.
+pub fn compute_fourier_bases_into(r: &[BinaryField128b], out: &mut [BinaryField128b]) {
+    assert_eq!(out.len(), 1<<r.len(), "Output buffer has the wrong length.");
+
+    out[0] = BinaryField128b::ONE;
+    for (k, r_k) in r.iter().enumerate() {
+        for iter in (0..1<<k).rev(){
+            out[2*iter+1] = out[iter]*(*r_k);
+            out[2*iter] = out[iter] - out[2*iter+1];
+        }
+    }
+}
+
+#[test]
+fn code_from_columns_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    let poly:Vec<BinaryField32b> = (0..1<<8).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(poly.len().trailing_zeros() as usize);
+    let encoded_poly = Code::new(&poly, &ntt, EncodingParams::default()).unwrap();
+
+    let columns:Vec<Vec<BinaryField32b>> = (0..RATE*encoded_poly.cols).map(|col| (0..encoded_poly.rows).map(|row| encoded_poly.row(row)[col]).collect()).collect();
+    let loaded = Code::from_columns(columns.clone(), encoded_poly.rows, encoded_poly.cols, EncodingParams::default()).unwrap();
+
+    assert_eq!(loaded.code, encoded_poly.code);
+    for (col, column) in columns.iter().enumerate().take(encoded_poly.cols){
+        assert_eq!(loaded.col(col), *column);
+    }
+
+    let scalars:Vec<BinaryField128b> = (0..encoded_poly.rows).map(|_| BinaryField128b::random(&mut rng)).collect();
+    assert_eq!(loaded.make_linear_combination(scalars.clone()), encoded_poly.make_linear_combination(scalars.clone()));
+    assert_eq!(loaded.make_linear_combination(scalars.clone()), make_linear_combination(make_coeff_matrix(&poly, encoded_poly.cols), scalars));
+
+    assert_eq!(
+        Code::from_columns(columns[1..].to_vec(), encoded_poly.rows, encoded_poly.cols, EncodingParams::default()).unwrap_err(),
+        CodeError::ColumnCountMismatch { expected: RATE*encoded_poly.cols, got: RATE*encoded_poly.cols - 1 }
+    );
+
+    let mut short_columns = columns.clone();
+    short_columns[3].pop();
+    assert_eq!(
+        Code::from_columns(short_columns, encoded_poly.rows, encoded_poly.cols, EncodingParams::default()).unwrap_err(),
+        CodeError::ColumnLengthMismatch { col: 3, expected: encoded_poly.rows, got: encoded_poly.rows - 1 }
+    );
+
+    assert_eq!(Code::from_columns(columns, encoded_poly.rows, 3, EncodingParams::default()).unwrap_err(), CodeError::InvalidColumnCount { cols: 3 });
+}
+
+#[test]
+fn systematic_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    for l in 2..12{
+        let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+        let ntt = AdditiveNTT::new(l);
+        let encoded_poly = Code::new(&poly, &ntt, EncodingParams::default()).unwrap();
+
+        assert!(encoded_poly.assert_systematic(&poly), "Code of length 2^{l} is not systematic");
+
+        let mut other = poly.clone();
+        other[poly.len() - 1] += BinaryField32b::ONE;
+        assert!(!encoded_poly.assert_systematic(&other));
+        assert!(!encoded_poly.assert_systematic(&poly[1..]));
+    }
+}
+
+#[test]
+fn flat_code_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    for l in [0, 5, 10]{
+        let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+        let ntt = AdditiveNTT::new(l);
+        let encoded_poly = Code::new(&poly, &ntt, EncodingParams::default()).unwrap();
+        let (rows, cols) = (encoded_poly.rows, encoded_poly.cols);
+
+        let nested = encode_interleaved(&make_coeff_matrix(&poly, cols), &ntt, rows, cols, EncodingParams::default());
+        for col in 0..RATE*cols{
+            let nested_col:Vec<BinaryField32b> = nested.iter().map(|row| row[col]).collect();
+            let flat_col:Vec<BinaryField32b> = (0..rows).map(|row| encoded_poly.row(row)[col]).collect();
+            assert_eq!(flat_col, nested_col);
+            if col < cols{
+                assert_eq!(encoded_poly.col(col), nested_col);
+            }
+        }
+
+        let scalars:Vec<BinaryField128b> = (0..rows).map(|_| BinaryField128b::random(&mut rng)).collect();
+        let nested_message:Vec<Vec<BinaryField32b>> = nested.iter().map(|row| row[..cols].to_vec()).collect();
+        assert_eq!(encoded_poly.make_linear_combination(scalars.clone()), make_linear_combination(nested_message, scalars));
+    }
+}
+
+//Checks encode_interleaved, Code::col and Code::make_linear_combination against plain serial loops for a 2^8 polynomial, so running this with and
+//without the parallel feature checks the rayon and sequential paths agree.
+#[test]
+fn sequential_fallback_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    let l = 8;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let encoded_poly = Code::new(&poly, &ntt, EncodingParams::default()).unwrap();
+    let (rows, cols) = (encoded_poly.rows, encoded_poly.cols);
+
+    let encoded_rows:Vec<Vec<BinaryField32b>> = poly.chunks(cols).map(|row| encode(&row.to_vec(), &ntt, EncodingParams::default()).unwrap()).collect();
+    assert_eq!(encode_interleaved(&make_coeff_matrix(&poly, cols), &ntt, rows, cols, EncodingParams::default()), encoded_rows);
+
+    for col in 0..cols{
+        assert_eq!(encoded_poly.col(col), encoded_rows.iter().map(|row| row[col]).collect::<Vec<_>>());
+    }
+
+    let scalars:Vec<BinaryField128b> = (0..rows).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let mut expected = vec![BinaryField128b::ZERO; 32*cols];
+    for (row, scalar) in encoded_rows.iter().zip(&scalars){
+        for col in 0..cols{
+            for bit in 0..32{
+                if (row[col].val()>>bit)&1 == 1{
+                    expected[32*col + bit] += *scalar;
+                }
+            }
+        }
+    }
+    assert_eq!(encoded_poly.make_linear_combination(scalars), Ok(expected));
+}
+
+#[test]
+fn linear_combination_scalar_count_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    let l = 8;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let encoded_poly = Code::new(&poly, &ntt, EncodingParams::default()).unwrap();
+
+    let rows = encoded_poly.rows;
+    let scalars:Vec<BinaryField128b> = (0..rows - 1).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let error = EncodeError::ScalarCountMismatch { rows, scalars: rows - 1 };
+    assert_eq!(encoded_poly.make_linear_combination(scalars.clone()), Err(error.clone()));
+    assert_eq!(make_linear_combination(make_coeff_matrix(&poly, encoded_poly.cols), scalars), Err(error));
+}
+
+#[test]
+fn fmt_field32_test(){
+    let v = BinaryField32b::new(0xdead_beef);
+
+    assert_eq!(fmt_field32(v, Radix::Hex), "0xdeadbeef");
+    assert_eq!(fmt_field32(v, Radix::Binary), "0b11011110101011011011111011101111");
+    assert_eq!(fmt_field32(v, Radix::Decimal), "3735928559");
+
+    let v = BinaryField32b::new(5);
+    assert_eq!(fmt_field32(v, Radix::Hex), "0x00000005");
+    assert_eq!(fmt_field32(v, Radix::Binary), "0b00000000000000000000000000000101");
+
+    let code = Code::from_columns((0..RATE*2).map(|i| vec![BinaryField32b::new(i as u32)]).collect(), 1, 2, EncodingParams::default()).unwrap();
+    assert_eq!(code.pretty(Radix::Decimal), "0 1 | 2 3 4 5 6 7");
+}
+
+#[test]
+fn code_debug_test(){
+    let code = Code::from_columns((0..RATE*2).map(|i| vec![BinaryField32b::new(0xdead_0000 + i as u32); 4]).collect(), 4, 2, EncodingParams::default()).unwrap();
+    let summary = format!("{code:?}");
+
+    assert_eq!(summary, format!("Code {{ rows: 4, cols: 2, codeword_len: {}, rate: {RATE} }}", RATE*2));
+    assert!(!summary.contains("BinaryField32b") && !summary.contains(&format!("{:?}", BinaryField32b::new(0xdead_0000))));
+
+    #[cfg(debug_assertions)]
+    {
+        let dump = code.dump_matrix();
+        assert_eq!(dump.lines().count(), code.rows);
+        assert!(dump.contains(&format!("{:?}", BinaryField32b::new(0xdead_0000))));
+    }
+}
+
+#[test]
+fn eval_folded_at_column_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    for log_cols in 0..6{
+        let folded_poly:Vec<BinaryField128b> = (0..32<<log_cols).map(|_| BinaryField128b::random(&mut rng)).collect();
+        let ntt = AdditiveNTT::new(log_cols + PACKING_DEGREE + 2);
+
+        for rate in [2, 4, 8]{
+            let params = EncodingParams{ rate, packing_degree: PACKING_DEGREE };
+            let encoded = encode_extension(&folded_poly, &ntt, params);
+            for (query, expected) in encoded.iter().enumerate(){
+                assert_eq!(eval_folded_at_column(&folded_poly, query, &ntt, params), *expected, "Query {query} failed for 2^{log_cols} columns at rate {rate}");
+            }
+        }
+    }
+}
+
+#[test]
+fn digest_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    let poly:Vec<BinaryField32b> = (0..1<<8).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(8);
+
+    let code = Code::new(&poly, &ntt, EncodingParams::default()).unwrap();
+    assert_eq!(code.digest(), Code::new(&poly, &ntt, EncodingParams::default()).unwrap().digest());
+
+    let mut modified = Code::new(&poly, &ntt, EncodingParams::default()).unwrap();
+    modified.update_coefficient(5, poly[5] + BinaryField32b::ONE, &ntt);
+    assert_ne!(code.digest(), modified.digest());
+}
+
+#[test]
+fn estimate_code_memory_test(){
+    use rand::thread_rng;
+    use crate::test::{random_opening, Opening};
+
+    let l = 10;
+    let Opening{ poly, ntt, merkle_tree, encoded_poly: code, .. } = random_opening(l, &mut thread_rng());
+
+    //commit has hashed the message columns, so they are cached next to the codeword.
+    let column_cache = (1<<l)*4 + code.cols*std::mem::size_of::<Vec<BinaryField32b>>();
+    assert_eq!(code.memory_usage(), (1<<l)*RATE*4 + column_cache);
+    assert_eq!(estimate_code_memory(l, RATE, PACKING_DEGREE), code.memory_usage() + merkle_tree.memory_usage());
+
+    //A code that hasn't been committed to only holds the codeword until its columns are asked for.
+    let fresh = Code::new(&poly, &ntt, EncodingParams::default()).unwrap();
+    assert_eq!(fresh.memory_usage(), (1<<l)*RATE*4);
+    fresh.message_columns();
+    assert_eq!(fresh.memory_usage(), code.memory_usage());
+}
+
+#[test]
+fn validate_encode_cosets_test(){
+    for log_len in 0..30{
+        assert_eq!(validate_encode_cosets(1<<log_len, RATE), Ok(()));
+    }
+
+    //Block 2's shift 2^32 wraps around to block 0's.
+    assert_eq!(validate_encode_cosets(1<<31, RATE), Err(EncodeError::CosetCollision { first: 0, second: 2, coset: 0 }));
+    assert_eq!(validate_encode_cosets(1<<31, 2), Ok(()));
+
+    //An empty message has every shift equal to 0, and other lengths have no domain, neither is reported as a collision.
+    for len in [0, 3, 12, (1<<20) + 1]{
+        assert_eq!(validate_encode_cosets(len, RATE), Err(EncodeError::InvalidMessageLength { len }));
+    }
+
+    let ntt = AdditiveNTT::new(4 + 2);
+    assert_eq!(encode(&vec![], &ntt, EncodingParams::default()), Err(EncodeError::InvalidMessageLength { len: 0 }));
+    assert_eq!(encode(&vec![BinaryField32b::ONE; 12], &ntt, EncodingParams::default()), Err(EncodeError::InvalidMessageLength { len: 12 }));
+    assert_eq!(encode(&vec![BinaryField32b::ONE; 16], &ntt, EncodingParams::default()).map(|code| code.len()), Ok(16*RATE));
+
+    //The Code constructors report the same errors instead of panicking.
+    assert_eq!(Code::new(&[], &ntt, EncodingParams::default()).unwrap_err(), EncodeError::InvalidMessageLength { len: 0 });
+    assert_eq!(Code::new(&[BinaryField32b::ONE; 12], &ntt, EncodingParams::default()).unwrap_err(), EncodeError::InvalidMessageLength { len: 12 });
+    assert_eq!(Code::from_coeff_matrix(&[], &ntt, EncodingParams::default()).unwrap_err(), EncodeError::InvalidMessageLength { len: 0 });
+    assert_eq!(Code::with_log_cols(&[BinaryField32b::ONE; 12], 31, &ntt).unwrap_err(), EncodeError::CosetCollision { first: 0, second: 2, coset: 0 });
+
+    //A partial trailing row is padded to whole rows rather than dropped.
+    let code = Code::with_log_cols(&[BinaryField32b::ONE; 40], 4, &ntt).unwrap();
+    let mut padded = vec![BinaryField32b::ONE; 40];
+    padded.resize(64, BinaryField32b::ZERO);
+    assert_eq!((code.rows, code.cols), (4, 16));
+    assert!(code.assert_systematic(&padded));
+}
+
+#[test]
+fn validate_rate_test(){
+    for log_cols in 0..=30{
+        for log_inv_rate in 1..=(32 - log_cols).min(4){
+            assert_eq!(validate_rate(log_inv_rate, log_cols), Ok(()));
+            assert_eq!(validate_encode_cosets(1<<log_cols, 1<<log_inv_rate), Ok(()));
+        }
+    }
+
+    assert_eq!(validate_rate(0, 10), Err(EncodeError::UnsupportedRate { log_inv_rate: 0, log_cols: 10 }));
+    assert_eq!(validate_rate(2, 31), Err(EncodeError::UnsupportedRate { log_inv_rate: 2, log_cols: 31 }));
+    assert!(validate_encode_cosets(1<<31, 4).is_err());
+    assert_eq!(validate_rate(255, 1), Err(EncodeError::UnsupportedRate { log_inv_rate: 255, log_cols: 1 }));
+}
+
+#[test]
+fn encode_extension_at_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    let folded_poly:Vec<BinaryField128b> = (0..32<<4).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(4 + PACKING_DEGREE + 2);
+
+    let encoded = encode_extension(&folded_poly, &ntt, EncodingParams::default());
+    let positions = vec![0, 3, 15, 16, 17, 40, 63, 3];
+    let expected:Vec<PackedAlgebra32> = positions.iter().map(|&position| encoded[position]).collect();
+
+    assert_eq!(encode_extension_at(&folded_poly, &positions, &ntt, EncodingParams::default()), expected);
+    assert_eq!(encode_extension_at(&folded_poly, &positions[..3], &ntt, EncodingParams::default()), expected[..3]);
+
+    //At rate 2 the codeword ends after the first parity block.
+    let params = EncodingParams{ rate: 2, packing_degree: PACKING_DEGREE };
+    let encoded = encode_extension(&folded_poly, &ntt, params);
+    let mut codeword = vec![PackedAlgebra32::new([BinaryField128b::ZERO; 32]); encoded.len()];
+    encode_extension_into(&folded_poly, &mut codeword, &ntt, params);
+    assert_eq!(codeword, encoded);
+    assert_eq!(encode_extension_at(&folded_poly, &[0, 16, 31], &ntt, params), vec![encoded[0], encoded[16], encoded[31]]);
+}
+
+#[test]
+fn packed_columns_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    let l = 8;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let code = Code::new(&poly, &ntt, EncodingParams::default()).unwrap();
+
+    let point:Vec<BinaryField128b> = (0..code.rows.trailing_zeros()).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let scalars = compute_fourier_bases(&point);
+    let folded_poly = code.make_linear_combination(scalars.clone()).unwrap();
+
+    //What the verifier compares the opened columns against, on both the systematic and the parity part.
+    let encoded = encode_extension(&folded_poly, &ntt, EncodingParams::default());
+    assert_eq!(code.packed_columns(&scalars).collect::<Vec<_>>(), encoded);
+    for query in [0, 1, code.cols - 1, code.cols, code.codeword_len() - 1]{
+        assert_eq!(code.packed_col(query, &scalars), eval_folded_at_column(&folded_poly, query, &ntt, EncodingParams::default()), "Column {query} differs");
+    }
+}
+
+#[test]
+fn encode_extension_round_trip_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    let bits = |vals:&[BinaryField32b]|->Vec<BinaryField128b>{
+        vals.iter().flat_map(|val| (0..32).map(move |i| if (val.val()>>i)&1 == 1 {BinaryField128b::ONE} else {BinaryField128b::ZERO})).collect()
+    };
+
+    for log_len in 0..6{
+        let ntt = AdditiveNTT::new(log_len + 2);
+
+        let message:Vec<BinaryField128b> = (0..32<<log_len).map(|_| BinaryField128b::random(&mut rng)).collect();
+        assert_eq!(decode_extension(&encode_extension(&message, &ntt, EncodingParams::default()), &ntt, EncodingParams::default()), message);
+
+        //Encoding the bits of a base field message on the extension path gives the bits of its base field encoding.
+        let base_message:Vec<BinaryField32b> = (0..1<<log_len).map(|_| BinaryField32b::random(&mut rng)).collect();
+        let extension_code = PackedAlgebra32::unpack(encode_extension(&bits(&base_message), &ntt, EncodingParams::default()));
+        assert_eq!(extension_code, bits(&encode(&base_message, &ntt, EncodingParams::default()).unwrap()), "Extension encoding differs from the base one for 2^{log_len} elements");
+    }
+}
+
+#[test]
+fn encoding_rate_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    let l = 8;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+
+    let codes:Vec<Code> = [2, 4].into_iter().map(|rate|{
+        let params = EncodingParams{ rate, packing_degree: PACKING_DEGREE };
+        let code = Code::new(&poly, &ntt, params).unwrap();
+        assert!(code.assert_systematic(&poly));
+
+        //The folded codeword is the encoding of the folded message, which is what the verifier checks on the queried columns.
+        let coeff_matrix = make_coeff_matrix(&poly, code.cols);
+        let scalars:Vec<BinaryField128b> = (0..code.rows).map(|_| BinaryField128b::random(&mut rng)).collect();
+        let folded_poly = make_linear_combination(coeff_matrix.clone(), scalars.clone()).unwrap();
+        let encoded_folded_poly = encode_extension(&folded_poly, &ntt, params);
+        assert_eq!(encoded_folded_poly.len(), rate*code.cols);
+        assert_eq!(PackedAlgebra32::unpack(encoded_folded_poly.clone()), make_linear_combination(code.code.chunks(code.width()).map(<[_]>::to_vec).collect(), scalars).unwrap());
+        assert_eq!(decode_extension(&encoded_folded_poly, &ntt, params), folded_poly);
+
+        code
+    }).collect();
+
+    //Both rates lay a polynomial this short out in the same shape, and the blocks are evaluated on the same cosets, so the rate 2 codeword is a prefix of the rate 4 one.
+    let (half, full) = (&codes[0], &codes[1]);
+    (0..half.rows).for_each(|row| assert_eq!(half.row(row), &full.row(row)[..half.width()]));
+}
+
+#[test]
+fn code_dimensions_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    let l = 10;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+
+    for rate in [2, 4, 8]{
+        let code = Code::new(&poly, &ntt, EncodingParams{ rate, packing_degree: PACKING_DEGREE }).unwrap();
+        let (log_rows, log_cols) = log_dimensions(l, rate, PACKING_DEGREE);
+
+        assert_eq!(1<<code.log_inv_rate(), rate);
+        assert_eq!(code.message_len(), 1<<log_cols);
+        assert_eq!(code.codeword_len(), rate<<log_cols);
+        assert_eq!(code.code.len(), (1<<log_rows)*code.codeword_len());
+        assert_eq!(code.message().len(), code.rows*code.message_len());
+    }
+}
+
+#[test]
+fn message_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    for l in [0, 3, 8, 12]{
+        let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+        let ntt = AdditiveNTT::new(l);
+
+        for rate in [2, 4, 8]{
+            let code = Code::new(&poly, &ntt, EncodingParams{ rate, packing_degree: PACKING_DEGREE }).unwrap();
+            assert_eq!(code.message(), poly, "Length 2^{l} at rate {rate} failed");
+        }
+
+        //The message survives a round trip of the codeword through its columns.
+        for rate in [2, 4]{
+            let code = Code::new(&poly, &ntt, EncodingParams{ rate, packing_degree: PACKING_DEGREE }).unwrap();
+            let columns = (0..code.codeword_len()).map(|col| code.codeword_col(col)).collect();
+            assert_eq!(Code::from_columns(columns, code.rows, code.cols, code.params()).unwrap().message(), poly);
+        }
+    }
+}
+
+#[test]
+fn message_columns_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    let l = 10;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let mut code = Code::new(&poly, &ntt, EncodingParams::default()).unwrap();
+
+    let extracted:Vec<Vec<BinaryField32b>> = (0..code.cols).map(|col| code.col(col)).collect();
+    assert_eq!(code.message_columns(), extracted);
+    assert!((0..code.cols).all(|col| code.col(col) == extracted[col] && code.codeword_col(col) == extracted[col]));
+
+    //Updating a coefficient drops the cached columns rather than serving stale ones.
+    code.update_coefficient(3, poly[3] + BinaryField32b::ONE, &ntt);
+    assert_eq!(code.col(3%code.cols), code.codeword_col(3%code.cols));
+    assert_ne!(code.col(3%code.cols), extracted[3%code.cols]);
+    assert!((0..code.cols).all(|col| code.message_columns()[col] == code.codeword_col(col)));
+}
+
+#[test]
+fn prefix_sums_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    for l in [0, 5, 10]{
+        let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+        let ntt = AdditiveNTT::new(l);
+        let code = Code::new(&poly, &ntt, EncodingParams::default()).unwrap();
+        assert_eq!(code.message(), poly);
+
+        let expected:Vec<BinaryField32b> = (0..poly.len()).map(|i| poly[..=i].iter().fold(BinaryField32b::ZERO, |acc, &coeff| acc + coeff)).collect();
+        assert_eq!(code.prefix_sums(), expected);
+    }
+}
+
+#[test]
+fn fourier_bases_small_point_test(){
+    use rand::thread_rng;
+
+    assert_eq!(compute_fourier_bases(&[]), vec![BinaryField128b::ONE]);
+
+    let r = BinaryField128b::random(thread_rng());
+    assert_eq!(compute_fourier_bases(&[r]), vec![BinaryField128b::ONE - r, r]);
+
+    let mut out = vec![BinaryField128b::ZERO];
+    compute_fourier_bases_into(&[], &mut out);
+    assert_eq!(out, compute_fourier_bases(&[]));
+}
@@ -1,19 +1,44 @@
-use std::{collections::HashMap};
+use std::collections::{BTreeMap, HashMap};
 
-use binius_field::BinaryField32b;
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use binius_field::{BinaryField128b, BinaryField16b, BinaryField32b, BinaryField64b, BinaryField8b, Field};
+#[cfg(feature = "parallel")]
+use rayon::iter::ParallelIterator;
+use serde::{Deserialize, Serialize};
 use sha3::{self, Digest, Keccak256};
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::verifier::VerifyError;
 
 
 
 //Implementation for Merkle Tree commitments, the hashing algorithm is set to Keccak256 and can be made generic over choice of hasher.
 
 //The data structure to construct the merkle tree is a hashmap whoes keys represent the layer of the tree, and the vector contains the nodes in the layer
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Hash(pub Vec<u8>);
+
+//Compares the digests without exiting at the first differing byte, so a verifier checking a path against a root doesn't leak how much of it matched.
+//Digests of different lengths are unequal, the length itself isn't treated as secret.
+impl ConstantTimeEq for Hash {
+    fn ct_eq(&self, other:&Self)->Choice{
+        self.0.as_slice().ct_eq(other.0.as_slice())
+    }
+}
+
+//Length in bytes of a Keccak256 digest.
+pub const HASH_LEN:usize = 32;
+//Merkle paths of several leaves combined into one opening. A sibling is left out whenever it is itself on the path of another opened leaf,
+//so nodes shared between the paths are only included once. Siblings are stored layer by layer from the leaves up, in increasing index order inside a layer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiPath{
+    pub siblings: Vec<Hash>
+}
+
+//leaf_count is the number of leaves the tree was built from, before the leaf layer was padded to a power of two with padding_leaf.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MerkleTree{
-    pub data: HashMap<usize, Vec<Hash>>
+    pub data: HashMap<usize, Vec<Hash>>,
+    pub leaf_count: usize
 }
 
 impl MerkleTree {
@@ -29,23 +54,144 @@ impl MerkleTree {
     )->Hash{
         self.data.get(&0).unwrap()[0].clone()
     }
+    //Number of leaves the tree was built from, the padding leaves are not counted.
+    pub fn num_leaves(
+        &self
+    )->usize{
+        self.leaf_count
+    }
+
+    //Panics if leaf_index is not below num_leaves(), see try_get_merkle_path.
     pub fn get_merkle_path(
         &self,
         leaf_index:usize
     )->Vec<Hash>{
-        get_merkle_path(&self.data, leaf_index)
+        self.try_get_merkle_path(leaf_index).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    //Same as get_merkle_path, but a leaf_index that is out of range or points at a padding leaf is reported instead of panicking.
+    pub fn try_get_merkle_path(
+        &self,
+        leaf_index:usize
+    )->Result<Vec<Hash>, MerkleError>{
+        if leaf_index >= self.num_leaves(){
+            return Err(MerkleError::LeafIndexOutOfRange { index: leaf_index, num_leaves: self.num_leaves() });
+        }
+        Ok(get_merkle_path(&self.data, leaf_index))
+    }
+
+    //The 2^cap_log nodes cap_log layers below the root. Handing a verifier the cap instead of the root lets paths stop cap_log layers short of it.
+    pub fn get_cap(
+        &self,
+        cap_log:usize
+    )->Vec<Hash>{
+        self.data.get(&cap_log).expect("Cap is deeper than the tree.").clone()
+    }
+
+    //The merkle path from the leaf up to the cap layer, i.e without its last cap_log siblings, to be checked with verify_with_cap.
+    pub fn get_merkle_path_to_cap(
+        &self,
+        leaf_index:usize,
+        cap_log:usize
+    )->Vec<Hash>{
+        let mut path = self.get_merkle_path(leaf_index);
+        path.truncate(path.len() - cap_log);
+        path
+    }
+
+    //Combined opening of the leaves at leaf_indices, to be checked with verify_multi_path. Indices can repeat and come in any order.
+    pub fn get_multi_path(
+        &self,
+        leaf_indices:&[usize]
+    )->MultiPath{
+        let mut indices = leaf_indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+
+        let mut siblings = Vec::new();
+        for depth in (1..self.data.len()).rev(){
+            let layer = &self.data[&depth];
+
+            for &index in &indices{
+                if indices.binary_search(&(index^1)).is_err(){
+                    siblings.push(layer[index^1].clone());
+                }
+            }
+
+            indices.iter_mut().for_each(|index| *index >>= 1);
+            indices.dedup();
+        }
+
+        MultiPath{ siblings }
+    }
+
+    //Bytes taken by the hashes in the tree, the hashmap and the layer vectors themselves are not counted.
+    pub fn memory_usage(
+        &self
+    )->usize{
+        self.data.values().flatten().map(|hash| std::mem::size_of::<Hash>() + hash.0.len()).sum()
     }
 
 }
 
 
-//Hashing a field element
-pub fn hash_field(data: &Vec<BinaryField32b>)->Hash{
-    let mut hash_state = Keccak256::new();
+//Fields whose elements can be hashed into leaves by hash_field. An element is serialized to the little-endian bytes of the integer it is stored as,
+//so all elements of a field take the same number of bytes and a leaf's bytes are never ambiguous.
+pub trait LeafField: Field{
+    type Bytes: AsRef<[u8]>;
+
+    fn to_le_bytes(self)->Self::Bytes;
+}
+
+macro_rules! impl_leaf_field {
+    ($($field:ty => $len:expr),*) => {
+        $(impl LeafField for $field{
+            type Bytes = [u8; $len];
+
+            fn to_le_bytes(self)->Self::Bytes{
+                self.val().to_le_bytes()
+            }
+        })*
+    };
+}
+
+impl_leaf_field!(BinaryField8b => 1, BinaryField16b => 2, BinaryField32b => 4, BinaryField64b => 8, BinaryField128b => 16);
+
+//Domain separation tags prefixed to the hashed bytes, so that a leaf can never hash to the same value as an internal node and be passed off as one.
+pub const LEAF_TAG:u8 = 0x00;
+pub const NODE_TAG:u8 = 0x01;
 
-    data.iter().for_each(|d| hash_state.update(d.val().to_le_bytes()));
+//Hashing a field element, as a leaf.
+pub fn hash_field<F:LeafField>(data: &[F])->Hash{
+    let mut hasher = LeafHasher::new();
+    data.iter().for_each(|&d| hasher.update(d));
+    hasher.finalize()
+}
+
+//Hashes a leaf one element at a time, giving the same digest as hash_field on all the elements fed to it. Used to hash columns as their entries come in row by row.
+#[derive(Clone)]
+pub struct LeafHasher(Keccak256);
+
+impl LeafHasher{
+    pub fn new()->Self{
+        let mut hash_state = Keccak256::new();
+        hash_state.update([LEAF_TAG]);
+        LeafHasher(hash_state)
+    }
+
+    pub fn update<F:LeafField>(&mut self, elem:F){
+        self.0.update(elem.to_le_bytes());
+    }
+
+    pub fn finalize(self)->Hash{
+        Hash(self.0.finalize().to_vec())
+    }
+}
 
-    Hash(hash_state.finalize().to_vec())
+impl Default for LeafHasher{
+    fn default()->Self{
+        Self::new()
+    }
 }
 
 
@@ -59,7 +205,7 @@ pub fn hash(data: &Vec<u8>)->Hash{
 //Hashing a concatentation of previous hashes, required for Merkle Tree construction.
 pub fn hash_concatenation(data1:&Hash, data2:&Hash)->Hash{
 
-    let val = [data1.0.clone(), data2.0.clone()].concat();
+    let val = [vec![NODE_TAG], data1.0.clone(), data2.0.clone()].concat();
 
     Hash(
         Keccak256::digest(val).to_vec()
@@ -67,11 +213,17 @@ pub fn hash_concatenation(data1:&Hash, data2:&Hash)->Hash{
 }
 
 
-//Constructing a merkle tree
+//The sentinel the leaf layer is padded with up to a power of two, the hash of the empty byte string.
+pub fn padding_leaf()->Hash{
+    hash(&vec![])
+}
+
+//Constructing a merkle tree, a leaf layer whose size isn't a power of two is padded with padding_leaf first.
+
+pub fn merklize(mut leaf_hashes:Vec<Hash>)->MerkleTree{
+    let leaf_count = leaf_hashes.len();
+    leaf_hashes.resize(leaf_count.next_power_of_two(), padding_leaf());
 
-pub fn merklize(leaf_hashes:Vec<Hash>)->MerkleTree{
-    assert!(leaf_hashes.len().is_power_of_two(), "Leaf layer's size needs to be a power of 2 to construct Merkle Tree.");
-    
     let mut tree:HashMap<usize, Vec<Hash>> = HashMap::new();
 
     let tree_depth = leaf_hashes.len().trailing_zeros() as usize;
@@ -86,7 +238,7 @@ pub fn merklize(leaf_hashes:Vec<Hash>)->MerkleTree{
         //Hashing the concatenations of 2*i and 2*i + 1 of the lower layer in parallel.
 
         let current_layer = 
-        (0..current_layer_size).into_par_iter().map(|i| hash_concatenation(&lower_layer[2*i], &lower_layer[2*i + 1])).collect();
+        into_par_iter!(0..current_layer_size).map(|i| hash_concatenation(&lower_layer[2*i], &lower_layer[2*i + 1])).collect();
         
         tree.insert(depth, current_layer);
 
@@ -94,7 +246,8 @@ pub fn merklize(leaf_hashes:Vec<Hash>)->MerkleTree{
     }
     
     MerkleTree{
-        data:tree
+        data:tree,
+        leaf_count
     }
 }
 
@@ -129,19 +282,280 @@ pub fn get_merkle_path(tree:&HashMap<usize, Vec<Hash>>, leaf_index:usize)->Vec<H
     path
 }
 
-pub fn verify_merkle_path(commitment:&Hash, leaf_hash:Hash, leaf_index:usize, merkle_path:&Vec<Hash>){
+pub fn verify_merkle_path(commitment:&Hash, leaf_hash:Hash, leaf_index:usize, merkle_path:&[Hash])->Result<(), VerifyError>{
+
+    if !bool::from(merkle_root_from_path(leaf_hash, leaf_index, merkle_path).ct_eq(commitment)){
+        return Err(VerifyError::MerklePathMismatch { query: leaf_index });
+    }
+    Ok(())
+}
+
+//Checks a combined opening from MerkleTree::get_multi_path of the (leaf_index, leaf_hash) pairs in leaves against the root of a tree with 2^tree_depth leaves.
+//Nodes are hashed up layer by layer, taking a sibling from the opening only when it can't be computed from the opened leaves.
+pub fn verify_multi_path(commitment:&Hash, tree_depth:usize, leaves:&[(usize, Hash)], multi_path:&MultiPath)->Result<(), VerifyError>{
+
+    let mut layer:BTreeMap<usize, Hash> = BTreeMap::new();
+    for (leaf_index, leaf_hash) in leaves{
+        if *leaf_index >= 1<<tree_depth{
+            return Err(VerifyError::QueryOutOfRange { query: *leaf_index, max: 1<<tree_depth });
+        }
+        //A repeated index has to be opened to the same leaf every time.
+        if layer.insert(*leaf_index, leaf_hash.clone()).is_some_and(|previous| previous != *leaf_hash){
+            return Err(VerifyError::MerklePathMismatch { query: *leaf_index });
+        }
+    }
+
+    let mut siblings = multi_path.siblings.iter();
+    for _ in 0..tree_depth{
+        let mut upper_layer = BTreeMap::new();
+
+        for (&index, hash) in &layer{
+            //Both children opened, the parent was already computed from the left one.
+            if index&1 == 1 && layer.contains_key(&(index^1)){
+                continue;
+            }
+            let sibling = match layer.get(&(index^1)) {
+                Some(sibling) => sibling,
+                None => siblings.next().ok_or_else(|| VerifyError::MalformedProof { reason: "multi path has too few siblings".to_string() })?
+            };
+
+            let parent = if index&1 == 0 {hash_concatenation(hash, sibling)} else {hash_concatenation(sibling, hash)};
+            upper_layer.insert(index>>1, parent);
+        }
+        layer = upper_layer;
+    }
+
+    if siblings.next().is_some(){
+        return Err(VerifyError::MalformedProof { reason: "multi path has too many siblings".to_string() });
+    }
+    if !layer.get(&0).is_some_and(|root| bool::from(root.ct_eq(commitment))){
+        return Err(VerifyError::MultiPathMismatch);
+    }
+    Ok(())
+}
+
+//Same as verify_merkle_path for a tree built from leaf_count leaves, also rejecting paths to the padding leaves and paths of the wrong length,
+//which has to be the depth of the tree padded to a power of two.
+pub fn verify_merkle_path_with_leaf_count(commitment:&Hash, leaf_count:usize, leaf_hash:Hash, leaf_index:usize, merkle_path:&Vec<Hash>)->Result<(), VerifyError>{
+
+    if leaf_index >= leaf_count{
+        return Err(VerifyError::QueryOutOfRange { query: leaf_index, max: leaf_count });
+    }
+    let depth = leaf_count.next_power_of_two().trailing_zeros() as usize;
+    if merkle_path.len() != depth{
+        return Err(VerifyError::MalformedProof { reason: format!("merkle path has length {}, expected {depth}", merkle_path.len()) });
+    }
+    verify_merkle_path(commitment, leaf_hash, leaf_index, merkle_path)
+}
+
+//Error thrown when a merkle path does not lead to the expected node, or can't be opened in the first place.
+#[derive(Clone, thiserror::Error, Debug, PartialEq, Eq)]
+pub enum MerkleError {
+    #[error("leaf {index} is out of range, the tree only has {num_leaves} leaves")]
+    LeafIndexOutOfRange { index: usize, num_leaves: usize },
+    #[error("path leads to cap node {index} but the cap only has {cap_len} nodes")]
+    CapIndexOutOfRange { index: usize, cap_len: usize },
+    #[error("path does not lead to cap node {index}")]
+    CapMismatch { index: usize },
+    #[error("path of leaf {index} leads to a different root than the first one")]
+    RootDisagreement { index: usize },
+    #[error("no leaves were opened")]
+    NoOpenings,
+}
+
+//Checks membership of a leaf against a cap, see MerkleTree::get_cap. The path, as given by get_merkle_path_to_cap, is hashed up to the cap layer
+//and the node it ends at has to be the cap node above the leaf.
+pub fn verify_with_cap(cap:&[Hash], leaf_hash:Hash, leaf_index:usize, merkle_path:&[Hash])->Result<(), MerkleError>{
+
+    let index = leaf_index>>merkle_path.len();
+    if index >= cap.len(){
+        return Err(MerkleError::CapIndexOutOfRange { index, cap_len: cap.len() });
+    }
+
+    if !bool::from(merkle_root_from_path(leaf_hash, leaf_index, merkle_path).ct_eq(&cap[index])){
+        return Err(MerkleError::CapMismatch { index });
+    }
+    Ok(())
+}
+
+//Recomputes the root of the tree by hashing the leaf with its siblings along the merkle path.
+pub fn merkle_root_from_path(leaf_hash:Hash, leaf_index:usize, merkle_path:&[Hash])->Hash{
 
     let mut hash = leaf_hash;
 
-    for d in 0..merkle_path.len(){
+    for (d, sibling) in merkle_path.iter().enumerate(){
         if (leaf_index>>d)&1 == 0 {
-            hash = hash_concatenation(&hash, &merkle_path[d])
+            hash = hash_concatenation(&hash, sibling)
         }
         else{
-            hash = hash_concatenation(&merkle_path[d], &hash)
+            hash = hash_concatenation(sibling, &hash)
         }
     }
 
+    hash
+}
+
+#[test]
+fn verify_with_cap_test(){
+    let leaf_hashes:Vec<Hash> = (0..16u8).map(|i| hash(&vec![i])).collect();
+    let tree = MerkleTree::new(leaf_hashes.clone());
+
+    let cap_log = 2;
+    let cap = tree.get_cap(cap_log);
+    assert_eq!(cap.len(), 1<<cap_log);
+
+    for (leaf_index, leaf_hash) in leaf_hashes.iter().enumerate(){
+        let path = tree.get_merkle_path_to_cap(leaf_index, cap_log);
+        assert_eq!(path.len(), 4 - cap_log);
+        assert_eq!(verify_with_cap(&cap, leaf_hash.clone(), leaf_index, &path), Ok(()));
+    }
+
+    let path = tree.get_merkle_path_to_cap(5, cap_log);
+    assert_eq!(verify_with_cap(&cap, leaf_hashes[4].clone(), 5, &path), Err(MerkleError::CapMismatch { index: 1 }));
+
+    let mut tampered_path = path.clone();
+    tampered_path[1] = leaf_hashes[0].clone();
+    assert_eq!(verify_with_cap(&cap, leaf_hashes[5].clone(), 5, &tampered_path), Err(MerkleError::CapMismatch { index: 1 }));
+
+    //A path that is too short lands below the cap, an index past the leaves lands outside of it.
+    assert_eq!(verify_with_cap(&cap, leaf_hashes[5].clone(), 5, &path[..1]), Err(MerkleError::CapMismatch { index: 2 }));
+    assert_eq!(verify_with_cap(&cap, leaf_hashes[5].clone(), 17, &path), Err(MerkleError::CapIndexOutOfRange { index: 4, cap_len: 4 }));
+}
+
+#[test]
+fn multi_path_test(){
+    let leaf_hashes:Vec<Hash> = (0..64u8).map(|i| hash(&vec![i])).collect();
+    let tree = MerkleTree::new(leaf_hashes.clone());
+    let root = tree.get_root();
+
+    for leaf_indices in [vec![0], vec![63, 0], vec![5, 4, 5, 17, 4, 40], (0..64).collect(), vec![]]{
+        let leaves:Vec<(usize, Hash)> = leaf_indices.iter().map(|&i| (i, leaf_hashes[i].clone())).collect();
+        let multi_path = tree.get_multi_path(&leaf_indices);
+
+        let path_len:usize = leaf_indices.iter().map(|&i| tree.get_merkle_path(i).len()).sum();
+        assert!(multi_path.siblings.len() <= path_len);
+
+        //The empty opening proves nothing and has to be rejected like any other opening not leading to the root.
+        let expected = if leaf_indices.is_empty() {Err(VerifyError::MultiPathMismatch)} else {Ok(())};
+        assert_eq!(verify_multi_path(&root, 6, &leaves, &multi_path), expected);
+        assert!(leaves.iter().all(|(i, leaf)| verify_merkle_path(&root, leaf.clone(), *i, &tree.get_merkle_path(*i)).is_ok()));
+    }
+
+    //Siblings shared by the paths of 4 and 5 are only included once.
+    assert_eq!(tree.get_multi_path(&[4, 5]).siblings.len(), 5);
+
+    let leaf_indices = [3, 9, 9, 30];
+    let multi_path = tree.get_multi_path(&leaf_indices);
+    let mut leaves:Vec<(usize, Hash)> = leaf_indices.iter().map(|&i| (i, leaf_hashes[i].clone())).collect();
+
+    let mut tampered_path = multi_path.clone();
+    tampered_path.siblings[2] = leaf_hashes[0].clone();
+    assert_eq!(verify_multi_path(&root, 6, &leaves, &tampered_path), Err(VerifyError::MultiPathMismatch));
+
+    tampered_path.siblings.pop();
+    assert!(matches!(verify_multi_path(&root, 6, &leaves, &tampered_path), Err(VerifyError::MalformedProof { .. })));
+
+    leaves[2].1 = leaf_hashes[10].clone();
+    assert_eq!(verify_multi_path(&root, 6, &leaves, &multi_path), Err(VerifyError::MerklePathMismatch { query: 9 }));
+
+    leaves[2] = (64, leaf_hashes[10].clone());
+    assert_eq!(verify_multi_path(&root, 6, &leaves, &multi_path), Err(VerifyError::QueryOutOfRange { query: 64, max: 64 }));
+}
+
+#[test]
+fn hash_ct_eq_test(){
+    let digest = hash(&vec![7]);
+
+    //Digests differing in the first or only the last byte, a truncated one and an empty one, which all have to compare unequal.
+    let mut first_flipped = digest.clone();
+    first_flipped.0[0] ^= 1;
+    let mut last_flipped = digest.clone();
+    last_flipped.0[HASH_LEN - 1] ^= 1;
+    let truncated = Hash(digest.0[..HASH_LEN - 1].to_vec());
+
+    for other in [digest.clone(), hash(&vec![8]), first_flipped, last_flipped, truncated, Hash(vec![])]{
+        assert_eq!(bool::from(digest.ct_eq(&other)), digest == other);
+        assert_eq!(bool::from(other.ct_eq(&digest)), other == digest);
+    }
+    assert!(bool::from(Hash(vec![]).ct_eq(&Hash(vec![]))));
+}
+
+#[test]
+fn non_power_of_two_leaves_test(){
+    for leaf_count in [1usize, 3, 5, 17]{
+        let leaf_hashes:Vec<Hash> = (0..leaf_count as u8).map(|i| hash(&vec![i])).collect();
+        let tree = MerkleTree::new(leaf_hashes.clone());
+        let root = tree.get_root();
+        let depth = leaf_count.next_power_of_two().trailing_zeros() as usize;
+
+        assert_eq!(tree.leaf_count, leaf_count);
+        assert!(tree.data[&depth][leaf_count..].iter().all(|leaf| *leaf == padding_leaf()));
+
+        for (leaf_index, leaf_hash) in leaf_hashes.iter().enumerate(){
+            let path = tree.get_merkle_path(leaf_index);
+            assert_eq!(path.len(), depth);
+            assert_eq!(verify_merkle_path_with_leaf_count(&root, leaf_count, leaf_hash.clone(), leaf_index, &path), Ok(()));
+        }
+
+        //The padding leaves are part of the tree, but can't be opened.
+        if !leaf_count.is_power_of_two(){
+            let path = get_merkle_path(&tree.data, leaf_count);
+            assert_eq!(verify_merkle_path(&root, padding_leaf(), leaf_count, &path), Ok(()));
+            assert_eq!(verify_merkle_path_with_leaf_count(&root, leaf_count, padding_leaf(), leaf_count, &path), Err(VerifyError::QueryOutOfRange { query: leaf_count, max: leaf_count }));
+        }
+
+        let multi_path = tree.get_multi_path(&[0, leaf_count - 1]);
+        let leaves = [(0, leaf_hashes[0].clone()), (leaf_count - 1, leaf_hashes[leaf_count - 1].clone())];
+        assert_eq!(verify_multi_path(&root, depth, &leaves, &multi_path), Ok(()));
+    }
+}
+
+#[test]
+fn try_get_merkle_path_test(){
+    let leaf_hashes:Vec<Hash> = (0..5u8).map(|i| hash(&vec![i])).collect();
+    let tree = MerkleTree::new(leaf_hashes);
+    assert_eq!(tree.num_leaves(), 5);
+
+    for leaf_index in 0..5{
+        assert_eq!(tree.try_get_merkle_path(leaf_index), Ok(tree.get_merkle_path(leaf_index)));
+    }
+    //Leaves 5 to 7 are padding, beyond them the index isn't even in the tree.
+    for leaf_index in [5, 7, 8, usize::MAX]{
+        assert_eq!(tree.try_get_merkle_path(leaf_index), Err(MerkleError::LeafIndexOutOfRange { index: leaf_index, num_leaves: 5 }));
+    }
+}
+
+#[test]
+fn domain_separation_test(){
+    let leaves:Vec<Vec<BinaryField32b>> = (0..8u32).map(|i| vec![BinaryField32b::new(i); 4]).collect();
+    let tree = MerkleTree::new(leaves.iter().map(|leaf| hash_field(leaf)).collect());
+    let root = tree.get_root();
+
+    //A 64 byte leaf made of the two children of the parent of leaves 2 and 3. Untagged, it would hash to the parent itself,
+    //so the parent's path would open it as leaf 1 of a tree with one layer less.
+    let children = [hash_field(&leaves[2]).0, hash_field(&leaves[3]).0].concat();
+    let forged_leaf:Vec<BinaryField32b> = children.chunks(4).map(|bytes| BinaryField32b::new(u32::from_le_bytes(bytes.try_into().unwrap()))).collect();
+    assert_eq!(forged_leaf.len(), 16);
+
+    let parent = hash_concatenation(&hash_field(&leaves[2]), &hash_field(&leaves[3]));
+    assert_ne!(hash_field(&forged_leaf), parent);
+
+    let parent_path = tree.get_merkle_path(2)[1..].to_vec();
+    assert_eq!(merkle_root_from_path(parent, 1, &parent_path), root);
+    assert_eq!(verify_merkle_path(&root, hash_field(&forged_leaf), 1, &parent_path), Err(VerifyError::MerklePathMismatch { query: 1 }));
+}
+
+#[test]
+fn hash_extension_field_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    let column:Vec<BinaryField128b> = (0..8).map(|_| BinaryField128b::random(&mut rng)).collect();
+
+    let bytes:Vec<u8> = [vec![LEAF_TAG], column.iter().flat_map(|d| d.val().to_le_bytes()).collect()].concat();
+    assert_eq!(hash_field(&column), Hash(Keccak256::digest(bytes).to_vec()));
 
-    assert_eq!(hash, *commitment)
+    //Extension field columns are committed and opened the same way as BinaryField32b ones.
+    let tree = MerkleTree::new((0..4).map(|i| hash_field(&column[2*i..2*i + 2])).collect());
+    assert_eq!(verify_merkle_path(&tree.get_root(), hash_field(&column[2..4]), 1, &tree.get_merkle_path(1)), Ok(()));
 }
@@ -1,23 +1,296 @@
-use binius_field::{BinaryField128b, BinaryField32b};
-use crate::utils::merkle::Hash;
+use binius_field::{BinaryField128b, BinaryField32b, Field};
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+use crate::{utils::{encoding::{compute_fourier_bases, EncodingParams, PACKING_DEGREE}, merkle::{Hash, MultiPath, HASH_LEN}, packed_arithmetic::PackedAlgebra32, serialization::as_integer}, verifier::{linear_combination, packed_linear_combination}};
 
 pub mod merkle;
 pub mod ntt;
 pub mod packed_arithmetic;
 pub mod encoding;
+pub mod transcript;
+pub mod serialization;
 
-#[derive(Debug)]
+//Number of columns opened by prove and checked by verify. It matches the heuristic num_queries_for_soundness(2, 0.75, 100), but that assumes queries over the
+//whole codeword while verify only opens the message columns, so no soundness level is claimed for it here.
+pub const NUM_QUERIES:usize = 241;
+
+//Minimum number of terms each rayon task in parallel_xor_sum adds up serially before the partial sums are combined. Our sums are dominated by the BinaryField128b
+//multiplications producing the terms, timing 2^20 terms showed no difference outside of noise between 1 and 2^14, so we pick one that still leaves plenty of tasks for rayon to balance.
+pub const XOR_SUM_MIN_LEN:usize = 1<<10;
+
+//Number of random mask rows commit_hiding appends below the coefficient matrix. Combined with the F_2 basis 2^0, ..., 2^127 of F_128 they give a uniformly random row over F_128,
+//so that many are needed for it to hide every F_128 entry of the folded polynomial.
+pub const HIDING_ROWS:usize = 128;
+
+//The scalars the HIDING_ROWS mask rows are folded with before scaling by the challenge gamma, the F_2 basis of F_128 made of its powers of 2.
+pub fn hiding_basis()->Vec<BinaryField128b>{
+    (0..HIDING_ROWS).map(|i| BinaryField128b::new(1<<i)).collect()
+}
+
+//Sums the items, i.e XORs them as we are in characteristic 2, in parallel. Rayon is kept from splitting below XOR_SUM_MIN_LEN items per task,
+//so the sum isn't broken into a task per term.
+#[cfg(feature = "parallel")]
+pub fn parallel_xor_sum<F:Field>(items: impl IndexedParallelIterator<Item = F>)->F{
+    items.with_min_len(XOR_SUM_MIN_LEN).reduce(||F::ZERO, |acc, item| acc + item)
+}
+
+//Without the parallel feature the items come from a std iterator and are summed in order.
+#[cfg(not(feature = "parallel"))]
+pub fn parallel_xor_sum<F:Field>(items: impl Iterator<Item = F>)->F{
+    items.fold(F::ZERO, |acc, item| acc + item)
+}
+
+//The log sizes of the rows and columns of the committed coefficient matrix are stored alongside the root, together with the log inverse rate
+//of the code the rows were encoded with, which the verifier derives the codeword length and the bounds of the opened columns from.
+//Deserializing goes through RawCommitment, so a deserialized commitment has passed the same checks as one decoded by from_fixed_bytes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "RawCommitment")]
 pub struct Commitment{
     pub commit: Hash,
-    pub cols: usize
+    pub cols: usize,
+    pub rows: usize,
+    pub log_inv_rate: usize
+}
+
+//The fields of a Commitment as they are deserialized, before Commitment::check_shape.
+#[derive(Deserialize)]
+struct RawCommitment{
+    commit: Hash,
+    cols: usize,
+    rows: usize,
+    log_inv_rate: usize
+}
+
+impl TryFrom<RawCommitment> for Commitment{
+    type Error = CommitmentDecodeError;
+
+    fn try_from(raw: RawCommitment)->Result<Commitment, CommitmentDecodeError>{
+        let commitment = Commitment{
+            commit: raw.commit,
+            cols: raw.cols,
+            rows: raw.rows,
+            log_inv_rate: raw.log_inv_rate
+        };
+        commitment.check_shape()?;
+
+        Ok(commitment)
+    }
 }
 
+impl Commitment{
+    //Number of variables of the committed multilinear polynomial, i.e the base-2 logarithm of its number of F_2 coefficients.
+    //Each BinaryField32b in the coefficient matrix packs 2^PACKING_DEGREE of them.
+    pub fn polynomial_log_len(
+        &self
+    )->usize{
+        self.rows + self.cols + PACKING_DEGREE
+    }
+
+    //Number of variables in the column part of a point. A point for the committed polynomial has polynomial_log_len() variables, the first rows of them
+    //select the row of the coefficient matrix, the next cols its column and the last PACKING_DEGREE the bit of the packed BinaryField32b entry,
+    //and the queried columns are folded with the eq table of the first part while the folded polynomial is evaluated at the other two.
+    pub fn col_point_len(
+        &self
+    )->usize{
+        self.cols + PACKING_DEGREE
+    }
+
+    //The params the rows of the coefficient matrix were encoded with, to re-encode the folded polynomial the same way.
+    pub fn encoding_params(
+        &self
+    )->EncodingParams{
+        EncodingParams{
+            rate: 1<<self.log_inv_rate,
+            packing_degree: PACKING_DEGREE
+        }
+    }
+
+    //Canonical 40 byte encoding for on-chain verifiers, the 32 byte root followed by the log number of rows, the log number of columns,
+    //the log inverse rate and PACKING_DEGREE, one byte each, and 4 reserved zero bytes.
+    pub fn to_fixed_bytes(
+        &self
+    )->[u8; FIXED_COMMITMENT_LEN]{
+        assert_eq!(self.commit.0.len(), HASH_LEN, "Root is not a Keccak256 digest.");
+
+        let mut bytes = [0u8; FIXED_COMMITMENT_LEN];
+        bytes[..HASH_LEN].copy_from_slice(&self.commit.0);
+        bytes[HASH_LEN] = self.rows as u8;
+        bytes[HASH_LEN + 1] = self.cols as u8;
+        bytes[HASH_LEN + 2] = self.log_inv_rate as u8;
+        bytes[HASH_LEN + 3] = PACKING_DEGREE as u8;
+        bytes
+    }
+
+    //Decodes the format of to_fixed_bytes, rejecting commitments made without parity blocks, with another packing degree, with a shape the codeword domain can't hold
+    //or for a polynomial of more than 2^MAX_POLYNOMIAL_LOG_LEN coefficients.
+    pub fn from_fixed_bytes(
+        bytes: &[u8; FIXED_COMMITMENT_LEN]
+    )->Result<Commitment, CommitmentDecodeError>{
+        let params = &bytes[HASH_LEN..];
+
+        if params[3] as usize != PACKING_DEGREE{
+            return Err(CommitmentDecodeError::PackingDegreeMismatch { packing_degree: params[3] });
+        }
+        let commitment = Commitment{
+            commit: Hash(bytes[..HASH_LEN].to_vec()),
+            cols: params[1] as usize,
+            rows: params[0] as usize,
+            log_inv_rate: params[2] as usize
+        };
+        commitment.check_shape()?;
+        if params[4..].iter().any(|&byte| byte != 0){
+            return Err(CommitmentDecodeError::NonZeroReserved);
+        }
+
+        Ok(commitment)
+    }
+
+    //The checks from_fixed_bytes and deserializing share, that the code has parity blocks, that the codeword of a row fits the domain and that
+    //the polynomial has at most MAX_POLYNOMIAL_LOG_LEN variables. Sums saturate, as deserialized fields can be anything.
+    fn check_shape(
+        &self
+    )->Result<(), CommitmentDecodeError>{
+        let (rows, cols) = (self.rows, self.cols);
+
+        if self.log_inv_rate == 0{
+            return Err(CommitmentDecodeError::UnsupportedRate { log_inv_rate: self.log_inv_rate });
+        }
+        //The codeword of a row, 2^(cols + log_inv_rate) entries, is evaluated on a domain of BinaryField32b elements.
+        if cols.saturating_add(self.log_inv_rate) > 32{
+            return Err(CommitmentDecodeError::InvalidShape { rows, cols });
+        }
+        //Verifying shifts by rows and cols, and the point has a variable per bit of the index of an F_2 coefficient.
+        let log_len = rows.saturating_add(cols + PACKING_DEGREE);
+        if log_len > MAX_POLYNOMIAL_LOG_LEN{
+            return Err(CommitmentDecodeError::TooLarge { log_len });
+        }
+        Ok(())
+    }
+}
+
+//Largest number of variables of a committed polynomial, so that the index of each of its F_2 coefficients, and any 1<<rows or 1<<cols, fits in a usize.
+pub const MAX_POLYNOMIAL_LOG_LEN:usize = usize::BITS as usize - 1;
+
+//Length of Commitment::to_fixed_bytes.
+pub const FIXED_COMMITMENT_LEN:usize = HASH_LEN + 8;
+
+//Error thrown when decoding a commitment from Commitment::to_fixed_bytes, or deserializing one, fails.
+#[derive(Clone, thiserror::Error, Debug, PartialEq, Eq)]
+pub enum CommitmentDecodeError {
+    #[error("commitment was made at rate 2^-{log_inv_rate}, which has no parity blocks")]
+    UnsupportedRate { log_inv_rate: usize },
+    #[error("commitment was made with packing degree {packing_degree}, expected {PACKING_DEGREE}")]
+    PackingDegreeMismatch { packing_degree: u8 },
+    #[error("a 2^{rows} x 2^{cols} coefficient matrix does not fit the codeword domain")]
+    InvalidShape { rows: usize, cols: usize },
+    #[error("a polynomial of 2^{log_len} coefficients exceeds the supported 2^{MAX_POLYNOMIAL_LOG_LEN}")]
+    TooLarge { log_len: usize },
+    #[error("reserved bytes must be zero")]
+    NonZeroReserved,
+}
+
+//Layout of one of the polynomials committed to by commit_heterogeneous, 2^log_len coefficients occupying 2^log_rows rows starting at row_offset.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PolyShape{
+    pub log_len: usize,
+    pub row_offset: usize,
+    pub log_rows: usize
+}
+
+//Commitment to several polynomials of different lengths under one root. They share the log number of columns cols, each is padded to at least one row
+//and they are stacked as consecutive row blocks, with shapes recording where each one lives so it can be opened on its own.
+#[derive(Clone, Debug)]
+pub struct HeterogeneousCommitment{
+    pub commit: Hash,
+    pub cols: usize,
+    pub shapes: Vec<PolyShape>
+}
+
+impl HeterogeneousCommitment{
+    pub fn total_rows(
+        &self
+    )->usize{
+        self.shapes.last().map_or(0, |shape| shape.row_offset + (1<<shape.log_rows))
+    }
+
+    //Lifts a point for the polynomial at index to one for its padded version, by fixing the padding variables, which select the zero coefficients, to 0.
+    pub fn padded_point(
+        &self,
+        index: usize,
+        point: &[BinaryField128b]
+    )->Vec<BinaryField128b>{
+
+        let shape = &self.shapes[index];
+        assert_eq!(point.len(), shape.log_len + PACKING_DEGREE, "Point has the wrong number of variables.");
+
+        let padding = (shape.log_rows + self.cols).saturating_sub(shape.log_len);
+        let mut padded_point = vec![BinaryField128b::ZERO; padding];
+        padded_point.extend_from_slice(point);
+        padded_point
+    }
+}
+
+//The leaf hashes of the queried columns are optional, they are only included by prove_with_leaf_hashes for callers that need them e.g for a combined transcript.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EvalProof{
+    #[serde(with = "as_integer")]
+    pub folded_poly: Vec<BinaryField128b>,
+    #[serde(with = "as_integer")]
+    pub queried_columns: Vec<Vec<BinaryField32b>>,
+    pub merkle_paths: Vec<Vec<Hash>>,
+    pub leaf_hashes: Option<Vec<Hash>>,
+    pub fold_eval: Option<FoldEval>,
+    pub linear_check: Option<LinearCheck>
+}
+
+//Claimed value of a linear relation sum(coeff[i]*c_i) among the coefficients of the committed polynomial, included by prove_with_linear_check,
+//together with the openings of the columns the involved coefficients live in.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinearCheck{
+    #[serde(with = "as_integer")]
+    pub claimed_sum: BinaryField128b,
+    pub column_indices: Vec<usize>,
+    #[serde(with = "as_integer")]
+    pub columns: Vec<Vec<BinaryField32b>>,
+    pub merkle_paths: Vec<Vec<Hash>>
+}
+
+//The evaluation of the folded polynomial claimed by the prover in prove_with_fold_eval, together with its partial sums over equal sized chunks of the folded polynomial.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FoldEval{
+    #[serde(with = "as_integer")]
+    pub eval: BinaryField128b,
+    #[serde(with = "as_integer")]
+    pub chunk_sums: Vec<BinaryField128b>
+}
+
+//Same as EvalProof, but the queried columns are opened with a single MultiPath instead of one merkle path each, see prove_batched.
+#[derive(Clone, Debug)]
+pub struct BatchEvalProof{
     pub folded_poly: Vec<BinaryField128b>,
     pub queried_columns: Vec<Vec<BinaryField32b>>,
+    pub multi_path: MultiPath
+}
+
+//Openings of one commitment at several points, prove_multi. The queried columns and their merkle paths are shared by all the points,
+//only the folded polynomial is sent once per point.
+#[derive(Clone, Debug)]
+pub struct MultiEvalProof{
+    pub folded_polys: Vec<Vec<BinaryField128b>>,
+    pub queried_columns: Vec<Vec<BinaryField32b>>,
     pub merkle_paths: Vec<Vec<Hash>>
 }
 
+//Opening of a commitment made with commit_hiding, see prove_hiding. The folded polynomial is masked with gamma times the folded mask rows,
+//whose evaluation at the column part of the point is mask_eval, and the columns are opened in the parity part of the codeword only.
+#[derive(Clone, Debug)]
+pub struct HidingEvalProof{
+    pub folded_poly: Vec<BinaryField128b>,
+    pub mask_eval: BinaryField128b,
+    pub queried_columns: Vec<Vec<BinaryField32b>>,
+    pub merkle_paths: Vec<Vec<Hash>>
+}
 
 impl EvalProof{
     pub fn new(
@@ -29,6 +302,162 @@ impl EvalProof{
             folded_poly,
             queried_columns,
             merkle_paths,
+            leaf_hashes: None,
+            fold_eval: None,
+            linear_check: None
+        }
+    }
+
+    pub fn with_leaf_hashes(
+        self,
+        leaf_hashes: Vec<Hash>
+    )->EvalProof{
+        EvalProof{
+            leaf_hashes: Some(leaf_hashes),
+            ..self
+        }
+    }
+
+    pub fn with_fold_eval(
+        self,
+        fold_eval: FoldEval
+    )->EvalProof{
+        EvalProof{
+            fold_eval: Some(fold_eval),
+            ..self
         }
     }
-}
\ No newline at end of file
+
+    pub fn with_linear_check(
+        self,
+        linear_check: LinearCheck
+    )->EvalProof{
+        EvalProof{
+            linear_check: Some(linear_check),
+            ..self
+        }
+    }
+
+    //Bytes taken by the folded polynomial, the queried columns and the merkle paths, i.e the size of the proof without any framing
+    //a serialization adds. The optional leaf hashes, fold evaluation and linear check are not counted.
+    pub fn size_in_bytes(
+        &self
+    )->usize{
+        let folded_poly = self.folded_poly.len()*std::mem::size_of::<BinaryField128b>();
+        let columns:usize = self.queried_columns.iter().map(|column| column.len()*std::mem::size_of::<BinaryField32b>()).sum();
+        let paths:usize = self.merkle_paths.iter().map(|path| path.len()*HASH_LEN).sum();
+
+        folded_poly + columns + paths
+    }
+
+    pub fn num_queries(
+        &self
+    )->usize{
+        self.queried_columns.len()
+    }
+
+    //The evaluation the proof asserts at point, the one verify compares eval against. It's the folded polynomial evaluated at the column part of point,
+    //its last cols + packing_degree variables for 2^cols committed columns, see Commitment::col_point_len. Nothing is checked against the commitment,
+    //so the value only means something once the proof verifies.
+    pub fn claimed_evaluation(
+        &self,
+        point: &[BinaryField128b],
+        packing_degree: usize,
+        cols: usize
+    )->BinaryField128b{
+        let col_point_len = cols + packing_degree;
+        assert!(point.len() >= col_point_len, "Point has {} variables, its column part alone needs {col_point_len}.", point.len());
+
+        folded_evaluation(&self.folded_poly, &point[point.len() - col_point_len..])
+    }
+}
+
+//The folded polynomial evaluated at col_point, the column part of a point, see EvalProof::claimed_evaluation.
+pub(crate) fn folded_evaluation(folded_poly: &[BinaryField128b], col_point: &[BinaryField128b])->BinaryField128b{
+    //The column part of a point of the scheme covers whole BinaryField32b, so the sum can go 32 terms at a time.
+    let scalars = compute_fourier_bases(col_point);
+    if scalars.len()%32 == 0 && folded_poly.len()%32 == 0{
+        packed_linear_combination(&PackedAlgebra32::pack(scalars), &PackedAlgebra32::pack(folded_poly.to_vec()))
+    } else {
+        linear_combination(&scalars, folded_poly)
+    }
+}
+
+//Opening of the single column touched by a coefficient update in both the old and the new commitment.
+//Since the two merkle paths are required to be equal, every other leaf, and so every other systematic coefficient, is the same in both trees.
+#[derive(Clone, Debug)]
+pub struct DiffProof{
+    pub old_column: Vec<BinaryField32b>,
+    pub new_column: Vec<BinaryField32b>,
+    pub old_path: Vec<Hash>,
+    pub new_path: Vec<Hash>
+}
+
+//The challenges a proof was made with, the fiat shamir queries it opens and the scalars the rows were folded with, in the order they were used.
+//The prover records them with prove_recording_challenges and verify_with_recorded_challenges checks the verifier derives the same ones.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChallengeTranscript{
+    pub queries: Vec<usize>,
+    pub fold_scalars: Vec<BinaryField128b>
+}
+
+#[test]
+fn parallel_xor_sum_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    for len in [0, 1, XOR_SUM_MIN_LEN - 1, XOR_SUM_MIN_LEN, 5*XOR_SUM_MIN_LEN + 3]{
+        let items:Vec<BinaryField128b> = (0..len).map(|_| BinaryField128b::random(&mut rng)).collect();
+
+        let expected = items.iter().fold(BinaryField128b::ZERO, |acc, item| acc + *item);
+        assert_eq!(parallel_xor_sum(par_iter!(items).copied()), expected, "Length {len} failed");
+    }
+}
+
+#[test]
+fn fixed_bytes_test(){
+    use rand::thread_rng;
+    use crate::{prover::commit, utils::ntt::AdditiveNTT};
+
+    let mut rng = thread_rng();
+    let l = 10;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let (commitment, _, _) = commit(&poly, &AdditiveNTT::new(l));
+
+    let bytes = commitment.to_fixed_bytes();
+    assert_eq!(bytes[..HASH_LEN], commitment.commit.0);
+    let decoded = Commitment::from_fixed_bytes(&bytes).unwrap();
+    assert_eq!((decoded.commit, decoded.rows, decoded.cols), (commitment.commit, commitment.rows, commitment.cols));
+
+    let mut other_rate = bytes;
+    other_rate[HASH_LEN + 2] = 1;
+    assert_eq!(Commitment::from_fixed_bytes(&other_rate).unwrap().encoding_params().rate, 2);
+
+    let mut no_parity = bytes;
+    no_parity[HASH_LEN + 2] = 0;
+    assert_eq!(Commitment::from_fixed_bytes(&no_parity).unwrap_err(), CommitmentDecodeError::UnsupportedRate { log_inv_rate: 0 });
+
+    let mut too_long = bytes;
+    too_long[HASH_LEN + 2] = (33 - commitment.cols) as u8;
+    assert_eq!(Commitment::from_fixed_bytes(&too_long).unwrap_err(), CommitmentDecodeError::InvalidShape { rows: commitment.rows, cols: commitment.cols });
+
+    let mut wrong_packing = bytes;
+    wrong_packing[HASH_LEN + 3] = 0;
+    assert_eq!(Commitment::from_fixed_bytes(&wrong_packing).unwrap_err(), CommitmentDecodeError::PackingDegreeMismatch { packing_degree: 0 });
+
+    let mut wrong_cols = bytes;
+    wrong_cols[HASH_LEN + 1] = 31;
+    assert_eq!(Commitment::from_fixed_bytes(&wrong_cols).unwrap_err(), CommitmentDecodeError::InvalidShape { rows: commitment.rows, cols: 31 });
+
+    //rows only enters the encoding through the size of the polynomial, which 255 rows overflow.
+    let mut too_many_rows = bytes;
+    too_many_rows[HASH_LEN] = 255;
+    assert_eq!(Commitment::from_fixed_bytes(&too_many_rows).unwrap_err(), CommitmentDecodeError::TooLarge { log_len: 255 + commitment.cols + PACKING_DEGREE });
+    let mut largest = bytes;
+    largest[HASH_LEN] = (MAX_POLYNOMIAL_LOG_LEN - commitment.cols - PACKING_DEGREE) as u8;
+    assert_eq!(Commitment::from_fixed_bytes(&largest).unwrap().polynomial_log_len(), MAX_POLYNOMIAL_LOG_LEN);
+
+    let mut wrong_reserved = bytes;
+    wrong_reserved[FIXED_COMMITMENT_LEN - 1] = 1;
+    assert_eq!(Commitment::from_fixed_bytes(&wrong_reserved).unwrap_err(), CommitmentDecodeError::NonZeroReserved);
+}
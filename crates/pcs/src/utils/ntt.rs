@@ -1,29 +1,155 @@
-use std::{ ops::{AddAssign, Mul}, process::Output, time::Instant};
+use std::{ ops::{AddAssign, Mul}, process::Output, sync::Arc, time::Instant};
 
 use binius_field::{BinaryField, BinaryField1b, BinaryField32b, ExtensionField, Field, TowerField};
 use rand::{random, thread_rng};
-use rayon::{iter::{IntoParallelIterator, IntoParallelRefIterator,IntoParallelRefMutIterator, IndexedParallelIterator, ParallelIterator}, prelude::ParallelSliceMut};
-
+#[cfg(test)]
+use proptest::{arbitrary::any, proptest};
+#[cfg(test)]
+use rand::{rngs::StdRng, SeedableRng};
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+
+//Number of butterflies of a block handed to a rayon thread at once by the parallel transforms.
+pub const NTT_PARALLEL_CHUNK_SIZE:usize = 1<<10;
+
+//The twiddles are behind an Arc so clones of an ntt, e.g one per polynomial committed to, share them instead of recomputing them.
+#[derive(Clone)]
 pub struct  AdditiveNTT{
     log_transform_size: usize,
-    twiddles: Vec<Vec<BinaryField32b>>
+    twiddles: Arc<Vec<Vec<BinaryField32b>>>
+}
+
+//Error thrown when an AdditiveNTT can't be built from the given parameters, or can't transform the given input.
+#[derive(Clone, thiserror::Error, Debug, PartialEq, Eq)]
+pub enum NttError {
+    #[error("expected {expected} levels of twiddles, got {got}")]
+    TwiddleLevelCountMismatch { expected: usize, got: usize },
+    #[error("twiddle level {level} has {got} twiddles, expected {expected}")]
+    TwiddleLevelLengthMismatch { level: usize, expected: usize, got: usize },
+    #[error("input length {len} is not a power of two")]
+    LengthNotPowerOfTwo { len: usize },
+    #[error("input length {len} exceeds the transform size {max}")]
+    LengthTooLarge { len: usize, max: usize },
 }
 
 impl  AdditiveNTT {
     
     pub fn new(log_transform_size: usize)->AdditiveNTT{
-        let twiddles = compute_twiddles(log_transform_size);
+        let twiddles = Arc::new(compute_twiddles(log_transform_size));
         AdditiveNTT{
             log_transform_size,
             twiddles
         }
     }
 
+    //Builds the ntt from twiddles computed elsewhere, e.g by compute_twiddles in another process or taken from another ntt with twiddles(), without recomputing them.
+    //Only their shape is checked against what compute_twiddles(log_transform_size) returns, check_normalization can be used to check their values.
+    pub fn from_twiddles(log_transform_size: usize, twiddles: impl Into<Arc<Vec<Vec<BinaryField32b>>>>)->Result<AdditiveNTT, NttError>{
+        let twiddles = twiddles.into();
+        //compute_twiddles returns a single level with a single twiddle for a transform of size 1.
+        let levels = log_transform_size.max(1);
+
+        if twiddles.len() != levels{
+            return Err(NttError::TwiddleLevelCountMismatch { expected: levels, got: twiddles.len() });
+        }
+        for (level, twiddles_level) in twiddles.iter().enumerate(){
+            let expected = 1<<(levels - 1 - level);
+            if twiddles_level.len() != expected{
+                return Err(NttError::TwiddleLevelLengthMismatch { level, expected, got: twiddles_level.len() });
+            }
+        }
+
+        Ok(AdditiveNTT{
+            log_transform_size,
+            twiddles
+        })
+    }
+
+    //The precomputed twiddles, which can be passed to from_twiddles to build another ntt of the same size sharing them.
+    pub fn twiddles(&self)->&Arc<Vec<Vec<BinaryField32b>>>{
+        &self.twiddles
+    }
+
+    //Checks the precomputed twiddles agree with the normalisation used in the butterflies. For every round r the normalised subspace polynomial
+    //W_r(x)/W_r(2^r) must be 1 at 2^r, and twiddles[r][b] must be its value at b*2^(r+1). The twiddles are subset sums,
+    //so it is enough to check this for b a power of two.
+    pub fn check_normalization(&self)->bool{
+
+        self.twiddles.iter().enumerate().all(|(r, twiddles_r)|{
+            //vanishing_map(x, 0) is 1 rather than the subspace polynomial x of the first round, which the twiddles of that round are computed with.
+            let subspace_poly = |x:BinaryField32b| if r == 0 {x} else {vanishing_map(x, r)};
+
+            let normalising_value:BinaryField32b = match subspace_poly(<BinaryField32b as TowerField>::basis(0, r).unwrap()).invert().into(){
+                Some(inverse) => inverse,
+                None => return false
+            };
+
+            let normalised = |x:BinaryField32b| subspace_poly(x)*normalising_value;
+
+            normalised(<BinaryField32b as TowerField>::basis(0, r).unwrap()) == BinaryField32b::ONE
+            && (0..twiddles_r.len().trailing_zeros()).all(|k| twiddles_r[1<<k] == normalised(BinaryField32b::new(1<<(k as usize + r + 1))))
+        })
+    }
+
     //Forward ntt using precomputed twiddles, we dont parallelise here but rather will do so accross ntts when we encode our polynomial in the pcs.
+    //A single transform too large for one thread should use forward_ntt_parallel instead.
+    //The length of coeffs is only checked in debug builds, see try_forward_ntt.
     pub fn forward_ntt<F:Copy + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(
         &self,
         coeffs: &mut Vec<F>,
         coset:u32
+    ){
+        self.forward_ntt_on_coset(coeffs, BinaryField32b::new(coset))
+    }
+
+    //Same as forward_ntt but with the coset as a field element, so callers computing it from e.g a block index can't silently truncate it to a u32.
+    pub fn forward_ntt_on_coset<F:Copy + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(
+        &self,
+        coeffs: &mut Vec<F>,
+        coset:BinaryField32b
+    ){
+        self.forward_ntt_slice(coeffs, coset)
+    }
+
+    //Same as forward_ntt but checks that the length of coeffs is a power of two no larger than the transform size,
+    //otherwise the rounds are computed from the trailing zeros of the length alone and the result is meaningless.
+    pub fn try_forward_ntt<F:Copy + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(
+        &self,
+        coeffs: &mut [F],
+        coset:u32
+    )->Result<(), NttError>{
+        self.check_len(coeffs.len())?;
+        self.forward_rounds(coeffs, BinaryField32b::new(coset));
+        Ok(())
+    }
+
+    fn check_len(&self, len:usize)->Result<(), NttError>{
+        if !len.is_power_of_two(){
+            return Err(NttError::LengthNotPowerOfTwo { len });
+        }
+        if len > 1<<self.log_transform_size{
+            return Err(NttError::LengthTooLarge { len, max: 1<<self.log_transform_size });
+        }
+        Ok(())
+    }
+
+    //Same as forward_ntt_on_coset but on a slice, so e.g a row of a larger flat buffer can be transformed in place. The Vec methods delegate to this,
+    //and like them the length of coeffs is only checked in debug builds, see try_forward_ntt.
+    pub fn forward_ntt_slice<F:Copy + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(
+        &self,
+        coeffs: &mut [F],
+        coset:BinaryField32b
+    ){
+        if cfg!(debug_assertions){
+            self.check_len(coeffs.len()).unwrap()
+        }
+        self.forward_rounds(coeffs, coset)
+    }
+
+    fn forward_rounds<F:Copy + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(
+        &self,
+        coeffs: &mut [F],
+        coset:BinaryField32b
     ){
         //Depth of the recursion, which is the base-2 logarithm of the length of the input.
 
@@ -35,7 +161,7 @@ impl  AdditiveNTT {
             let parts = 1<<r;
             
             let normalising_value = vanishing_map(<BinaryField32b as TowerField>::basis(0, r as usize).unwrap(), r as usize).invert().unwrap();
-            let coeset_twiddle = vanishing_map(BinaryField32b::new(coset), r as usize)*normalising_value;
+            let coeset_twiddle = vanishing_map(coset, r as usize)*normalising_value;
 
             for p in 0..parts{
                 for b in 0..(1<<(rounds-1 - r)){
@@ -56,10 +182,100 @@ impl  AdditiveNTT {
         }
     }
 
+    //Debug helper checking that the inverse transform on coset undoes the forward one for coeffs. Unlike comparing against poly_eval this is cheap,
+    //and it exercises the coset twiddles for any coset rather than only the domain itself.
+    pub fn check_roundtrip<F:Copy + PartialEq + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(&self, coeffs:&[F], coset:BinaryField32b)->bool{
+        let mut transformed = coeffs.to_vec();
+        self.forward_ntt_slice(&mut transformed, coset);
+        self.inverse_ntt_slice(&mut transformed, coset);
+        transformed == coeffs
+    }
+
+    //Computes the single entry at index of the forward ntt of coeffs. Only the butterflies that feed into that entry are evaluated,
+    //at each round we keep the half of the current values whose index agrees with index on the bit being processed, so this takes O(n) instead of O(n log n).
+    pub fn forward_ntt_single<F:Copy + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(
+        &self,
+        coeffs: &[F],
+        coset:u32,
+        index:usize
+    )->F{
+        assert!(index < coeffs.len());
+
+        let rounds = coeffs.len().trailing_zeros();
+        let mut current = coeffs.to_vec();
+
+        for r in (0..rounds).rev(){
+            let half = 1<<r;
+
+            let normalising_value = vanishing_map(<BinaryField32b as TowerField>::basis(0, r as usize).unwrap(), r as usize).invert().unwrap();
+            let coset_twiddle = vanishing_map(BinaryField32b::new(coset), r as usize)*normalising_value;
+            let twiddle = self.twiddles[r as usize][index>>(r+1)] + coset_twiddle;
+
+            for p in 0..half{
+                let mut left_val = current[p];
+                left_val += current[p + half] * twiddle;
+
+                if (index>>r)&1 == 0{
+                    current[p] = left_val;
+                }
+                else{
+                    let mut right_val = current[p + half];
+                    right_val += left_val;
+                    current[p] = right_val;
+                }
+            }
+            current.truncate(half);
+        }
+
+        current[0]
+    }
+
+    //The length of coeffs is only checked in debug builds, see try_inverse_ntt.
     pub fn inverse_ntt<F:Copy + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(
         &self,
         coeffs: &mut Vec<F>,
         coset:u32
+    ){
+        self.inverse_ntt_on_coset(coeffs, BinaryField32b::new(coset))
+    }
+
+    //Same as inverse_ntt but with the coset as a field element, see forward_ntt_on_coset.
+    pub fn inverse_ntt_on_coset<F:Copy + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(
+        &self,
+        coeffs: &mut Vec<F>,
+        coset:BinaryField32b
+    ){
+        self.inverse_ntt_slice(coeffs, coset)
+    }
+
+    //Same as inverse_ntt but checks the length of coeffs like try_forward_ntt.
+    pub fn try_inverse_ntt<F:Copy + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(
+        &self,
+        coeffs: &mut [F],
+        coset:u32
+    )->Result<(), NttError>{
+        self.check_len(coeffs.len())?;
+        self.inverse_rounds(coeffs, BinaryField32b::new(coset));
+        Ok(())
+    }
+
+    //Same as inverse_ntt_on_coset but on a slice, so e.g a row of a larger flat buffer can be transformed in place. The Vec methods delegate to this,
+    //and like them the length of coeffs is only checked in debug builds, see try_inverse_ntt.
+    pub fn inverse_ntt_slice<F:Copy + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(
+        &self,
+        coeffs: &mut [F],
+        coset:BinaryField32b
+    ){
+        if cfg!(debug_assertions){
+            self.check_len(coeffs.len()).unwrap()
+        }
+        self.inverse_rounds(coeffs, coset)
+    }
+
+    fn inverse_rounds<F:Copy + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(
+        &self,
+        coeffs: &mut [F],
+        coset:BinaryField32b
     ){
         //Depth of the recursion, which is the base-2 logarithm of the length of the input.
 
@@ -71,7 +287,7 @@ impl  AdditiveNTT {
             let parts = 1<<r;
 
             let normalising_value = vanishing_map(<BinaryField32b as TowerField>::basis(0, r as usize).unwrap(), r as usize).invert().unwrap();
-            let coset_twiddle = vanishing_map(BinaryField32b::new(coset), r as usize)*normalising_value;
+            let coset_twiddle = vanishing_map(coset, r as usize)*normalising_value;
 
             for p in 0..parts{
                 for b in 0..1<<(rounds-1 - r){
@@ -93,25 +309,151 @@ impl  AdditiveNTT {
 
         }
     }
+
+    //Same as forward_ntt but each round is split across rayon threads. The butterflies of round r act on disjoint blocks of 2^(r+1) coefficients,
+    //block b pairing its two halves with twiddle b, so the blocks are independent subproblems. The halves are further split into chunks,
+    //since the last rounds only have a few blocks.
+    pub fn forward_ntt_parallel<F:Copy + Send + Sync + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(
+        &self,
+        coeffs: &mut [F],
+        coset:u32
+    ){
+        let rounds = coeffs.len().trailing_zeros();
+
+        for r in (0..rounds).rev(){
+            let normalising_value = vanishing_map(<BinaryField32b as TowerField>::basis(0, r as usize).unwrap(), r as usize).invert().unwrap();
+            let coset_twiddle = vanishing_map(BinaryField32b::new(coset), r as usize)*normalising_value;
+            let twiddles_r = &self.twiddles[r as usize];
+
+            par_chunks_mut!(coeffs, 1<<(r+1)).enumerate().for_each(|(b, block)|{
+                let twiddle = twiddles_r[b] + coset_twiddle;
+                let (left, right) = block.split_at_mut(1<<r);
+
+                par_chunks_mut!(left, NTT_PARALLEL_CHUNK_SIZE).zip(par_chunks_mut!(right, NTT_PARALLEL_CHUNK_SIZE)).for_each(|(left, right)|{
+                    for (left_val, right_val) in left.iter_mut().zip(right.iter_mut()){
+                        *left_val += *right_val * twiddle;
+                        *right_val += *left_val;
+                    }
+                });
+            });
+        }
+    }
+
+    //Same as inverse_ntt but each round is split across rayon threads, see forward_ntt_parallel.
+    pub fn inverse_ntt_parallel<F:Copy + Send + Sync + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(
+        &self,
+        coeffs: &mut [F],
+        coset:u32
+    ){
+        let rounds = coeffs.len().trailing_zeros();
+
+        for r in 0..rounds{
+            let normalising_value = vanishing_map(<BinaryField32b as TowerField>::basis(0, r as usize).unwrap(), r as usize).invert().unwrap();
+            let coset_twiddle = vanishing_map(BinaryField32b::new(coset), r as usize)*normalising_value;
+            let twiddles_r = &self.twiddles[r as usize];
+
+            par_chunks_mut!(coeffs, 1<<(r+1)).enumerate().for_each(|(b, block)|{
+                let twiddle = twiddles_r[b] + coset_twiddle;
+                let (left, right) = block.split_at_mut(1<<r);
+
+                par_chunks_mut!(left, NTT_PARALLEL_CHUNK_SIZE).zip(par_chunks_mut!(right, NTT_PARALLEL_CHUNK_SIZE)).for_each(|(left, right)|{
+                    for (left_val, right_val) in left.iter_mut().zip(right.iter_mut()){
+                        *right_val += *left_val;
+                        *left_val += *right_val * twiddle;
+                    }
+                });
+            });
+        }
+    }
+
+    //Forward ntt of every row on the same coset. All the rows go through a round before the next one starts, so the twiddles of the round
+    //are computed and loaded once for the whole batch rather than once per row. The rows are split across rayon threads and must have the same length.
+    pub fn forward_ntt_batch<F:Copy + Send + Sync + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(
+        &self,
+        rows: &mut [Vec<F>],
+        coset:BinaryField32b
+    ){
+        let rounds = self.batch_rounds(rows);
+
+        for r in (0..rounds).rev(){
+            let normalising_value = vanishing_map(<BinaryField32b as TowerField>::basis(0, r as usize).unwrap(), r as usize).invert().unwrap();
+            let coset_twiddle = vanishing_map(coset, r as usize)*normalising_value;
+            let twiddles_r = &self.twiddles[r as usize];
+
+            par_iter_mut!(rows).for_each(|row|{
+                for (b, block) in row.chunks_mut(1<<(r+1)).enumerate(){
+                    let twiddle = twiddles_r[b] + coset_twiddle;
+                    let (left, right) = block.split_at_mut(1<<r);
+
+                    for (left_val, right_val) in left.iter_mut().zip(right.iter_mut()){
+                        *left_val += *right_val * twiddle;
+                        *right_val += *left_val;
+                    }
+                }
+            });
+        }
+    }
+
+    //Inverse ntt of every row on the same coset, see forward_ntt_batch.
+    pub fn inverse_ntt_batch<F:Copy + Send + Sync + Mul<BinaryField32b, Output =F> + AddAssign<<F as Mul<BinaryField32b>>::Output>>(
+        &self,
+        rows: &mut [Vec<F>],
+        coset:BinaryField32b
+    ){
+        let rounds = self.batch_rounds(rows);
+
+        for r in 0..rounds{
+            let normalising_value = vanishing_map(<BinaryField32b as TowerField>::basis(0, r as usize).unwrap(), r as usize).invert().unwrap();
+            let coset_twiddle = vanishing_map(coset, r as usize)*normalising_value;
+            let twiddles_r = &self.twiddles[r as usize];
+
+            par_iter_mut!(rows).for_each(|row|{
+                for (b, block) in row.chunks_mut(1<<(r+1)).enumerate(){
+                    let twiddle = twiddles_r[b] + coset_twiddle;
+                    let (left, right) = block.split_at_mut(1<<r);
+
+                    for (left_val, right_val) in left.iter_mut().zip(right.iter_mut()){
+                        *right_val += *left_val;
+                        *left_val += *right_val * twiddle;
+                    }
+                }
+            });
+        }
+    }
+
+    //Number of rounds of a batched transform, checking the rows have the same length and, in debug builds, that it is valid for this ntt.
+    fn batch_rounds<F>(&self, rows:&[Vec<F>])->u32{
+        let len = rows.first().map_or(1, |row| row.len());
+        assert!(rows.iter().all(|row| row.len() == len), "Rows of a batched ntt must have the same length.");
+        if cfg!(debug_assertions){
+            self.check_len(len).unwrap()
+        }
+        len.trailing_zeros()
+    }
 }
 
 //Recursively generates twiddles.
+//Every level of the twiddles of a smaller transform is a prefix of the same level for a larger one, the subspace evaluations of each level only
+//gaining basis elements at the end, so an ntt can transform any power of two length up to its size by only indexing the rounds and twiddles it needs.
+//The expansion of each level only depends on that level's subspace evaluations so the levels are expanded in parallel, the doubling within a level stays sequential.
+//Level i expands to 2^(log_transform_size - 1 - i) twiddles, so level 0 alone is half of the total work and the speedup over expanding the levels one after the other is at most 2x.
 pub fn compute_twiddles(log_transform_size:usize)->Vec<Vec<BinaryField32b>>{
     let s_evals = precompute_subspace_evals(log_transform_size);
-    let s_evals_expanded = s_evals
-        .iter()
-        .enumerate()
-        .map(|(i, s_evals_i)| {
-            let mut expanded = Vec::with_capacity(1 << s_evals_i.len());
-            expanded.push(BinaryField32b::ZERO);
-            for &eval in s_evals_i.iter() {
-                for i in 0..expanded.len() {
-                    expanded.push(expanded[i] + eval);
-                }
-            }
-            expanded
-                }).collect();
-    s_evals_expanded
+    par_iter!(s_evals)
+        .map(|s_evals_i| expand_subspace_evals(s_evals_i))
+        .collect()
+}
+
+//All the subset sums of the subspace evaluations of a level, built by doubling.
+fn expand_subspace_evals(s_evals_i:&[BinaryField32b])->Vec<BinaryField32b>{
+    let mut expanded = Vec::with_capacity(1 << s_evals_i.len());
+    expanded.push(BinaryField32b::ZERO);
+    for &eval in s_evals_i.iter() {
+        for i in 0..expanded.len() {
+            expanded.push(expanded[i] + eval);
+        }
+    }
+    expanded
 }
 
 fn precompute_subspace_evals(log_domain_size: usize) -> Vec<Vec<BinaryField32b>>{
@@ -179,6 +521,199 @@ pub fn vanishing_map(val:BinaryField32b, size:usize)->BinaryField32b{
     res
 }
 
+#[test]
+fn check_normalization_test(){
+    for log_transform_size in 1..14{
+        let mut ntt = AdditiveNTT::new(log_transform_size);
+        assert!(ntt.check_normalization(), "Normalisation failed for 2^{log_transform_size}");
+
+        if ntt.twiddles[0].len() > 1{
+            Arc::make_mut(&mut ntt.twiddles)[0][1] += BinaryField32b::ONE;
+            assert!(!ntt.check_normalization());
+        }
+    }
+}
+
+#[test]
+fn from_twiddles_test(){
+    for log_transform_size in 0..12{
+        let ntt = AdditiveNTT::new(log_transform_size);
+        let loaded = AdditiveNTT::from_twiddles(log_transform_size, compute_twiddles(log_transform_size)).unwrap();
+
+        let poly:Vec<BinaryField32b> = (0..1<<log_transform_size).map(|_| BinaryField32b::random(thread_rng())).collect();
+        for coset in [0, 1<<log_transform_size]{
+            let (mut expected, mut got) = (poly.clone(), poly.clone());
+            ntt.forward_ntt(&mut expected, coset);
+            loaded.forward_ntt(&mut got, coset);
+            assert_eq!(got, expected);
+
+            ntt.inverse_ntt(&mut expected, coset);
+            loaded.inverse_ntt(&mut got, coset);
+            assert_eq!(got, expected);
+        }
+    }
+
+    let mut twiddles = compute_twiddles(6);
+    twiddles[2].pop();
+    assert_eq!(AdditiveNTT::from_twiddles(6, twiddles).err(), Some(NttError::TwiddleLevelLengthMismatch { level: 2, expected: 8, got: 7 }));
+    assert_eq!(AdditiveNTT::from_twiddles(7, compute_twiddles(6)).err(), Some(NttError::TwiddleLevelCountMismatch { expected: 7, got: 6 }));
+}
+
+#[test]
+fn shared_twiddles_test(){
+    let ntt = AdditiveNTT::new(8);
+    let shared = AdditiveNTT::from_twiddles(8, ntt.twiddles().clone()).unwrap();
+    let cloned = shared.clone();
+    assert!(Arc::ptr_eq(ntt.twiddles(), shared.twiddles()));
+    assert!(Arc::ptr_eq(ntt.twiddles(), cloned.twiddles()));
+
+    let poly:Vec<BinaryField32b> = (0..1<<8).map(|_| BinaryField32b::random(thread_rng())).collect();
+    for coset in [0, 1<<8]{
+        let mut expected = poly.clone();
+        ntt.forward_ntt(&mut expected, coset);
+        for other in [&shared, &cloned]{
+            let mut got = poly.clone();
+            other.forward_ntt(&mut got, coset);
+            assert_eq!(got, expected);
+
+            other.inverse_ntt(&mut got, coset);
+            assert_eq!(got, poly);
+        }
+    }
+}
+
+#[test]
+fn smaller_transform_test(){
+    let large = AdditiveNTT::new(12);
+    let small = AdditiveNTT::new(8);
+
+    for (level, (large_level, small_level)) in large.twiddles().iter().zip(small.twiddles().iter()).enumerate(){
+        assert_eq!(large_level[..small_level.len()], small_level[..], "level {level} of the twiddles is not a prefix");
+    }
+
+    let poly:Vec<BinaryField32b> = (0..1<<8).map(|_| BinaryField32b::random(thread_rng())).collect();
+    for coset in [0, 1<<8, 3<<8]{
+        let (mut expected, mut got) = (poly.clone(), poly.clone());
+        small.forward_ntt(&mut expected, coset);
+        large.forward_ntt(&mut got, coset);
+        assert_eq!(got, expected);
+        assert_eq!(large.forward_ntt_single(&poly, coset, 5), expected[5]);
+
+        small.inverse_ntt(&mut expected, coset);
+        large.inverse_ntt(&mut got, coset);
+        assert_eq!(got, expected);
+        assert_eq!(got, poly);
+    }
+}
+
+#[test]
+fn field_coset_test(){
+    let ntt = AdditiveNTT::new(8);
+    let poly:Vec<BinaryField32b> = (0..1<<8).map(|_| BinaryField32b::random(thread_rng())).collect();
+
+    //Cosets with the top bits set, whose block index times the transform length only just fits in a u32.
+    for coset in [1<<8, 0x8000_0000, u32::MAX<<8]{
+        let (mut expected, mut got) = (poly.clone(), poly.clone());
+        ntt.forward_ntt(&mut expected, coset);
+        ntt.forward_ntt_on_coset(&mut got, BinaryField32b::new(coset));
+        assert_eq!(got, expected);
+        assert_eq!(ntt.forward_ntt_single(&poly, coset, 7), got[7]);
+
+        ntt.inverse_ntt(&mut expected, coset);
+        ntt.inverse_ntt_on_coset(&mut got, BinaryField32b::new(coset));
+        assert_eq!(got, expected);
+        assert_eq!(got, poly);
+    }
+}
+
+#[test]
+fn batch_ntt_test(){
+    let ntt = AdditiveNTT::new(6);
+    let matrix:Vec<Vec<BinaryField32b>> = (0..64).map(|_| (0..64).map(|_| BinaryField32b::random(thread_rng())).collect()).collect();
+
+    for coset in [0, 1<<6, 3<<6]{
+        let mut expected = matrix.clone();
+        expected.iter_mut().for_each(|row| ntt.forward_ntt(row, coset));
+        let mut got = matrix.clone();
+        ntt.forward_ntt_batch(&mut got, BinaryField32b::new(coset));
+        assert_eq!(got, expected);
+
+        expected.iter_mut().for_each(|row| ntt.inverse_ntt(row, coset));
+        ntt.inverse_ntt_batch(&mut got, BinaryField32b::new(coset));
+        assert_eq!(got, expected);
+        assert_eq!(got, matrix);
+    }
+}
+
+#[test]
+fn slice_ntt_test(){
+    let ntt = AdditiveNTT::new(6);
+    let flat:Vec<BinaryField32b> = (0..4<<6).map(|_| BinaryField32b::random(thread_rng())).collect();
+    let coset = BinaryField32b::new(1<<6);
+
+    let mut transformed = flat.clone();
+    transformed.chunks_mut(1<<6).for_each(|row| ntt.forward_ntt_slice(row, coset));
+    for (row, transformed_row) in flat.chunks(1<<6).zip(transformed.chunks(1<<6)){
+        let mut expected = row.to_vec();
+        ntt.forward_ntt_on_coset(&mut expected, coset);
+        assert_eq!(transformed_row, expected);
+    }
+
+    transformed.chunks_mut(1<<6).for_each(|row| ntt.inverse_ntt_slice(row, coset));
+    assert_eq!(transformed, flat);
+}
+
+#[test]
+fn try_ntt_test(){
+    let ntt = AdditiveNTT::new(6);
+    let poly:Vec<BinaryField32b> = (0..1<<6).map(|_| BinaryField32b::random(thread_rng())).collect();
+
+    let (mut expected, mut got) = (poly.clone(), poly.clone());
+    ntt.forward_ntt(&mut expected, 0);
+    assert_eq!(ntt.try_forward_ntt(&mut got, 0), Ok(()));
+    assert_eq!(got, expected);
+    assert_eq!(ntt.try_inverse_ntt(&mut got, 0), Ok(()));
+    assert_eq!(got, poly);
+
+    let mut not_power_of_two = poly[..48].to_vec();
+    assert_eq!(ntt.try_forward_ntt(&mut not_power_of_two, 0), Err(NttError::LengthNotPowerOfTwo { len: 48 }));
+    assert_eq!(ntt.try_inverse_ntt(&mut not_power_of_two, 0), Err(NttError::LengthNotPowerOfTwo { len: 48 }));
+    assert_eq!(not_power_of_two, poly[..48]);
+
+    let mut too_large = [poly.clone(), poly.clone()].concat();
+    assert_eq!(ntt.try_forward_ntt(&mut too_large, 0), Err(NttError::LengthTooLarge { len: 128, max: 64 }));
+    assert_eq!(ntt.try_inverse_ntt(&mut too_large, 0), Err(NttError::LengthTooLarge { len: 128, max: 64 }));
+}
+
+#[test]
+fn parallel_ntt_test(){
+    for log_transform_size in 3..17{
+        let ntt = AdditiveNTT::new(log_transform_size);
+        let poly:Vec<BinaryField32b> = (0..1<<log_transform_size).map(|_| BinaryField32b::random(thread_rng())).collect();
+
+        for coset in [0, 1<<log_transform_size]{
+            let (mut serial, mut parallel) = (poly.clone(), poly.clone());
+            ntt.forward_ntt(&mut serial, coset);
+            ntt.forward_ntt_parallel(&mut parallel, coset);
+            assert_eq!(parallel, serial, "forward transforms differ for 2^{log_transform_size}");
+
+            ntt.inverse_ntt(&mut serial, coset);
+            ntt.inverse_ntt_parallel(&mut parallel, coset);
+            assert_eq!(parallel, serial, "inverse transforms differ for 2^{log_transform_size}");
+            assert_eq!(parallel, poly);
+        }
+    }
+}
+
+#[test]
+fn parallel_twiddles_test(){
+    for log_transform_size in 1..16{
+        let serial:Vec<Vec<BinaryField32b>> = precompute_subspace_evals(log_transform_size).iter().map(|s_evals_i| expand_subspace_evals(s_evals_i)).collect();
+
+        assert_eq!(compute_twiddles(log_transform_size), serial);
+    }
+}
+
 #[test]
 
 fn twiddles_test(){
@@ -223,7 +758,7 @@ fn forward_test(){
         let time = Instant::now();
 
         
-        let mut poly: Vec<BinaryField32b> = (0..1<<i).into_par_iter()
+        let mut poly: Vec<BinaryField32b> = into_par_iter!(0..1<<i)
         .map(|_| {
             let mut rng = thread_rng();
             BinaryField32b::random(rng)
@@ -232,8 +767,8 @@ fn forward_test(){
         println!("Time taken {:?}", time.elapsed());
 
 
-        let mut test_evals:Vec<_> = (0..1<<i).into_par_iter().map(|i| poly_eval(&poly, BinaryField32b::from(i))).collect();
-        test_evals.par_chunks_mut(2).for_each(|pair| pair.swap(0, 1));
+        let mut test_evals:Vec<_> = into_par_iter!(0..1<<i).map(|i| poly_eval(&poly, BinaryField32b::from(i))).collect();
+        par_chunks_mut!(test_evals, 2).for_each(|pair| pair.swap(0, 1));
         println!("Computing twiddles");
 
         let ntt = AdditiveNTT::new(poly.len().trailing_zeros() as usize);
@@ -245,3 +780,15 @@ fn forward_test(){
         assert_eq!(test_evals, poly)
     }
 }
+
+#[cfg(test)]
+proptest!{
+    #[test]
+    fn roundtrip_test(log_transform_size in 3usize..13, coset in any::<u32>(), seed in any::<u64>()){
+        let ntt = AdditiveNTT::new(log_transform_size);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let poly:Vec<BinaryField32b> = (0..1<<log_transform_size).map(|_| BinaryField32b::random(&mut rng)).collect();
+
+        assert!(ntt.check_roundtrip(&poly, BinaryField32b::new(coset)), "Roundtrip failed for 2^{log_transform_size} on coset {coset:#x}");
+    }
+}
@@ -1,114 +1,291 @@
-use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
+use std::{iter::{Product, Sum}, ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign}};
 use paste::paste;
-use binius_field::{BinaryField128b, BinaryField2b,BinaryField16b, BinaryField8b, BinaryField4b, BinaryField32b, Field};
-use rand::thread_rng;
-use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use binius_field::{BinaryField128b, BinaryField1b, BinaryField2b,BinaryField16b, BinaryField8b, BinaryField4b, BinaryField32b, Error as FieldError, ExtensionField, Field};
+use rand::{thread_rng, RngCore};
+use subtle::{Choice, ConstantTimeEq};
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
 use concat_arrays::concat_arrays;
 
-//Implementation of the packed algebra required as part of the block level testing scheme, essentially requiring us to perform operations with vectors of 32 F_128 elements as if they were elements in the F_32 extension field.
+use crate::utils::parallel_xor_sum;
+
+//Implementation of the packed algebra required as part of the block level testing scheme, essentially requiring us to perform operations with vectors of N F_128 elements as if they were elements in the F_N extension field.
+//N is the width of one of the tower fields the packed multiplications below are generated for, i.e 2, 4, 8, 16 or 32, the scheme itself packing 32.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub struct PackedAlgebra32(pub [BinaryField128b;32]);
+pub struct PackedAlgebra<const N: usize>(pub [BinaryField128b;N]);
 
+pub type PackedAlgebra32 = PackedAlgebra<32>;
 
-impl PackedAlgebra32 {
-    pub fn new(vec: [BinaryField128b;32])->PackedAlgebra32{
-        PackedAlgebra32(vec)
+impl<const N: usize> PackedAlgebra<N> {
+    pub fn new(vec: [BinaryField128b;N])->PackedAlgebra<N>{
+        PackedAlgebra(vec)
     }
 
-    pub fn pack(vec: Vec<BinaryField128b>)->Vec<PackedAlgebra32>{
+    pub fn pack(vec: Vec<BinaryField128b>)->Vec<PackedAlgebra<N>>{
+
+        par_chunks!(vec, N)
+        .map(|chunk| PackedAlgebra(<[BinaryField128b;N]>::try_from(chunk).unwrap())).collect()
+    }
 
-        vec.par_iter().copied().chunks(32)
-        .map(|chunk| PackedAlgebra32(<[BinaryField128b;32]>::try_from(chunk).unwrap())).collect()
+    pub fn unpack(vec:Vec<PackedAlgebra<N>>)->Vec<BinaryField128b>{
+        par_iter!(vec).flat_map(|pack| pack.0.to_vec()).collect()
     }
 
-    pub fn unpack(vec:Vec<PackedAlgebra32>)->Vec<BinaryField128b>{
-        vec.par_iter().map(|pack| pack.0.to_vec()).flatten().collect()
+    //Checks every lane is ZERO or ONE, i.e the pack is the bit decomposition of a BinaryField{N}b, as it is when a column is folded with boolean scalars.
+    pub fn is_bit_embedding(&self)->bool{
+        self.0.iter().all(|&lane| lane == BinaryField128b::ZERO || lane == BinaryField128b::ONE)
     }
 
 }
 
-impl Mul<BinaryField32b> for PackedAlgebra32 {
+macro_rules! impl_packed_algebra_mul {
+    ($($level:literal),*) => {
+        paste!{
+        $(
+            impl Mul<[<BinaryField $level b>]> for PackedAlgebra<$level> {
 
-    type Output = Self;
+                type Output = Self;
 
-    fn mul(self, rhs:BinaryField32b) -> Self::Output {
+                fn mul(self, rhs:[<BinaryField $level b>]) -> Self::Output {
 
-        PackedAlgebra32(PackedMul32(&self.0, rhs))
-    }
+                    PackedAlgebra([<PackedMul $level>](&self.0, rhs))
+                }
+
+            }
+        )*
+        }
+    };
+}
+
+impl_packed_algebra_mul!(32, 16, 8, 4, 2);
 
+//The bit embedding of val, i.e lane i is ONE when bit i of val is set and ZERO otherwise.
+impl From<BinaryField32b> for PackedAlgebra32 {
+    fn from(val:BinaryField32b)->Self{
+        PackedAlgebra(core::array::from_fn(|i| if (val.val()>>i)&1 == 1 {BinaryField128b::ONE} else {BinaryField128b::ZERO}))
+    }
 }
 
 
-impl Add for PackedAlgebra32 {
+impl<const N: usize> Add for PackedAlgebra<N> {
 
     type Output = Self;
 
-    fn add(self, rhs:PackedAlgebra32) -> Self::Output {
-        PackedAlgebra32(packed_tower_add_32(&self.0, &rhs.0))
+    fn add(self, rhs:PackedAlgebra<N>) -> Self::Output {
+        PackedAlgebra(packed_tower_add(&self.0, &rhs.0))
     }
 
 }
 
-impl Sub for PackedAlgebra32 {
+/// Subtraction is lane-wise addition, the lanes lying in a field of characteristic 2 where every element is its own negative.
+/// The Sub impls below all call packed_tower_add on purpose, sub_equals_add checks they keep agreeing with Add.
+impl<const N: usize> Sub for PackedAlgebra<N> {
 
     type Output = Self;
 
-    fn sub(self, rhs:PackedAlgebra32) -> Self::Output {
-        PackedAlgebra32(packed_tower_add_32(&self.0, &rhs.0))
+    fn sub(self, rhs:PackedAlgebra<N>) -> Self::Output {
+        PackedAlgebra(packed_tower_add(&self.0, &rhs.0))
     }
 
 }
 
-impl Add<&Self> for PackedAlgebra32 {
+impl<const N: usize> Add<&Self> for PackedAlgebra<N> {
 
     type Output = Self;
 
-    fn add(self, rhs:&PackedAlgebra32) -> Self::Output {
-        PackedAlgebra32(packed_tower_add_32(&self.0, &rhs.0))
+    fn add(self, rhs:&PackedAlgebra<N>) -> Self::Output {
+        PackedAlgebra(packed_tower_add(&self.0, &rhs.0))
     }
 
 }
 
-impl Sub<&Self> for PackedAlgebra32 {
+impl<const N: usize> Sub<&Self> for PackedAlgebra<N> {
 
     type Output = Self;
 
-    fn sub(self, rhs:&PackedAlgebra32) -> Self::Output {
-        PackedAlgebra32(packed_tower_add_32(&self.0, &rhs.0))
+    fn sub(self, rhs:&PackedAlgebra<N>) -> Self::Output {
+        PackedAlgebra(packed_tower_add(&self.0, &rhs.0))
     }
 
 }
 
-impl Add<&mut Self> for PackedAlgebra32 {
+impl<const N: usize> Add<&mut Self> for PackedAlgebra<N> {
 
     type Output = Self;
 
-    fn add(self, rhs:&mut PackedAlgebra32) -> Self::Output {
-        PackedAlgebra32(packed_tower_add_32(&self.0, &rhs.0))
+    fn add(self, rhs:&mut PackedAlgebra<N>) -> Self::Output {
+        PackedAlgebra(packed_tower_add(&self.0, &rhs.0))
     }
 
 }
 
-impl Sub<&mut Self> for PackedAlgebra32 {
+impl<const N: usize> Sub<&mut Self> for PackedAlgebra<N> {
 
     type Output = Self;
 
-    fn sub(self, rhs:&mut PackedAlgebra32) -> Self::Output {
-        PackedAlgebra32(packed_tower_add_32(&self.0, &rhs.0))
+    fn sub(self, rhs:&mut PackedAlgebra<N>) -> Self::Output {
+        PackedAlgebra(packed_tower_add(&self.0, &rhs.0))
     }
 
 }
 
-impl AddAssign for PackedAlgebra32 {
+impl<const N: usize> AddAssign for PackedAlgebra<N> {
     fn add_assign(&mut self, rhs: Self) {
-        *self = PackedAlgebra32(packed_tower_add_32(&self.0, &rhs.0))
+        *self = PackedAlgebra(packed_tower_add(&self.0, &rhs.0))
     }
 }
 
-impl SubAssign for PackedAlgebra32 {
+/// Goes through Sub rather than AddAssign, so it stays in line with the other Sub impls if they ever change.
+impl<const N: usize> SubAssign for PackedAlgebra<N> {
     fn sub_assign(&mut self, rhs: Self) {
-        *self += rhs
+        *self = *self - rhs
+    }
+}
+
+//As a PackedField a pack is just the vector of its N lanes, so the operations with a BinaryField128b or another pack below act lane by lane.
+//In particular multiplying two packs is the lane-wise product, not a product in the algebra, whereas Mul<BinaryField{N}b> above acts on the F_N side.
+impl<const N: usize> Default for PackedAlgebra<N> {
+    fn default()->Self{
+        PackedAlgebra([BinaryField128b::ZERO; N])
+    }
+}
+
+impl<const N: usize> ConstantTimeEq for PackedAlgebra<N> {
+    fn ct_eq(&self, other:&Self)->Choice{
+        self.0.iter().zip(other.0.iter()).fold(Choice::from(1), |acc, (lane, other_lane)| acc & lane.ct_eq(other_lane))
+    }
+}
+
+impl<const N: usize> Mul for PackedAlgebra<N> {
+
+    type Output = Self;
+
+    fn mul(self, rhs:PackedAlgebra<N>) -> Self::Output {
+        PackedAlgebra(core::array::from_fn(|i| self.0[i]*rhs.0[i]))
+    }
+
+}
+
+impl<const N: usize> MulAssign for PackedAlgebra<N> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self*rhs
+    }
+}
+
+impl<const N: usize> Add<BinaryField128b> for PackedAlgebra<N> {
+
+    type Output = Self;
+
+    fn add(self, rhs:BinaryField128b) -> Self::Output {
+        self + PackedAlgebra([rhs; N])
+    }
+
+}
+
+impl<const N: usize> Sub<BinaryField128b> for PackedAlgebra<N> {
+
+    type Output = Self;
+
+    fn sub(self, rhs:BinaryField128b) -> Self::Output {
+        self - PackedAlgebra([rhs; N])
     }
+
+}
+
+//Scales the pack on the F_128 side. For the bit embedding of a BinaryField32b v this is v times rhs in the algebra, which is what
+//unpacked_linear_combination accumulates with a conditional add of rhs per set bit of v.
+impl<const N: usize> Mul<BinaryField128b> for PackedAlgebra<N> {
+
+    type Output = Self;
+
+    fn mul(self, rhs:BinaryField128b) -> Self::Output {
+        PackedAlgebra(self.0.map(|lane| lane*rhs))
+    }
+
+}
+
+impl<const N: usize> AddAssign<BinaryField128b> for PackedAlgebra<N> {
+    fn add_assign(&mut self, rhs: BinaryField128b) {
+        *self = *self + rhs
+    }
+}
+
+impl<const N: usize> SubAssign<BinaryField128b> for PackedAlgebra<N> {
+    fn sub_assign(&mut self, rhs: BinaryField128b) {
+        *self = *self - rhs
+    }
+}
+
+impl<const N: usize> MulAssign<BinaryField128b> for PackedAlgebra<N> {
+    fn mul_assign(&mut self, rhs: BinaryField128b) {
+        *self = *self*rhs
+    }
+}
+
+impl<const N: usize> Sum for PackedAlgebra<N> {
+    fn sum<I: Iterator<Item = Self>>(iter: I)->Self{
+        iter.fold(Self::default(), |acc, pack| acc + pack)
+    }
+}
+
+impl<const N: usize> Product for PackedAlgebra<N> {
+    fn product<I: Iterator<Item = Self>>(iter: I)->Self{
+        iter.fold(PackedAlgebra([BinaryField128b::ONE; N]), |acc, pack| acc*pack)
+    }
+}
+
+impl<const N: usize> binius_field::PackedField for PackedAlgebra<N> {
+    type Scalar = BinaryField128b;
+
+    const LOG_WIDTH: usize = N.trailing_zeros() as usize;
+
+    fn get_checked(&self, i:usize)->Result<BinaryField128b, FieldError>{
+        self.0.get(i).copied().ok_or(FieldError::IndexOutOfRange { index: i, max: N })
+    }
+
+    fn set_checked(&mut self, i:usize, scalar:BinaryField128b)->Result<(), FieldError>{
+        let lane = self.0.get_mut(i).ok_or(FieldError::IndexOutOfRange { index: i, max: N })?;
+        *lane = scalar;
+        Ok(())
+    }
+
+    fn random(mut rng: impl RngCore)->Self{
+        PackedAlgebra(core::array::from_fn(|_| <BinaryField128b as Field>::random(&mut rng)))
+    }
+
+    fn broadcast(scalar:BinaryField128b)->Self{
+        PackedAlgebra([scalar; N])
+    }
+
+    fn from_fn(f: impl FnMut(usize) -> BinaryField128b)->Self{
+        PackedAlgebra(core::array::from_fn(f))
+    }
+
+    fn square(self)->Self{
+        PackedAlgebra(self.0.map(|lane| lane.square()))
+    }
+
+    fn invert_or_zero(self)->Self{
+        PackedAlgebra(self.0.map(|lane| lane.invert().unwrap_or(BinaryField128b::ZERO)))
+    }
+
+    fn interleave(self, other:Self, log_block_len:usize)->(Self, Self){
+        assert!(log_block_len < Self::LOG_WIDTH, "Block length must be less than the width.");
+        let block_len = 1<<log_block_len;
+
+        //The second block of each pair of blocks of self is swapped with the first block of the same pair of other.
+        let (mut lhs, mut rhs) = (self, other);
+        for start in (0..N).step_by(2*block_len){
+            lhs.0[start + block_len..start + 2*block_len].swap_with_slice(&mut rhs.0[start..start + block_len]);
+        }
+        (lhs, rhs)
+    }
+}
+
+//Lane-wise sum of two packs of any width.
+pub fn packed_tower_add<const N: usize>(lhs: &[BinaryField128b;N], rhs: &[BinaryField128b;N])->[BinaryField128b;N]
+{
+    core::array::from_fn(|i| lhs[i] + rhs[i])
 }
 
 
@@ -147,10 +324,18 @@ pub fn packed_tower_add_32(lhs: &[BinaryField128b;32], rhs: &[BinaryField128b;32
 
 
 
+//Generates the packed arithmetic of each level from the level below it. The multiplication of a level is named PackedMul{level} unless a name is given,
+//which is used for level 32 so PackedMul32 can pick between it and the fast path below.
 macro_rules! PackedTowerAlgebra {
     ($field:ty, $($level:expr),* => $( $next:expr),* ) => {
         paste!{
         $(
+            PackedTowerAlgebra!{@level $field, $level => $next, [<PackedMul $level>]}
+        )*
+        }
+    };
+    (@level $field:ty, $level:expr => $next:expr, $mul:ident) => {
+        paste!{
 
             pub fn [<PackedMulAlpha $level>](lhs:[$field; $level ])->[$field; $level]{
                 let (a0, a1) = lhs.split_at(lhs.len()>>1);
@@ -173,7 +358,7 @@ macro_rules! PackedTowerAlgebra {
                 res
             }
 
-            pub fn [<PackedMul $level>](lhs:&[$field ; $level], rhs: [<BinaryField $level b>])->[$field; $level]{
+            pub fn $mul(lhs:&[$field ; $level], rhs: [<BinaryField $level b>])->[$field; $level]{
                 let (a_0, a_1) = lhs.split_at($level>>1);
                 let (b_0, b_1) = rhs.into();
                 let a_0 = <[$field; $next]>::try_from(a_0).unwrap();
@@ -197,13 +382,71 @@ macro_rules! PackedTowerAlgebra {
                 concat_arrays![t1,z3]
 
             }
-
-        )*
         }
     };
 }
 
-PackedTowerAlgebra!{BinaryField128b, 32,16,8,4 => 16,8,4,2}
+PackedTowerAlgebra!{BinaryField128b, 16,8,4 => 8,4,2}
+PackedTowerAlgebra!{@level BinaryField128b, 32 => 16, packed_mul_tower_32}
+
+//Multiplies the F_32 element with coefficients lhs over F_128 by rhs. With GFNI this goes through packed_mul_32_linear, otherwise through the tower recursion.
+pub fn PackedMul32(lhs:&[BinaryField128b;32], rhs: BinaryField32b)->[BinaryField128b;32]{
+    #[cfg(all(target_arch = "x86_64", target_feature = "gfni"))]
+    {
+        packed_mul_32_linear(lhs, rhs)
+    }
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "gfni")))]
+    {
+        packed_mul_tower_32(lhs, rhs)
+    }
+}
+
+//Multiplication by rhs is F_2-linear in the lanes, lane i being the coefficient of the basis element 1<<i which rhs maps to (1<<i)*rhs,
+//so lane j of the product is the sum of the lanes i for which bit j of (1<<i)*rhs is set. The 32 products are computed with the packed
+//multiplication of the field crate, which uses GFNI when it is enabled, and the sums are read off tables of the subset sums of 4 lanes at a time.
+#[cfg_attr(not(all(target_arch = "x86_64", target_feature = "gfni")), allow(dead_code))]
+fn packed_mul_32_linear(lhs:&[BinaryField128b;32], rhs: BinaryField32b)->[BinaryField128b;32]{
+    use binius_field::{PackedBinaryField4x32b, PackedField};
+
+    let mut products = [0u32; 32];
+    for (i, chunk) in products.chunks_mut(4).enumerate(){
+        let packed = PackedBinaryField4x32b::from_fn(|k| BinaryField32b::new(1<<(4*i + k)))*rhs;
+        chunk.iter_mut().enumerate().for_each(|(k, product)| *product = packed.get(k).val());
+    }
+
+    //tables[g][s] is the sum of the lanes 4g + k for the bits k set in s.
+    let mut tables = [[BinaryField128b::ZERO; 16]; 8];
+    for (g, table) in tables.iter_mut().enumerate(){
+        for s in 1..16{
+            table[s] = table[s & (s - 1)] + lhs[4*g + s.trailing_zeros() as usize];
+        }
+    }
+
+    //After transposing, bit i of columns[j] is bit j of products[i], so its nibbles index the tables.
+    let columns = transpose_bits_32(products);
+    core::array::from_fn(|j| {
+        tables.iter().enumerate().fold(BinaryField128b::ZERO, |acc, (g, table)| acc + table[((columns[j]>>(4*g)) & 15) as usize])
+    })
+}
+
+//Transposes the 32x32 bit matrix with row i the bits of rows[i], by swapping off-diagonal blocks of halving sizes.
+fn transpose_bits_32(mut rows:[u32; 32])->[u32; 32]{
+    let mut width = 16;
+    let mut mask:u32 = 0x0000_FFFF;
+    while width != 0{
+        //Blocks of width rows and columns, the upper right block of each pair being swapped with the lower left one.
+        for start in (0..32).step_by(2*width){
+            for i in start..start + width{
+                let t = ((rows[i]>>width) ^ rows[i + width]) & mask;
+                rows[i] ^= t<<width;
+                rows[i + width] ^= t;
+            }
+        }
+        width >>= 1;
+        mask ^= mask<<width;
+    }
+    rows
+}
 
 
 
@@ -236,30 +479,238 @@ pub fn toweralg(){
     for i in res.iter(){
         print!("{:?}", i.val())
     }
-    println!("\n")
+    println!("\n");
+
+    //The product of two bit embeddings is the bit embedding of the product, whichever path PackedMul32 takes.
+    let expected = PackedAlgebra32::from(test).0;
+    assert_eq!(res, expected);
+    assert_eq!(packed_mul_tower_32(&b, rand), expected);
+    assert_eq!(packed_mul_32_linear(&b, rand), expected);
+}
+
+#[test]
+fn transpose_bits_32_test(){
+    let mut rng = thread_rng();
+    let rows:[u32; 32] = core::array::from_fn(|_| BinaryField32b::random(&mut rng).val());
+    let columns = transpose_bits_32(rows);
+    for (i, row) in rows.iter().enumerate(){
+        for (j, column) in columns.iter().enumerate(){
+            assert_eq!((column>>i) & 1, (row>>j) & 1);
+        }
+    }
+}
+
+#[test]
+fn packed_mul_32_paths_test(){
+    let mut rng = thread_rng();
+    for _ in 0..16{
+        let lhs:[BinaryField128b;32] = core::array::from_fn(|_| BinaryField128b::random(&mut rng));
+        let rhs = BinaryField32b::random(&mut rng);
+        assert_eq!(packed_mul_32_linear(&lhs, rhs), packed_mul_tower_32(&lhs, rhs));
+    }
 }
 
 
 //Evaluates a vector packed coeffs, i.e each BinaryField32b actually represents 32 coefficients of the based field at a time, at a random point.
-pub fn evaluate_unpacked(poly:&Vec<BinaryField32b>, basis:&Vec<BinaryField128b>)->BinaryField128b{
+//Evaluates the multilinear polynomial whose F_2 coefficients are packed 32 to a BinaryField32b, given the basis for the evaluation point in any extension F of F_2.
+pub fn evaluate_unpacked<F: ExtensionField<BinaryField1b>>(poly:&[BinaryField32b], basis:&[F])->F{
     assert_eq!(poly.len()*32, basis.len());
 
 
-    basis.par_iter().chunks(32).zip(poly.par_iter()).map(|(basis_chunk, packed_coeff)|
+    parallel_xor_sum(par_chunks!(basis, 32).zip(par_iter!(poly)).map(|(basis_chunk, packed_coeff)|
     {
-    let mut acc = BinaryField128b::ZERO;
+    let mut acc = F::ZERO;
 
-    for i in 0..32{
+    for (i, basis_elem) in basis_chunk.iter().enumerate(){
 
         if (packed_coeff.val()>>i)&1 == 1{
-            acc+=basis_chunk[i]
+            acc+=*basis_elem
         }
     }
     acc
     }
-    ).reduce(||BinaryField128b::ZERO, |acc,g| acc+g)
+    ))
+
+
+
+
+}
+
+#[test]
+fn packed_algebra_16_test(){
+    let mut rng = thread_rng();
+    let lanes:[BinaryField128b;16] = core::array::from_fn(|_| BinaryField128b::random(&mut rng));
+    let scalar = BinaryField16b::random(&mut rng);
+
+    //Lane i is the coefficient of the i-th basis element of F_16, so multiplying by scalar maps it onto the bits of that basis element times scalar.
+    let mut expected = [BinaryField128b::ZERO;16];
+    for (i, &lane) in lanes.iter().enumerate(){
+        let product = BinaryField16b::new(1<<i)*scalar;
+        for (j, expected_j) in expected.iter_mut().enumerate(){
+            if (product.val()>>j)&1 == 1{
+                *expected_j += lane;
+            }
+        }
+    }
+
+    assert_eq!(PackedAlgebra::<16>::new(lanes)*scalar, PackedAlgebra(expected));
+
+    let bits = PackedAlgebra::<16>::new(core::array::from_fn(|i| BinaryField128b::new(((scalar.val()>>i)&1) as u128)));
+    let other = BinaryField16b::random(&mut rng);
+    let product = scalar*other;
+    assert_eq!(bits*other, PackedAlgebra(core::array::from_fn(|i| BinaryField128b::new(((product.val()>>i)&1) as u128))));
+}
+
+#[test]
+fn packed_field_test(){
+    use binius_field::{packed::{get_packed_slice, iter_packed_slice, set_packed_slice}, PackedField};
+
+    let mut rng = thread_rng();
+    assert_eq!(PackedAlgebra32::LOG_WIDTH, 5);
+
+    let mut pack = PackedAlgebra32::random(&mut rng);
+    let scalar = <BinaryField128b as Field>::random(&mut rng);
+    pack.set(7, scalar);
+    assert_eq!(pack.get(7), scalar);
+    assert!(matches!(pack.get_checked(32), Err(FieldError::IndexOutOfRange { index: 32, max: 32 })));
+    assert!(pack.set_checked(32, scalar).is_err());
+
+    assert!(PackedAlgebra32::broadcast(scalar).iter().all(|lane| lane == scalar));
+
+    let square = pack.square();
+    let inverse = pack.invert_or_zero();
+    for i in 0..32{
+        assert_eq!(square.get(i), pack.get(i)*pack.get(i));
+        assert_eq!(inverse.get(i)*pack.get(i), BinaryField128b::ONE);
+    }
+    assert_eq!(PackedAlgebra32::zero().invert_or_zero(), PackedAlgebra32::zero());
+
+    let mut packs = PackedAlgebra32::pack((0..64).map(|_| <BinaryField128b as Field>::random(&mut rng)).collect());
+    assert_eq!(iter_packed_slice(&packs).collect::<Vec<_>>(), PackedAlgebra32::unpack(packs.clone()));
+    set_packed_slice(&mut packs, 40, scalar);
+    assert_eq!(get_packed_slice(&packs, 40), scalar);
+    assert_eq!(packs[1].get(8), scalar);
+}
 
+#[test]
+fn packed_field_interleave_test(){
+    use binius_field::PackedField;
+
+    let a = PackedAlgebra::<8>::from_fn(|i| BinaryField128b::new(i as u128));
+    let b = PackedAlgebra::<8>::from_fn(|i| BinaryField128b::new(8 + i as u128));
+
+    //The example from the documentation of PackedField::interleave, with LOG_WIDTH 3 and log_block_len 1.
+    let (c, d) = a.interleave(b, 1);
+    assert_eq!(c.iter().map(|lane| lane.val()).collect::<Vec<_>>(), vec![0, 1, 8, 9, 4, 5, 12, 13]);
+    assert_eq!(d.iter().map(|lane| lane.val()).collect::<Vec<_>>(), vec![2, 3, 10, 11, 6, 7, 14, 15]);
 
+    for log_block_len in 0..3{
+        let (c, d) = a.interleave(b, log_block_len);
+        assert_eq!(c.interleave(d, log_block_len), (a, b));
+    }
+}
 
+#[test]
+fn packed_algebra32_interleave_test(){
+    use binius_field::PackedField;
+
+    let a = PackedAlgebra32::from_fn(|i| BinaryField128b::new(i as u128));
+    let b = PackedAlgebra32::from_fn(|i| BinaryField128b::new(32 + i as u128));
+
+    //The documentation example carried over to width 32, blocks of 2 lanes of a and b alternating.
+    let (c, d) = a.interleave(b, 1);
+    let expected_c:Vec<u128> = (0..8).flat_map(|k| [4*k, 4*k + 1, 32 + 4*k, 33 + 4*k]).collect();
+    let expected_d:Vec<u128> = (0..8).flat_map(|k| [4*k + 2, 4*k + 3, 34 + 4*k, 35 + 4*k]).collect();
+    assert_eq!(c.iter().map(|lane| lane.val()).collect::<Vec<_>>(), expected_c);
+    assert_eq!(d.iter().map(|lane| lane.val()).collect::<Vec<_>>(), expected_d);
+
+    //With blocks of half the width the halves are swapped across the two packs.
+    let (c, d) = a.interleave(b, 4);
+    assert_eq!(c.iter().map(|lane| lane.val()).collect::<Vec<_>>(), (0..16).chain(32..48).collect::<Vec<_>>());
+    assert_eq!(d.iter().map(|lane| lane.val()).collect::<Vec<_>>(), (16..32).chain(48..64).collect::<Vec<_>>());
+}
 
-}
\ No newline at end of file
+#[cfg(test)]
+proptest::proptest!{
+    #[test]
+    fn packed_algebra32_interleave_proptest(log_block_len in 0usize..5, seed in proptest::arbitrary::any::<u64>()){
+        use binius_field::PackedField;
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let a = PackedAlgebra32::random(&mut rng);
+        let b = PackedAlgebra32::random(&mut rng);
+        let (c, d) = a.interleave(b, log_block_len);
+
+        //Lane i lies in an even block of its pair when bit log_block_len of i is clear, in which case c keeps a's lane and d takes a's lane
+        //from the odd block, otherwise c takes b's lane from the even block and d keeps b's lane.
+        let block_len = 1<<log_block_len;
+        for i in 0..32{
+            if i & block_len == 0{
+                assert_eq!((c.get(i), d.get(i)), (a.get(i), a.get(i + block_len)));
+            }
+            else{
+                assert_eq!((c.get(i), d.get(i)), (b.get(i - block_len), b.get(i)));
+            }
+        }
+        assert_eq!(c.interleave(d, log_block_len), (a, b));
+    }
+}
+
+#[test]
+#[allow(clippy::op_ref)]
+fn sub_equals_add(){
+    use binius_field::PackedField;
+
+    let mut rng = thread_rng();
+    for _ in 0..16{
+        let a = PackedAlgebra32::random(&mut rng);
+        let mut b = PackedAlgebra32::random(&mut rng);
+
+        assert_eq!(a - b, a + b);
+        assert_eq!(a - &b, a + &b);
+        assert_eq!(a - &mut b, a + &mut b);
+
+        let (mut difference, mut sum) = (a, a);
+        difference -= b;
+        sum += b;
+        assert_eq!(difference, sum);
+
+        assert_eq!(a - a, PackedAlgebra32::new([BinaryField128b::ZERO; 32]));
+    }
+}
+
+#[test]
+fn is_bit_embedding_test(){
+    let mut rng = thread_rng();
+    let val = BinaryField32b::random(&mut rng);
+
+    let mut bits = PackedAlgebra32::from(val);
+    assert!(bits.is_bit_embedding());
+
+    bits.0[7] = BinaryField128b::new(2);
+    assert!(!bits.is_bit_embedding());
+}
+
+#[test]
+fn evaluate_unpacked_test(){
+    use crate::utils::encoding::compute_fourier_bases;
+
+    let mut rng = thread_rng();
+    let poly:Vec<BinaryField32b> = (0..1<<5).map(|_| BinaryField32b::new(rng.next_u32())).collect();
+    let point:Vec<BinaryField128b> = (0..10).map(|_| <BinaryField128b as Field>::random(&mut rng)).collect();
+    let basis = compute_fourier_bases(&point);
+
+    let mut expected = BinaryField128b::ZERO;
+    for (i, basis_elem) in basis.iter().enumerate(){
+        if (poly[i/32].val()>>(i%32))&1 == 1{
+            expected += *basis_elem;
+        }
+    }
+    assert_eq!(evaluate_unpacked(&poly, &basis), expected);
+
+    //A basis over a smaller field is summed in that field.
+    let small_basis:Vec<BinaryField32b> = (0..poly.len()*32).map(|_| BinaryField32b::new(rng.next_u32())).collect();
+    let lifted:Vec<BinaryField128b> = small_basis.iter().map(|&b| b.into()).collect();
+    assert_eq!(BinaryField128b::from(evaluate_unpacked(&poly, &small_basis)), evaluate_unpacked(&poly, &lifted));
+}
@@ -0,0 +1,108 @@
+use binius_field::{BinaryField128b, BinaryField32b};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+
+//Field elements are serialized as the integers they are stored as, i.e their coordinates in the tower basis, so the encoding doesn't depend on
+//how the field implementation represents elements internally. Vectors of elements are serialized as vectors of integers.
+pub trait AsInteger: Sized{
+    type Repr: Serialize + DeserializeOwned;
+
+    fn to_repr(&self)->Self::Repr;
+    fn from_repr(repr: Self::Repr)->Self;
+}
+
+impl AsInteger for BinaryField32b{
+    type Repr = u32;
+
+    fn to_repr(&self)->u32{
+        self.val()
+    }
+    fn from_repr(repr: u32)->Self{
+        BinaryField32b::new(repr)
+    }
+}
+
+impl AsInteger for BinaryField128b{
+    type Repr = u128;
+
+    fn to_repr(&self)->u128{
+        self.val()
+    }
+    fn from_repr(repr: u128)->Self{
+        BinaryField128b::new(repr)
+    }
+}
+
+impl<T:AsInteger> AsInteger for Vec<T>{
+    type Repr = Vec<T::Repr>;
+
+    fn to_repr(&self)->Self::Repr{
+        self.iter().map(|t| t.to_repr()).collect()
+    }
+    fn from_repr(repr: Self::Repr)->Self{
+        repr.into_iter().map(T::from_repr).collect()
+    }
+}
+
+//To be used as #[serde(with = "as_integer")] on fields holding field elements.
+pub mod as_integer{
+    use super::*;
+
+    pub fn serialize<T:AsInteger, S:Serializer>(value: &T, serializer: S)->Result<S::Ok, S::Error>{
+        value.to_repr().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T:AsInteger, D:Deserializer<'de>>(deserializer: D)->Result<T, D::Error>{
+        T::Repr::deserialize(deserializer).map(T::from_repr)
+    }
+}
+
+#[test]
+fn eval_proof_roundtrip_test(){
+    use rand::thread_rng;
+    use crate::{prover::prove_with_fold_eval, test::{random_opening, Opening}, utils::{Commitment, EvalProof}, verifier::verify_with_fold_eval};
+
+    let mut rng = thread_rng();
+    let l = 6;
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+    let queries = vec![0, 3, encoded_poly.cols - 1];
+    let proof = prove_with_fold_eval(&poly, &encoded_poly, &merkle_tree, &point, &queries, 2).unwrap();
+
+    let commitment_bytes = bincode::serialize(&commitment).unwrap();
+    let proof_bytes = bincode::serialize(&proof).unwrap();
+    let commitment:Commitment = bincode::deserialize(&commitment_bytes).unwrap();
+    let deserialized:EvalProof = bincode::deserialize(&proof_bytes).unwrap();
+
+    assert_eq!(deserialized.folded_poly, proof.folded_poly);
+    assert_eq!(deserialized.queried_columns, proof.queried_columns);
+    assert_eq!(deserialized.fold_eval, proof.fold_eval);
+    assert_eq!(verify_with_fold_eval(commitment, eval, deserialized, point, queries, &[0, 1], &ntt), Ok(()));
+
+    //Elements are written as their integer values, little-endian by bincode, right after the length of the folded polynomial.
+    assert_eq!(proof_bytes[8..24], proof.folded_poly[0].val().to_le_bytes());
+
+    //Deserializing checks the shape like Commitment::from_fixed_bytes, so a tampered commitment is rejected before it reaches verify.
+    let mut tampered = commitment_bytes.clone();
+    let rows_offset = commitment_bytes.len() - 16;
+    tampered[rows_offset..rows_offset + 8].copy_from_slice(&255u64.to_le_bytes());
+    assert!(bincode::deserialize::<Commitment>(&tampered).unwrap_err().to_string().contains("exceeds the supported"));
+    let mut no_parity = commitment_bytes;
+    let rate_offset = no_parity.len() - 8;
+    no_parity[rate_offset..].copy_from_slice(&0u64.to_le_bytes());
+    assert!(bincode::deserialize::<Commitment>(&no_parity).is_err());
+}
+
+#[test]
+fn size_in_bytes_test(){
+    use rand::thread_rng;
+    use crate::{prover::prove_with_queries, test::{random_opening, Opening}};
+
+    let Opening{ poly, commitment, merkle_tree, encoded_poly, point, .. } = random_opening(10, &mut thread_rng());
+    let queries = vec![0, 5, 9, encoded_poly.cols - 1];
+    let proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+    assert_eq!(proof.num_queries(), queries.len());
+
+    //Bincode prefixes every vector, hash included, with an 8 byte length and writes a byte for each of the three empty options.
+    let q = queries.len();
+    let framing = 8 + (8 + 8*q) + (8 + 8*q + 8*q*commitment.cols) + 3;
+    assert_eq!(bincode::serialize(&proof).unwrap().len(), proof.size_in_bytes() + framing);
+}
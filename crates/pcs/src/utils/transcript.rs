@@ -0,0 +1,156 @@
+use binius_field::BinaryField128b;
+use sha3::{Digest, Keccak256};
+
+use crate::{utils::merkle::Hash, verifier::VerifyError};
+
+//Fiat-Shamir transcript over Keccak256. Everything observed is absorbed into the running hash state and challenges are squeezed out of it,
+//so prover and verifier draw the same challenges as long as they observe the same messages in the same order.
+#[derive(Clone, Default)]
+pub struct Transcript{
+    state: Keccak256
+}
+
+impl Transcript{
+    pub fn new()->Transcript{
+        Transcript::default()
+    }
+
+    pub fn observe(
+        &mut self,
+        bytes: &[u8]
+    ){
+        self.state.update(bytes);
+    }
+
+    //Draws count query indices in 0..max. Each index is read off a digest of the current state, which is then absorbed back so the next one differs.
+    //The reduction mod max is only unbiased when max is a power of two, which is the case for the number of committed columns.
+    pub fn challenge_indices(
+        &mut self,
+        count: usize,
+        max: usize
+    )->Vec<usize>{
+        assert!(max > 0, "Cannot draw queries from an empty range.");
+
+        (0..count).map(|_|{
+            let digest = self.state.clone().finalize();
+            self.state.update(digest);
+
+            (u64::from_le_bytes(digest[..8].try_into().unwrap()) % max as u64) as usize
+        }).collect()
+    }
+
+    //Same as challenge_indices, but repeated indices are drawn again until count distinct ones are found, in the order they were first drawn.
+    //There are only max distinct indices, so asking for more is an error rather than a loop that never ends.
+    pub fn draw_distinct_queries(
+        &mut self,
+        count: usize,
+        max: usize
+    )->Result<Vec<usize>, VerifyError>{
+        if count > max{
+            return Err(VerifyError::TooManyQueries { requested: count, available: max });
+        }
+
+        let mut drawn = vec![false; max];
+        let mut queries = Vec::with_capacity(count);
+        while queries.len() < count{
+            let query = self.challenge_indices(1, max)[0];
+            if !drawn[query]{
+                drawn[query] = true;
+                queries.push(query);
+            }
+        }
+        Ok(queries)
+    }
+
+    //Draws a BinaryField128b from a digest of the current state, which is then absorbed back as in challenge_indices.
+    pub fn challenge_field(
+        &mut self
+    )->BinaryField128b{
+        let digest = self.state.clone().finalize();
+        self.state.update(digest);
+
+        BinaryField128b::new(u128::from_le_bytes(digest[..16].try_into().unwrap()))
+    }
+
+    //Returns the queries challenge_indices would produce next without advancing the transcript, to compare the prover's and verifier's derivations while debugging.
+    pub fn peek_queries(
+        &self,
+        count: usize,
+        max: usize
+    )->Vec<usize>{
+        self.clone().challenge_indices(count, max)
+    }
+}
+
+//The queries of a non-interactive proof, drawn from a transcript that has observed the root and rate of the commitment, the point, the claimed evaluation and then the folded polynomial,
+//each element as the little-endian bytes of its integer value. Observing the statement binds the queries to it, so the point and evaluation can't be picked once the queries are known,
+//and a commitment claiming another rate than the rows were encoded at draws other queries than the proof opens.
+//Prover and verifier both call this, so they agree on the queries as long as the proof carries the folded polynomial the prover drew them from.
+//The count queries are distinct, see draw_distinct_queries, so a proof opens exactly count columns, and are returned in increasing order, the order they are opened in.
+pub fn fiat_shamir_queries(root: &Hash, log_inv_rate: usize, point: &[BinaryField128b], eval: BinaryField128b, folded_poly: &[BinaryField128b], count: usize, max: usize)->Result<Vec<usize>, VerifyError>{
+    let mut transcript = Transcript::new();
+    transcript.observe(&root.0);
+    transcript.observe(&(log_inv_rate as u64).to_le_bytes());
+    point.iter().for_each(|val| transcript.observe(&val.val().to_le_bytes()));
+    transcript.observe(&eval.val().to_le_bytes());
+    folded_poly.iter().for_each(|val| transcript.observe(&val.val().to_le_bytes()));
+
+    let mut queries = transcript.draw_distinct_queries(count, max)?;
+    queries.sort_unstable();
+    Ok(queries)
+}
+
+//The challenge gamma the mask rows of a hiding commitment are folded with, drawn after the prover has sent the evaluation of the mask at the point,
+//so a wrong mask_eval only goes unnoticed for a single value of gamma.
+pub fn hiding_challenge(root: &Hash, point: &[BinaryField128b], mask_eval: BinaryField128b)->BinaryField128b{
+    let mut transcript = Transcript::new();
+    transcript.observe(&root.0);
+    point.iter().for_each(|val| transcript.observe(&val.val().to_le_bytes()));
+    transcript.observe(&mask_eval.val().to_le_bytes());
+
+    transcript.challenge_field()
+}
+
+//The queries of a hiding proof, drawn like fiat_shamir_queries from the rate*message_cols codeword columns, except that columns in the systematic part,
+//the first message_cols, are drawn again as opening them would reveal the coefficients.
+pub fn hiding_queries(root: &Hash, folded_poly: &[BinaryField128b], count: usize, message_cols: usize, rate: usize)->Vec<usize>{
+    let mut transcript = Transcript::new();
+    transcript.observe(&root.0);
+    folded_poly.iter().for_each(|val| transcript.observe(&val.val().to_le_bytes()));
+
+    let mut queries = Vec::with_capacity(count);
+    while queries.len() < count{
+        let query = transcript.challenge_indices(1, rate*message_cols)[0];
+        if query >= message_cols{
+            queries.push(query);
+        }
+    }
+    queries
+}
+
+//Sorts the queries and drops repeats, the form prove and verify open and check the columns in. A column opened twice adds nothing to soundness,
+//it only makes the proof bigger, and both sides agree on the opened columns as long as they start from the same queries.
+pub fn canonical_queries(queries: &[usize])->Vec<usize>{
+    let mut queries = queries.to_vec();
+    queries.sort_unstable();
+    queries.dedup();
+    queries
+}
+
+#[test]
+fn peek_queries_test(){
+    let mut transcript = Transcript::new();
+    transcript.observe(b"root");
+
+    let peeked = transcript.peek_queries(20, 1<<10);
+    assert_eq!(transcript.peek_queries(20, 1<<10), peeked);
+    assert_eq!(transcript.challenge_indices(20, 1<<10), peeked);
+    assert!(peeked.iter().all(|&query| query < 1<<10));
+
+    //Drawing advances the state, and prover and verifier transcripts observing the same messages agree.
+    let mut verifier_transcript = Transcript::new();
+    verifier_transcript.observe(b"root");
+    verifier_transcript.challenge_indices(20, 1<<10);
+    assert_ne!(transcript.peek_queries(20, 1<<10), peeked);
+    assert_eq!(transcript.challenge_indices(5, 1<<10), verifier_transcript.challenge_indices(5, 1<<10));
+}
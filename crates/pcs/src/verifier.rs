@@ -1,45 +1,862 @@
-
-use crate::utils::{encoding::{compute_fourier_bases, encode_extension}, merkle::{hash_field, verify_merkle_path}, ntt::AdditiveNTT, packed_arithmetic::PackedAlgebra32, Commitment, EvalProof};
+use crate::utils::{encoding::{compute_fourier_bases, compute_fourier_bases_into, encode_extension_at, encode_extension_into, validate_rate, EncodingParams, PACKING_DEGREE}, merkle::{hash_field, merkle_root_from_path, verify_merkle_path, verify_multi_path, Hash, MerkleError}, ntt::AdditiveNTT, packed_arithmetic::PackedAlgebra32, parallel_xor_sum, transcript::{canonical_queries, fiat_shamir_queries, hiding_challenge, hiding_queries}, hiding_basis, BatchEvalProof, ChallengeTranscript, Commitment, DiffProof, EvalProof, HeterogeneousCommitment, HidingEvalProof, MultiEvalProof, HIDING_ROWS, NUM_QUERIES, XOR_SUM_MIN_LEN};
 use binius_field::{BinaryField128b, BinaryField32b, ExtensionField, Field};
-use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+use subtle::ConstantTimeEq;
+
+//Error returned when a proof is rejected, queries are reported by the index of the column they open.
+#[derive(Clone, thiserror::Error, Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    #[error("rows of 2^{cols} columns can't be encoded at rate 2^-{log_inv_rate}")]
+    UnsupportedRate { log_inv_rate: usize, cols: usize },
+    #[error("commitment was made at rate 2^-{got}, expected 2^-{expected}")]
+    RateMismatch { expected: usize, got: usize },
+    #[error("query {query} is out of range 0..{max}")]
+    QueryOutOfRange { query: usize, max: usize },
+    #[error("{requested} distinct queries requested but only {available} columns are committed")]
+    TooManyQueries { requested: usize, available: usize },
+    #[error("recorded queries diverge from the re-derived ones at position {position}")]
+    QueryChallengeMismatch { position: usize },
+    #[error("recorded fold scalars diverge from the re-derived ones at position {position}")]
+    FoldScalarMismatch { position: usize },
+    #[error("merkle path of column {query} does not lead to the commitment")]
+    MerklePathMismatch { query: usize },
+    #[error("combined merkle opening does not lead to the commitment")]
+    MultiPathMismatch,
+    #[error("included leaf hash of column {query} does not match the opened column")]
+    LeafHashMismatch { query: usize },
+    #[error("opened column {query} does not fold to the re-encoded folded polynomial")]
+    ColumnCheckFailed { query: usize },
+    #[error("folded polynomial does not evaluate to the claimed evaluation")]
+    EvaluationMismatch,
+    #[error("sampled chunk {chunk} does not match its claimed sum")]
+    ChunkSumMismatch { chunk: usize },
+    #[error("linear relation does not match the claimed sum")]
+    LinearRelationMismatch,
+    #[error("commitments to 2^{old_rows} x 2^{old_cols} and 2^{new_rows} x 2^{new_cols} coefficient matrices can't differ in a single coefficient")]
+    ShapeMismatch { old_rows: usize, old_cols: usize, new_rows: usize, new_cols: usize },
+    #[error("coefficient {index} is out of range for a polynomial of {len} coefficients")]
+    CoefficientOutOfRange { index: usize, len: usize },
+    #[error("the old and new openings of column {col} have different siblings, so other columns changed")]
+    DiffPathMismatch { col: usize },
+    #[error("row {row} of the opened column changed, but the changed coefficient is in row {changed_row}")]
+    UnchangedRowMismatch { row: usize, changed_row: usize },
+    #[error("point has {got} variables, expected {expected}")]
+    PointLengthMismatch { expected: usize, got: usize },
+    #[error("malformed proof: {reason}")]
+    MalformedProof { reason: String },
+    #[error("opening at point {point} failed: {error}")]
+    PointCheckFailed { point: usize, error: Box<VerifyError> },
+}
+
+//Checks the folded polynomial can be re-encoded at the rate the commitment was made at, and every query indexes one of the 2^commit.cols committed columns.
+pub fn check_queries(commit: &Commitment, queries: &[usize])->Result<(), VerifyError>{
+    check_rate(commit)?;
+    check_query_range(commit.cols, queries)
+}
+
+//Checks the rate recorded in the commitment is one the code supports, see validate_rate. The codeword lengths and query bounds are then derived from commit.log_inv_rate,
+//where verify_hiding opens parity columns, a commitment claiming another rate than the one its rows were encoded at gets merkle paths of the wrong length.
+pub fn check_rate(commit: &Commitment)->Result<(), VerifyError>{
+    validate_rate(commit.log_inv_rate, commit.cols).map_err(|_| VerifyError::UnsupportedRate { log_inv_rate: commit.log_inv_rate, cols: commit.cols })
+}
+
+//Checks requested distinct queries can be drawn from the 2^commit.cols committed columns. With fewer columns than queries some would have to repeat,
+//and the soundness of the query phase, which counts distinct opened columns, no longer holds.
+pub fn check_query_count(commit: &Commitment, requested: usize)->Result<(), VerifyError>{
+    let available = 1<<commit.cols;
+
+    if requested > available{
+        return Err(VerifyError::TooManyQueries { requested, available });
+    }
+    Ok(())
+}
+
+//Heuristic number of queried columns for target_bits of soundness from a code of rate 2^-log_inv_rate and relative distance code_distance.
+//It is not a theorem applied with its conditions checked. It assumes every query independently catches a cheating prover with probability code_distance/3,
+//the unique decoding radius used by Ligero style analyses of interleaved Reed-Solomon codes, and ignores the proximity gap error, which depends on the field size.
+//The least q with (1 - code_distance/3)^q at most 2^-target_bits is then q = ceil(target_bits/-log2(1 - code_distance/3)).
+//It also assumes the queries range over the whole codeword, while verify only opens the message columns, so it says nothing about the soundness of verify.
+//A Reed-Solomon code, as produced by encode, meets the Singleton bound code_distance = 1 - 2^-log_inv_rate.
+pub fn num_queries_for_soundness(log_inv_rate: usize, code_distance: f64, target_bits: usize)->usize{
+    assert!(code_distance > 0.0 && code_distance <= 1.0 - 0.5f64.powi(log_inv_rate as i32), "Distance {code_distance} is not possible at rate 2^-{log_inv_rate}.");
+
+    let bits_per_query = -(1.0 - code_distance/3.0).log2();
+    (target_bits as f64/bits_per_query).ceil() as usize
+}
+
+fn check_query_range(log_cols: usize, queries: &[usize])->Result<(), VerifyError>{
+    let max = 1<<log_cols;
 
+    match queries.iter().find(|&&query| query >= max) {
+        Some(&query) => Err(VerifyError::QueryOutOfRange { query, max }),
+        None => Ok(())
+    }
+}
+
+//Splits point into its row and column parts, the column part being its last commit.col_point_len() variables, see Commitment::col_point_len.
+//A point too short to hold the column part is rejected instead of underflowing the split.
+pub fn split_point<'a>(commit: &Commitment, point: &'a [BinaryField128b])->Result<(&'a [BinaryField128b], &'a [BinaryField128b]), VerifyError>{
+    match point.len().checked_sub(commit.col_point_len()) {
+        Some(row_len) => Ok(point.split_at(row_len)),
+        None => Err(VerifyError::PointLengthMismatch { expected: commit.polynomial_log_len(), got: point.len() })
+    }
+}
+
+//The scalars the rows of a proof against commit are folded with, the eq table of the row part of the point.
+pub fn derive_fold_scalars(commit: &Commitment, point: &[BinaryField128b])->Result<Vec<BinaryField128b>, VerifyError>{
+    Ok(compute_fourier_bases(split_point(commit, point)?.0))
+}
+
+//The challenges verify uses for proof, the queries re-derived from the transcript by fiat_shamir_queries and the fold scalars of derive_fold_scalars.
+pub fn derive_challenges(commit: &Commitment, eval: BinaryField128b, point: &[BinaryField128b], proof: &EvalProof)->Result<ChallengeTranscript, VerifyError>{
+    Ok(ChallengeTranscript{
+        queries: fiat_shamir_queries(&commit.commit, commit.log_inv_rate, point, eval, &proof.folded_poly, NUM_QUERIES, 1<<commit.cols)?,
+        fold_scalars: derive_fold_scalars(commit, point)?
+    })
+}
+
+//Checks the challenges recorded by the prover are the ones the verifier re-derives, returning the first position they diverge at otherwise.
+pub fn check_recorded_challenges(commit: &Commitment, eval: BinaryField128b, point: &[BinaryField128b], proof: &EvalProof, recorded: &ChallengeTranscript)->Result<(), VerifyError>{
+    compare_challenges(recorded, &derive_challenges(commit, eval, point, proof)?)
+}
+
+fn compare_challenges(recorded: &ChallengeTranscript, derived: &ChallengeTranscript)->Result<(), VerifyError>{
+    if let Some(position) = first_divergence(&recorded.queries, &derived.queries){
+        return Err(VerifyError::QueryChallengeMismatch { position });
+    }
+    match first_divergence(&recorded.fold_scalars, &derived.fold_scalars) {
+        Some(position) => Err(VerifyError::FoldScalarMismatch { position }),
+        None => Ok(())
+    }
+}
+
+//A shorter sequence diverges where it ends.
+fn first_divergence<T:PartialEq>(recorded: &[T], derived: &[T])->Option<usize>{
+    recorded.iter().zip(derived).position(|(a, b)| a != b)
+    .or_else(|| (recorded.len() != derived.len()).then(|| recorded.len().min(derived.len())))
+}
+
+//Verifies a proof made with prove, re-deriving the queries it opens from the root and the folded polynomial it carries.
+//Commitments to fewer than NUM_QUERIES columns are rejected with TooManyQueries, see check_query_count.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
 pub fn verify(
+    commit: Commitment,
+    eval: BinaryField128b,
+    proof:EvalProof,
+    point: Vec<BinaryField128b>,
+    ntt: &AdditiveNTT
+)->Result<(), VerifyError>{
+    check_query_count(&commit, NUM_QUERIES)?;
+    let queries = fiat_shamir_queries(&commit.commit, commit.log_inv_rate, &point, eval, &proof.folded_poly, NUM_QUERIES, 1<<commit.cols)?;
+
+    verify_with_queries(commit, eval, proof, point, queries, ntt)
+}
+
+//Verifies a proof made with prove_with_queries at the given queries.
+pub fn verify_with_queries(
+    commit: Commitment,
+    eval: BinaryField128b,
+    proof:EvalProof,
+    point: Vec<BinaryField128b>,
+    queries: Vec<usize>,
+    ntt: &AdditiveNTT
+)->Result<(), VerifyError>{
+    verify_with_leaf_fn(commit, eval, proof, point, queries, ntt, |_, column| hash_field(column))
+}
+
+//Verifies a proof for a commitment made with commit_with_leaf_fn, leaf_fn has to be the function the leaves were computed with.
+pub fn verify_with_leaf_fn(
+    commit: Commitment,
+    eval: BinaryField128b,
+    proof:EvalProof,
+    point: Vec<BinaryField128b>,
+    queries: Vec<usize>,
+    ntt: &AdditiveNTT,
+    leaf_fn: impl Fn(usize, &[BinaryField32b])->Hash + Sync
+)->Result<(), VerifyError>{
+
+    verify_columns(&commit, &proof, &point, &queries, ntt, leaf_fn)?;
+
+    //verify_columns has checked the point has the committed number of variables.
+    traced!("eval-check", check_evaluation(eval, proof.claimed_evaluation(&point, PACKING_DEGREE, commit.cols)))
+}
+
+//Same as verify, but eval is checked against the fold_eval carried by the proof, see prove_with_fold_eval. Instead of the full dot product of the folded
+//polynomial with the eq table, the chunk sums are checked to add up to eval and only the chunks in sampled_chunks are recomputed.
+//This gives up soundness for less verifier work, a prover lying about a single chunk sum goes unnoticed unless that chunk is sampled,
+//so with s distinct uniformly random samples out of k chunks a wrong evaluation is only caught with probability s/k instead of always.
+pub fn verify_with_fold_eval(
+    commit: Commitment,
+    eval: BinaryField128b,
+    proof:EvalProof,
+    point: Vec<BinaryField128b>,
+    queries: Vec<usize>,
+    sampled_chunks: &[usize],
+    ntt: &AdditiveNTT
+)->Result<(), VerifyError>{
+
+    verify_columns(&commit, &proof, &point, &queries, ntt, |_, column| hash_field(column))?;
+
+    traced!("eval-check", {
+        let fold_eval = proof.fold_eval.as_ref().ok_or_else(|| malformed("proof does not carry a fold evaluation".to_string()))?;
+        check_evaluation(eval, fold_eval.eval)?;
+        check_evaluation(eval, fold_eval.chunk_sums.iter().fold(BinaryField128b::ZERO, |acc, sum| acc + *sum))?;
+
+        let chunks = fold_eval.chunk_sums.len();
+        if !chunks.is_power_of_two() || chunks > proof.folded_poly.len(){
+            return Err(malformed(format!("invalid number of chunks {chunks}")));
+        }
+
+        //The leading variables of the column part of the point select the chunk, the others the position inside it.
+        let log_chunks = chunks.trailing_zeros() as usize;
+        let (chunk_point, offset_point) = split_point(&commit, &point)?.1.split_at(log_chunks);
+        let mut offset_scalars = vec![BinaryField128b::ZERO; 1<<offset_point.len()];
+        compute_fourier_bases_into(offset_point, &mut offset_scalars);
+
+        for &chunk in sampled_chunks{
+            if chunk >= chunks{
+                return Err(VerifyError::QueryOutOfRange { query: chunk, max: chunks });
+            }
+
+            let chunk_scalar = chunk_point.iter().enumerate().fold(BinaryField128b::ONE, |acc, (i, r)|
+                if (chunk>>(log_chunks - 1 - i))&1 == 1 {acc * *r} else {acc * (BinaryField128b::ONE - *r)}
+            );
+            let vals = &proof.folded_poly[chunk*offset_scalars.len()..(chunk + 1)*offset_scalars.len()];
+
+            if fold_eval.chunk_sums[chunk] != chunk_scalar*linear_combination(&offset_scalars, vals){
+                return Err(VerifyError::ChunkSumMismatch { chunk });
+            }
+        }
+        Ok(())
+    })
+}
+
+fn malformed(reason: String)->VerifyError{
+    VerifyError::MalformedProof { reason }
+}
+
+fn check_evaluation(eval: BinaryField128b, computed: BinaryField128b)->Result<(), VerifyError>{
+    if eval != computed{
+        return Err(VerifyError::EvaluationMismatch);
+    }
+    Ok(())
+}
+
+//Checks the point and the sizes of everything in the proof match a rows x 2^log_cols coefficient matrix opened at queries, so nothing is indexed out of bounds later.
+fn check_proof_shape(proof: &EvalProof, point_len: usize, expected_point_len: usize, rows: usize, log_cols: usize, queries: &[usize])->Result<(), VerifyError>{
+
+    check_opening_shape(&proof.folded_poly, &proof.queried_columns, point_len, expected_point_len, rows, log_cols, queries)?;
+
+    check_merkle_paths(&proof.merkle_paths, log_cols, queries)?;
+    if proof.leaf_hashes.as_ref().is_some_and(|leaf_hashes| leaf_hashes.len() != queries.len()){
+        return Err(malformed(format!("expected {} leaf hashes", queries.len())));
+    }
+    Ok(())
+}
+
+//Checks there is a path per query and every one climbs from a leaf to the root of the tree over the 2^log_cols committed columns.
+fn check_merkle_paths(merkle_paths: &[Vec<Hash>], log_cols: usize, queries: &[usize])->Result<(), VerifyError>{
+    if merkle_paths.len() != queries.len(){
+        return Err(malformed(format!("expected {} merkle paths", queries.len())));
+    }
+    if let Some(i) = merkle_paths.iter().position(|path| path.len() != log_cols){
+        return Err(malformed(format!("merkle path of column {} has length {}, expected {log_cols}", queries[i], merkle_paths[i].len())));
+    }
+    Ok(())
+}
+
+//The checks of check_proof_shape shared by every kind of proof, on the point, the folded polynomial and the opened columns.
+fn check_opening_shape(
+    folded_poly: &[BinaryField128b],
+    queried_columns: &[Vec<BinaryField32b>],
+    point_len: usize,
+    expected_point_len: usize,
+    rows: usize,
+    log_cols: usize,
+    queries: &[usize]
+)->Result<(), VerifyError>{
+
+    if point_len != expected_point_len{
+        return Err(VerifyError::PointLengthMismatch { expected: expected_point_len, got: point_len });
+    }
+    if folded_poly.len() != 32<<log_cols{
+        return Err(malformed(format!("folded polynomial has length {}, expected {}", folded_poly.len(), 32<<log_cols)));
+    }
+    if queried_columns.len() != queries.len(){
+        return Err(malformed(format!("expected {} opened columns", queries.len())));
+    }
+    if let Some(i) = queried_columns.iter().position(|column| column.len() != rows){
+        return Err(malformed(format!("opened column {} has length {}, expected {rows}", queries[i], queried_columns[i].len())));
+    }
+    Ok(())
+}
+
+//Checks the queried columns against the commitment and the re-encoded folded polynomial, everything but the final evaluation check.
+fn verify_columns(
+    commit: &Commitment,
+    proof: &EvalProof,
+    point: &[BinaryField128b],
+    queries: &[usize],
+    ntt: &AdditiveNTT,
+    leaf_fn: impl Fn(usize, &[BinaryField32b])->Hash + Sync
+)->Result<(), VerifyError>{
+
+    //The proof opens every requested column once, in increasing order.
+    let queries = &canonical_queries(queries);
+    check_queries(commit, queries)?;
+    check_proof_shape(proof, point.len(), commit.polynomial_log_len(), 1<<commit.rows, commit.cols, queries)?;
+
+    let scalars = derive_fold_scalars(commit, point)?;
+    check_columns(&commit.commit, proof, &scalars, queries, ntt, commit.encoding_params(), leaf_fn)
+}
+
+//Checks every opened column against the root and that folding it with scalars gives the entry of the folded polynomial re-encoded with params at its query.
+fn check_columns(
+    root: &Hash,
+    proof: &EvalProof,
+    scalars: &Vec<BinaryField128b>,
+    queries: &[usize],
+    ntt: &AdditiveNTT,
+    params: EncodingParams,
+    leaf_fn: impl Fn(usize, &[BinaryField32b])->Hash + Sync
+)->Result<(), VerifyError>{
+
+    let encoded_columns = traced!("re-encode", encode_extension_at(&proof.folded_poly, queries, ntt, params));
+
+    //The queries are checked independently of each other, so in parallel, stopping at the first one that fails.
+    traced!("query-check", par_iter!(queries).enumerate().try_for_each(|(i, &query)|{
+        let leaf_hash = leaf_fn(query, &proof.queried_columns[i]);
+        if proof.leaf_hashes.as_ref().is_some_and(|leaf_hashes| leaf_hashes[i] != leaf_hash){
+            return Err(VerifyError::LeafHashMismatch { query });
+        }
+
+        check_query(root, scalars, encoded_columns[i], query, &proof.queried_columns[i], leaf_hash, &proof.merkle_paths[i])
+    }))
+}
+
+//Checks a single opened column against commit the way verify checks each of its queries, e.g to verify the queries one at a time as they arrive.
+//scalars is the eq table of the row part of the point, as given by derive_fold_scalars, and encoded_column the entry of the re-encoded
+//folded polynomial at query, as given by eval_folded_at_column. The column has to be a leaf of a commitment made with commit, i.e hashed with hash_field.
+//The evaluation itself is not checked, a proof is only accepted once every query passes and its folded polynomial evaluates to the claim.
+pub fn verify_query(
+    commit: &Commitment,
+    scalars: &[BinaryField128b],
+    encoded_column: PackedAlgebra32,
+    query: usize,
+    column: &[BinaryField32b],
+    path: &[Hash]
+)->Result<(), VerifyError>{
+
+    check_queries(commit, &[query])?;
+    if column.len() != 1<<commit.rows{
+        return Err(malformed(format!("opened column {query} has length {}, expected {}", column.len(), 1<<commit.rows)));
+    }
+    if scalars.len() != column.len(){
+        return Err(malformed(format!("{} fold scalars given for a column of length {}", scalars.len(), column.len())));
+    }
+    if path.len() != commit.cols{
+        return Err(malformed(format!("merkle path of column {query} has length {}, expected {}", path.len(), commit.cols)));
+    }
+
+    check_query(&commit.commit, scalars, encoded_column, query, column, hash_field(column), path)
+}
+
+//The checks of a single query once the shapes are known to match, the column's leaf against the root and its fold against the re-encoded folded polynomial.
+fn check_query(
+    root: &Hash,
+    scalars: &[BinaryField128b],
+    encoded_column: PackedAlgebra32,
+    query: usize,
+    column: &[BinaryField32b],
+    leaf_hash: Hash,
+    path: &[Hash]
+)->Result<(), VerifyError>{
+    verify_merkle_path(root, leaf_hash, query, path)?;
+    check_column_fold(scalars, encoded_column, query, column)
+}
+
+fn check_column_fold(scalars: &[BinaryField128b], encoded_column: PackedAlgebra32, query: usize, column: &[BinaryField32b])->Result<(), VerifyError>{
+    if unpacked_linear_combination(scalars, column) != encoded_column{
+        return Err(VerifyError::ColumnCheckFailed { query });
+    }
+    Ok(())
+}
+
+//Checks that folding every opened column with scalars gives the entry of the folded polynomial re-encoded with params at its query.
+fn check_column_folds(
+    folded_poly: &[BinaryField128b],
+    queried_columns: &[Vec<BinaryField32b>],
+    scalars: &Vec<BinaryField128b>,
+    queries: &[usize],
+    ntt: &AdditiveNTT,
+    params: EncodingParams
+)->Result<(), VerifyError>{
+    let encoded_columns = traced!("re-encode", encode_extension_at(folded_poly, queries, ntt, params));
+
+    traced!("column-check", par_iter!(queries).enumerate().try_for_each(|(i, &query)|
+        check_column_fold(scalars, encoded_columns[i], query, &queried_columns[i])
+    ))
+}
+
+//Verifies a proof made with prove_multi, i.e that evals[i] is the evaluation at points[i] of one and the same committed polynomial.
+//The shared queried columns are checked against the root once, then every point's folded polynomial has to agree with those very columns
+//and evaluate to its claim. A failure at one of the points is reported as PointCheckFailed with its index.
+//As in verify_with_queries, the proof is checked against canonical_queries(queries).
+pub fn verify_multi_consistency(
+    commit: Commitment,
+    evals: &[BinaryField128b],
+    proof: MultiEvalProof,
+    points: &[Vec<BinaryField128b>],
+    queries: Vec<usize>,
+    ntt: &AdditiveNTT
+)->Result<(), VerifyError>{
+
+    let queries = canonical_queries(&queries);
+    check_queries(&commit, &queries)?;
+    if evals.len() != points.len() || proof.folded_polys.len() != points.len(){
+        return Err(malformed(format!("expected {} evaluations and folded polynomials", points.len())));
+    }
+    check_merkle_paths(&proof.merkle_paths, commit.cols, &queries)?;
+
+    traced!("merkle-check", for (i, &query) in queries.iter().enumerate(){
+        let column = proof.queried_columns.get(i).ok_or_else(|| malformed(format!("expected {} opened columns", queries.len())))?;
+        verify_merkle_path(&commit.commit, hash_field(column), query, &proof.merkle_paths[i])?;
+    });
+
+    for (i, ((point, folded_poly), &eval)) in points.iter().zip(&proof.folded_polys).zip(evals).enumerate(){
+        let check_point = ||{
+            check_opening_shape(folded_poly, &proof.queried_columns, point.len(), commit.polynomial_log_len(), 1<<commit.rows, commit.cols, &queries)?;
+
+            let scalars = derive_fold_scalars(&commit, point)?;
+            check_column_folds(folded_poly, &proof.queried_columns, &scalars, &queries, ntt, commit.encoding_params())?;
+
+            let scalars = compute_fourier_bases(split_point(&commit, point)?.1);
+            check_evaluation(eval, linear_combination(&scalars, folded_poly))
+        };
+
+        check_point().map_err(|error| VerifyError::PointCheckFailed { point: i, error: Box::new(error) })?;
+    }
+    Ok(())
+}
+
+//Verifies a proof made with prove_hiding for a commitment made with commit_hiding. The opened columns are checked as in verify, against the re-encoded folded polynomial
+//with the scalars extended by gamma times hiding_basis for the mask rows, and the first half of the folded polynomial, the one holding the coefficients of poly, has to evaluate to eval + gamma*mask_eval.
+pub fn verify_hiding(
+    commit: Commitment,
+    eval: BinaryField128b,
+    proof: HidingEvalProof,
+    point: Vec<BinaryField128b>,
+    ntt: &AdditiveNTT
+)->Result<(), VerifyError>{
+
+    check_rate(&commit)?;
+
+    //Every row is twice as long as the one commit would lay poly out in, and the mask rows come on top.
+    let (message_cols, rows) = (2<<commit.cols, (1<<commit.rows) + HIDING_ROWS);
+    let queries = canonical_queries(&hiding_queries(&commit.commit, &proof.folded_poly, NUM_QUERIES, message_cols, commit.encoding_params().rate));
+
+    check_opening_shape(&proof.folded_poly, &proof.queried_columns, point.len(), commit.polynomial_log_len(), rows, commit.cols + 1, &queries)?;
+    check_merkle_paths(&proof.merkle_paths, commit.cols + 1 + commit.log_inv_rate, &queries)?;
+
+    traced!("merkle-check", par_iter!(queries).enumerate().try_for_each(|(i, &query)|{
+        verify_merkle_path(&commit.commit, hash_field(&proof.queried_columns[i]), query, &proof.merkle_paths[i])
+    }))?;
+
+    let (row_point, col_point) = point.split_at(commit.rows);
+    let gamma = hiding_challenge(&commit.commit, &point, proof.mask_eval);
+    let mut scalars = compute_fourier_bases(row_point);
+    scalars.extend(hiding_basis().into_iter().map(|scalar| gamma*scalar));
+    check_column_folds(&proof.folded_poly, &proof.queried_columns, &scalars, &queries, ntt, commit.encoding_params())?;
+
+    traced!("eval-check", {
+        let scalars = compute_fourier_bases(col_point);
+
+        check_evaluation(eval + gamma*proof.mask_eval, linear_combination(&scalars, &proof.folded_poly[..proof.folded_poly.len()/2]))
+    })
+}
+
+//Verifies a proof made with prove_batched, same as verify except the opened columns are checked against the root with their combined opening.
+//As in verify_with_queries, the proof is checked against canonical_queries(queries).
+pub fn verify_batched(
+    commit: Commitment,
+    eval: BinaryField128b,
+    proof:BatchEvalProof,
+    point: Vec<BinaryField128b>,
+    queries: Vec<usize>,
+    ntt: &AdditiveNTT
+)->Result<(), VerifyError>{
+
+    let queries = canonical_queries(&queries);
+    check_queries(&commit, &queries)?;
+    check_opening_shape(&proof.folded_poly, &proof.queried_columns, point.len(), commit.polynomial_log_len(), 1<<commit.rows, commit.cols, &queries)?;
+
+    traced!("merkle-check", {
+        let leaves:Vec<(usize, Hash)> = queries.iter().zip(&proof.queried_columns).map(|(&query, column)| (query, hash_field(column))).collect();
+        verify_multi_path(&commit.commit, commit.cols, &leaves, &proof.multi_path)?;
+    });
+
+    let scalars = derive_fold_scalars(&commit, &point)?;
+    check_column_folds(&proof.folded_poly, &proof.queried_columns, &scalars, &queries, ntt, commit.encoding_params())?;
+
+    traced!("eval-check", {
+        let scalars = compute_fourier_bases(split_point(&commit, &point)?.1);
+
+        check_evaluation(eval, linear_combination(&scalars, &proof.folded_poly))
+    })
+}
+
+
+//The root the opened columns of proof lead to, for a caller comparing it to a root stored elsewhere instead of passing it to verify.
+//It is recomputed from the path of the smallest query, and the paths of all the other queries have to lead to the same root.
+pub fn recompute_root_from_queries(proof: &EvalProof, queries: &[usize])->Result<Hash, MerkleError>{
+    let queries = &canonical_queries(queries);
+    let leaf_root = |i:usize| merkle_root_from_path(hash_field(&proof.queried_columns[i]), queries[i], &proof.merkle_paths[i]);
+
+    if queries.is_empty() || proof.queried_columns.len() != queries.len() || proof.merkle_paths.len() != queries.len(){
+        return Err(MerkleError::NoOpenings);
+    }
+
+    let root = leaf_root(0);
+    match (1..queries.len()).find(|&i| leaf_root(i) != root) {
+        Some(i) => Err(MerkleError::RootDisagreement { index: queries[i] }),
+        None => Ok(root)
+    }
+}
+
+//Same as verify, but also checks the linear relation claimed by prove_with_linear_check. Coefficient i sits in row i/2^commit.cols of the systematic
+//column i%2^commit.cols, so once the opened columns are checked against the root the relation is recomputed from them and compared to the claim.
+pub fn verify_with_linear_check(
     commit: Commitment,
     eval: BinaryField128b,
     proof:EvalProof,
     point: Vec<BinaryField128b>,
     queries: Vec<usize>,
+    relation: &[(usize, BinaryField128b)],
     ntt: &AdditiveNTT
-){
+)->Result<(), VerifyError>{
+
+    let linear_check = proof.linear_check.clone().ok_or_else(|| malformed("proof does not carry a linear check".to_string()))?;
+    let root = commit.commit.clone();
+    let (rows, cols) = (1<<commit.rows, 1<<commit.cols);
 
-    let scalars = compute_fourier_bases(&point[..point.len() - (commit.cols + 5)].to_vec());
-    let encoded_poly = encode_extension(&proof.folded_poly, ntt);
+    verify_with_queries(commit, eval, proof, point, queries, ntt)?;
+
+    if linear_check.columns.len() != linear_check.column_indices.len() || linear_check.merkle_paths.len() != linear_check.column_indices.len(){
+        return Err(malformed("expected an opened column and merkle path per linear check column".to_string()));
+    }
+    for (i, &col) in linear_check.column_indices.iter().enumerate(){
+        verify_merkle_path(&root, hash_field(&linear_check.columns[i]), col, &linear_check.merkle_paths[i])?;
+    }
+
+    let mut sum = BinaryField128b::ZERO;
+    for &(index, scalar) in relation{
+        let position = linear_check.column_indices.iter().position(|&col| col == index%cols)
+        .filter(|&position| index < rows*cols && linear_check.columns[position].len() == rows)
+        .ok_or_else(|| malformed(format!("column of coefficient {index} was not opened")))?;
+
+        sum += scalar*BinaryField128b::from(linear_check.columns[position][index/cols]);
+    }
+
+    if sum != linear_check.claimed_sum{
+        return Err(VerifyError::LinearRelationMismatch);
+    }
+    Ok(())
+}
+
+//Verifies an opening of the polynomial at index of a commit_heterogeneous commitment, made with prove_heterogeneous.
+//The opened columns are folded with the polynomial's eq table in its own row block and zero everywhere else.
+//As in verify_with_queries, the proof is checked against canonical_queries(queries).
+pub fn verify_heterogeneous(
+    commitment: &HeterogeneousCommitment,
+    index: usize,
+    eval: BinaryField128b,
+    proof: &EvalProof,
+    point: &[BinaryField128b],
+    queries: &[usize],
+    ntt: &AdditiveNTT
+)->Result<(), VerifyError>{
+
+    let queries = &canonical_queries(queries);
+    check_query_range(commitment.cols, queries)?;
+
+    let shape = commitment.shapes.get(index).ok_or_else(|| malformed(format!("no polynomial {index} in the commitment")))?;
+    check_proof_shape(proof, point.len(), shape.log_len + PACKING_DEGREE, commitment.total_rows(), commitment.cols, queries)?;
+
+    let padded_point = commitment.padded_point(index, point);
+    let (row_point, col_point) = padded_point.split_at(shape.log_rows);
+
+    let mut scalars = vec![BinaryField128b::ZERO; commitment.total_rows()];
+    compute_fourier_bases_into(row_point, &mut scalars[shape.row_offset..shape.row_offset + (1<<shape.log_rows)]);
+
+    //commit_heterogeneous encodes every block with the default params, see Code::with_log_cols.
+    check_columns(&commitment.commit, proof, &scalars, queries, ntt, EncodingParams::default(), |_, column| hash_field(column))?;
+
+    traced!("eval-check", {
+        let scalars = compute_fourier_bases(col_point);
+
+        check_evaluation(eval, linear_combination(&scalars, &proof.folded_poly))
+    })
+}
+
+//Same as verify, but first checks the challenges the prover recorded with prove_recording_challenges match the ones the verifier derives from the transcript.
+//A divergence means prover and verifier disagree on how the challenges are derived.
+pub fn verify_with_recorded_challenges(
+    commit: Commitment,
+    eval: BinaryField128b,
+    proof:EvalProof,
+    point: Vec<BinaryField128b>,
+    ntt: &AdditiveNTT,
+    recorded: &ChallengeTranscript
+)->Result<(), VerifyError>{
+
+    if point.len() != commit.polynomial_log_len(){
+        return Err(VerifyError::PointLengthMismatch { expected: commit.polynomial_log_len(), got: point.len() });
+    }
+    check_query_count(&commit, NUM_QUERIES)?;
+    let derived = derive_challenges(&commit, eval, &point, &proof)?;
+    compare_challenges(recorded, &derived)?;
+
+    verify_with_queries(commit, eval, proof, point, derived.queries, ntt)
+}
+
+//Lengths of the eq table and codeword buffers verify_no_alloc needs for proofs against commit. The eq buffer is shared by the row and column halves of the point.
+pub fn no_alloc_scratch_lens(commit: &Commitment)->(usize, usize){
+    ((1<<commit.rows).max(1<<commit.col_point_len()), 1<<(commit.cols + commit.log_inv_rate))
+}
+
+//Variant of verify for verifiers with a fixed memory budget. The eq tables are expanded into eq_scratch and the folded polynomial is re-encoded into codeword_scratch,
+//and the sums are done serially so rayon doesn't allocate tasks. The Keccak digests of the opened columns are the only allocations left.
+//Like verify_with_queries the proof is checked against canonical_queries(queries), which is computed in place, so queries is left sorted with the distinct
+//queries at its front.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_no_alloc(
+    commit: &Commitment,
+    eval: BinaryField128b,
+    proof: &EvalProof,
+    point: &[BinaryField128b],
+    queries: &mut [usize],
+    ntt: &AdditiveNTT,
+    eq_scratch: &mut [BinaryField128b],
+    codeword_scratch: &mut [PackedAlgebra32]
+)->Result<(), VerifyError>{
+
+    let queries = canonicalize_in_place(queries);
+    check_queries(commit, queries)?;
+    check_proof_shape(proof, point.len(), commit.polynomial_log_len(), 1<<commit.rows, commit.cols, queries)?;
+
+    let (eq_len, codeword_len) = no_alloc_scratch_lens(commit);
+    assert!(eq_scratch.len() >= eq_len && codeword_scratch.len() >= codeword_len, "Scratch buffers are too small.");
+
+    let (row_point, col_point) = split_point(commit, point)?;
+
+    let encoded_poly = &mut codeword_scratch[..codeword_len];
+    encode_extension_into(&proof.folded_poly, encoded_poly, ntt, commit.encoding_params());
+
+    let scalars = &mut eq_scratch[..1<<row_point.len()];
+    compute_fourier_bases_into(row_point, scalars);
 
     for i in 0..queries.len(){
         let leaf_hash = hash_field(&proof.queried_columns[i]);
+        if proof.leaf_hashes.as_ref().is_some_and(|leaf_hashes| leaf_hashes[i] != leaf_hash){
+            return Err(VerifyError::LeafHashMismatch { query: queries[i] });
+        }
+
+        verify_merkle_path(&commit.commit, leaf_hash, queries[i],&proof.merkle_paths[i])?;
 
-        verify_merkle_path(&commit.commit, leaf_hash, queries[i],&proof.merkle_paths[i]);
-        let linear_combination =  unpacked_linear_combination(&scalars, &proof.queried_columns[i]);
-        assert_eq!(encoded_poly[queries[i]],linear_combination, "Query {i} failed")
+        let mut linear_combination = [BinaryField128b::ZERO; 32];
+        for (scalar, val) in scalars.iter().zip(&proof.queried_columns[i]){
+            for (bit, entry) in linear_combination.iter_mut().enumerate(){
+                if (val.val()>>bit)&1 == 1{
+                    *entry += *scalar
+                }
+            }
+        }
+        if encoded_poly[queries[i]] != PackedAlgebra32::new(linear_combination){
+            return Err(VerifyError::ColumnCheckFailed { query: queries[i] });
+        }
     }
 
-    let scalars = compute_fourier_bases(&point[point.len() - (commit.cols + 5)..].to_vec());
+    let scalars = &mut eq_scratch[..1<<col_point.len()];
+    compute_fourier_bases_into(col_point, scalars);
 
-    assert_eq!(eval, linear_combination(&scalars, &proof.folded_poly));
+    check_evaluation(eval, scalars.iter().zip(&proof.folded_poly).fold(BinaryField128b::ZERO, |acc, (scalar, val)| acc + *scalar * *val))
+}
 
+//Sorts queries and moves the distinct ones to the front, returning them, i.e canonical_queries(queries) without allocating.
+fn canonicalize_in_place(queries: &mut [usize])->&[usize]{
+    queries.sort_unstable();
+
+    let mut distinct = 0;
+    for i in 0..queries.len(){
+        if distinct == 0 || queries[i] != queries[distinct - 1]{
+            queries[distinct] = queries[i];
+            distinct += 1;
+        }
+    }
+    &queries[..distinct]
 }
 
+//Checks that the polynomials committed to in old_commit and new_commit only differ in the coefficient at changed_index.
+//Both openings must be valid, share the same siblings, i.e every other column is unchanged, and the opened columns may only differ in the row of changed_index.
+pub fn verify_coefficient_diff(
+    old_commit: &Commitment,
+    new_commit: &Commitment,
+    changed_index: usize,
+    proof: &DiffProof
+)->Result<(), VerifyError>{
 
-pub fn linear_combination<F0:ExtensionField<F1>, F1:Field>(scalars: &Vec<F0>, vals: &Vec<F1>)->F0{
+    if (old_commit.rows, old_commit.cols) != (new_commit.rows, new_commit.cols){
+        return Err(VerifyError::ShapeMismatch { old_rows: old_commit.rows, old_cols: old_commit.cols, new_rows: new_commit.rows, new_cols: new_commit.cols });
+    }
 
-    vals.par_iter().zip(scalars.par_iter()).map(|(val, scalar)|  *scalar* *val).reduce(||F0::ZERO, |acc,g| acc + g)
+    let (rows, len) = (1<<old_commit.rows, 1<<(old_commit.rows + old_commit.cols));
+    if changed_index >= len{
+        return Err(VerifyError::CoefficientOutOfRange { index: changed_index, len });
+    }
+    let (changed_row, col) = (changed_index>>old_commit.cols, changed_index%(1<<old_commit.cols));
+    if proof.old_column.len() != rows || proof.new_column.len() != rows{
+        return Err(malformed(format!("opened columns must have length {rows}")));
+    }
+
+    for (column, path, commit) in [(&proof.old_column, &proof.old_path, old_commit), (&proof.new_column, &proof.new_path, new_commit)]{
+        if !bool::from(merkle_root_from_path(hash_field(column), col, path).ct_eq(&commit.commit)){
+            return Err(VerifyError::MerklePathMismatch { query: col });
+        }
+    }
+    if proof.old_path != proof.new_path{
+        return Err(VerifyError::DiffPathMismatch { col });
+    }
+
+    match proof.old_column.iter().zip(&proof.new_column).enumerate().position(|(row, (old_val, new_val))| row != changed_row && old_val != new_val) {
+        Some(row) => Err(VerifyError::UnchangedRowMismatch { row, changed_row }),
+        None => Ok(())
+    }
 }
 
-pub fn unpacked_linear_combination(scalars: &Vec<BinaryField128b>, vals: &Vec<BinaryField32b>)->PackedAlgebra32{
+//Debugging helper for a proof that verify rejects, shrinks the set of queries to a minimal one that is still rejected, which isolates the offending columns.
+//We first keep halving the queries while one of the halves still fails, then try dropping the remaining queries one at a time.
+//Returns the proof restricted to the remaining queries together with those queries. As the proof opens canonical_queries(queries), so do the subsets,
+//i.e the returned queries are sorted and distinct whatever order queries came in.
+pub fn minimize_failing_proof(
+    commit: Commitment,
+    eval: BinaryField128b,
+    proof:EvalProof,
+    point: Vec<BinaryField128b>,
+    queries: Vec<usize>,
+    ntt: &AdditiveNTT
+)->(EvalProof, Vec<usize>){
+
+    //The columns of the proof are in the order of the canonical queries, which positions in subset refer to.
+    let queries = canonical_queries(&queries);
+    let fails = |subset:&[usize]|{
+        let (sub_proof, sub_queries) = restrict_proof(&proof, &queries, subset);
+        verify_with_queries(commit.clone(), eval, sub_proof, point.clone(), sub_queries, ntt).is_err()
+    };
+
+    let mut subset:Vec<usize> = (0..queries.len()).collect();
+    assert!(fails(&subset), "The proof is accepted, there is nothing to minimize.");
+
+    while subset.len() > 1{
+        let (left, right) = subset.split_at(subset.len()/2);
+        if fails(left){
+            subset = left.to_vec();
+        }
+        else if fails(right){
+            subset = right.to_vec();
+        }
+        else{
+            break;
+        }
+    }
+
+    let mut i = 0;
+    while i < subset.len(){
+        let mut candidate = subset.clone();
+        candidate.remove(i);
+        if fails(&candidate){
+            subset = candidate;
+        }
+        else{
+            i += 1;
+        }
+    }
+
+    restrict_proof(&proof, &queries, &subset)
+}
+
+//Keeps only the queries, and their opened columns and paths, at the given positions of the canonical query list the proof was made for.
+fn restrict_proof(proof:&EvalProof, queries:&[usize], subset:&[usize])->(EvalProof, Vec<usize>){
+    (
+        EvalProof{
+            folded_poly: proof.folded_poly.clone(),
+            queried_columns: subset.iter().map(|&i| proof.queried_columns[i].clone()).collect(),
+            merkle_paths: subset.iter().map(|&i| proof.merkle_paths[i].clone()).collect(),
+            leaf_hashes: proof.leaf_hashes.as_ref().map(|hashes| subset.iter().map(|&i| hashes[i].clone()).collect()),
+            fold_eval: proof.fold_eval.clone(),
+            linear_check: proof.linear_check.clone()
+        },
+        subset.iter().map(|&i| queries[i]).collect()
+    )
+}
+
+pub fn linear_combination<F0:ExtensionField<F1>, F1:Field>(scalars: &[F0], vals: &[F1])->F0{
+
+    parallel_xor_sum(par_iter!(vals).zip(par_iter!(scalars)).map(|(val, scalar)| *scalar * *val))
+}
+
+//Same as linear_combination for scalars and values packed 32 to a PackedAlgebra32. The lane-wise products of each pair of packs are accumulated
+//into a single pack, so every step adds up 32 terms at once, and only the 32 lanes of the final pack are summed.
+pub fn packed_linear_combination(scalars: &[PackedAlgebra32], vals: &[PackedAlgebra32])->BinaryField128b{
+    let acc:PackedAlgebra32 = par_iter!(vals).zip(par_iter!(scalars)).map(|(val, scalar)| *scalar * *val).sum();
+
+    acc.0.iter().fold(BinaryField128b::ZERO, |sum, lane| sum + *lane)
+}
+
+//Chunk size for linear_combination_chunked that matches the tasks linear_combination gets from parallel_xor_sum. The linear_combination bench
+//times linear_combination_chunked at this and neighbouring chunk sizes against linear_combination itself, rerun it before changing either.
+pub const LINEAR_COMBINATION_CHUNK_SIZE:usize = XOR_SUM_MIN_LEN;
+
+//Same as linear_combination, but each rayon task sums chunk_size consecutive terms before the partial sums are reduced, instead of reducing term by term.
+pub fn linear_combination_chunked<F0:ExtensionField<F1>, F1:Field>(scalars: &[F0], vals: &[F1], chunk_size:usize)->F0{
+
+    assert!(chunk_size > 0, "Chunk size must be positive.");
+
+    par_chunks!(vals, chunk_size).zip(par_chunks!(scalars, chunk_size))
+    .map(|(val_chunk, scalar_chunk)| val_chunk.iter().zip(scalar_chunk).fold(F0::ZERO, |acc, (val, scalar)| acc + *scalar* *val))
+    .sum()
+}
+
+pub fn unpacked_linear_combination(scalars: &[BinaryField128b], vals: &[BinaryField32b])->PackedAlgebra32{
+
+    //When every scalar lies in the BinaryField32b subfield, e.g when the point is boolean, we accumulate in the subfield and only lift the 32 sums at the end.
+    let subfield_scalars: Option<Vec<BinaryField32b>> = scalars.iter().map(|&scalar| BinaryField32b::try_from(scalar).ok()).collect();
+
+    match subfield_scalars {
+        Some(subfield_scalars) => unpacked_linear_combination_subfield(&subfield_scalars, vals),
+        None => unpacked_linear_combination_full(scalars, vals)
+    }
+}
+
+fn unpacked_linear_combination_subfield(scalars: &[BinaryField32b], vals: &[BinaryField32b])->PackedAlgebra32{
+
+    let mut res = [BinaryField32b::ZERO; 32];
+
+    par_iter_mut!(res).enumerate().for_each(|(i, val)|
+        for k in 0..scalars.len(){
+            if (vals[k].val()>>i)&1==1{
+                *val += scalars[k]
+            }
+        }
+    );
+    PackedAlgebra32::new(res.map(BinaryField128b::from))
+}
+
+fn unpacked_linear_combination_full(scalars: &[BinaryField128b], vals: &[BinaryField32b])->PackedAlgebra32{
 
     let mut res = [BinaryField128b::ZERO; 32];
 
-    res.par_iter_mut().enumerate().for_each(|(i, val)|
+    par_iter_mut!(res).enumerate().for_each(|(i, val)|
         for k in 0..scalars.len(){
             if (vals[k].val()>>i)&1==1{
                 *val += scalars[k]
@@ -47,4 +864,772 @@ pub fn unpacked_linear_combination(scalars: &Vec<BinaryField128b>, vals: &Vec<Bi
         }
     );
     PackedAlgebra32::new(res)
-}
\ No newline at end of file
+}
+
+#[test]
+fn unpacked_linear_combination_subfield_test(){
+    use binius_field::BinaryField1b;
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    for l in 1..10{
+        let point:Vec<BinaryField128b> = (0..l).map(|_| BinaryField1b::random(&mut rng).into()).collect();
+        let scalars = compute_fourier_bases(&point);
+        let vals:Vec<BinaryField32b> = (0..scalars.len()).map(|_| BinaryField32b::random(&mut rng)).collect();
+
+        let subfield_scalars:Vec<BinaryField32b> = scalars.iter().map(|&scalar| BinaryField32b::try_from(scalar).unwrap()).collect();
+        assert_eq!(unpacked_linear_combination_subfield(&subfield_scalars, &vals), unpacked_linear_combination_full(&scalars, &vals));
+        assert_eq!(unpacked_linear_combination(&scalars, &vals), unpacked_linear_combination_full(&scalars, &vals));
+
+        let point:Vec<BinaryField128b> = (0..l).map(|_| BinaryField128b::random(&mut rng)).collect();
+        let scalars = compute_fourier_bases(&point);
+        assert_eq!(unpacked_linear_combination(&scalars, &vals), unpacked_linear_combination_full(&scalars, &vals));
+    }
+}
+
+#[test]
+fn packed_scalar_mul_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    let scalars:Vec<BinaryField128b> = (0..1<<8).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let vals:Vec<BinaryField32b> = (0..1<<8).map(|_| BinaryField32b::random(&mut rng)).collect();
+
+    let scaled = scalars.iter().zip(vals.iter()).fold(PackedAlgebra32::default(), |acc, (&scalar, &val)| acc + PackedAlgebra32::from(val)*scalar);
+    assert_eq!(scaled, unpacked_linear_combination(&scalars, &vals));
+}
+
+#[test]
+fn linear_combination_chunked_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    let scalars:Vec<BinaryField128b> = (0..(1<<12) + 3).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let vals:Vec<BinaryField128b> = (0..(1<<12) + 3).map(|_| BinaryField128b::random(&mut rng)).collect();
+
+    let expected = scalars.iter().zip(vals.iter()).fold(BinaryField128b::ZERO, |acc, (scalar, val)| acc + *scalar * *val);
+
+    assert_eq!(linear_combination(&scalars, &vals), expected);
+    for chunk_size in [1, 2, 7, 64, 1<<10, 1<<13]{
+        assert_eq!(linear_combination_chunked(&scalars, &vals, chunk_size), expected, "Chunk size {chunk_size} failed");
+    }
+}
+
+#[test]
+fn packed_linear_combination_test(){
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    for len in [32, 32<<7, 32<<12]{
+        let scalars:Vec<BinaryField128b> = (0..len).map(|_| BinaryField128b::random(&mut rng)).collect();
+        let vals:Vec<BinaryField128b> = (0..len).map(|_| BinaryField128b::random(&mut rng)).collect();
+
+        let packed = packed_linear_combination(&PackedAlgebra32::pack(scalars.clone()), &PackedAlgebra32::pack(vals.clone()));
+        assert_eq!(packed, linear_combination(&scalars, &vals), "Length {len} failed");
+    }
+}
+
+#[test]
+fn minimize_failing_proof_test(){
+    use rand::thread_rng;
+    use crate::{prover::prove_with_queries, test::{random_opening, Opening}};
+
+    let mut rng = thread_rng();
+    let l = 10;
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+    let queries:Vec<usize> = (0..encoded_poly.cols).collect();
+
+    let mut proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+    let bad = 11;
+    proof.queried_columns[bad][0] += BinaryField32b::ONE;
+
+    let (minimal_proof, minimal_queries) = minimize_failing_proof(commitment, eval, proof.clone(), point, queries, &ntt);
+
+    assert_eq!(minimal_queries, vec![bad]);
+    assert_eq!(minimal_proof.queried_columns, vec![proof.queried_columns[bad].clone()]);
+    assert_eq!(minimal_proof.merkle_paths, vec![proof.merkle_paths[bad].clone()]);
+}
+
+#[test]
+fn minimize_unsorted_queries_test(){
+    use rand::thread_rng;
+    use crate::{prover::prove_with_queries, test::{random_opening, Opening}};
+
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(10, &mut thread_rng());
+
+    //The proof opens 0, 3, 7, 11 and the last column, in that order, so column 7 sits at position 2 of the proof but not of queries.
+    let queries = vec![11, 7, 3, 7, encoded_poly.cols - 1, 0, 11, 3];
+    let mut proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+    assert_eq!(proof.queried_columns.len(), 5);
+    proof.queried_columns[2][0] += BinaryField32b::ONE;
+
+    let (minimal_proof, minimal_queries) = minimize_failing_proof(commitment, eval, proof.clone(), point, queries, &ntt);
+
+    assert_eq!(minimal_queries, vec![7]);
+    assert_eq!(minimal_proof.queried_columns, vec![proof.queried_columns[2].clone()]);
+    assert_eq!(minimal_proof.merkle_paths, vec![proof.merkle_paths[2].clone()]);
+}
+
+#[test]
+fn coefficient_diff_test(){
+    use rand::thread_rng;
+    use crate::{prover::{commit, prove_coefficient_diff, ProveError}, utils::encoding::{Code, EncodingParams}};
+
+    let mut rng = thread_rng();
+    let l = 8;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let (old_commit, old_tree, old_code) = commit(&poly, &ntt);
+
+    let changed_index = 37;
+    let mut new_poly = poly.clone();
+    new_poly[changed_index] += BinaryField32b::ONE;
+    let (new_commit, new_tree, new_code) = commit(&new_poly, &ntt);
+
+    let mut updated_code = Code::new(&poly, &ntt, EncodingParams::default()).unwrap();
+    updated_code.update_coefficient(changed_index, new_poly[changed_index], &ntt);
+    assert!(updated_code.assert_systematic(&new_poly));
+
+    let proof = prove_coefficient_diff(&old_code, &new_code, changed_index, (&old_tree, &new_tree)).unwrap();
+    assert_eq!(verify_coefficient_diff(&old_commit, &new_commit, changed_index, &proof), Ok(()));
+
+    //A second change in the same column is caught by the column check, one in another column by the paths.
+    let (cols, changed_row) = (1<<old_commit.cols, changed_index>>old_commit.cols);
+    let errors = [VerifyError::UnchangedRowMismatch { row: changed_row + 1, changed_row }, VerifyError::DiffPathMismatch { col: changed_index%cols }];
+    for (other_index, error) in [changed_index + cols, changed_index + 1].into_iter().zip(errors){
+        let mut two_changes = new_poly.clone();
+        two_changes[other_index] += BinaryField32b::ONE;
+        let (two_commit, two_tree, two_code) = commit(&two_changes, &ntt);
+
+        let proof = prove_coefficient_diff(&old_code, &two_code, changed_index, (&old_tree, &two_tree)).unwrap();
+        assert_eq!(verify_coefficient_diff(&old_commit, &two_commit, changed_index, &proof), Err(error));
+    }
+
+    let len = poly.len();
+    assert_eq!(verify_coefficient_diff(&old_commit, &new_commit, len, &proof), Err(VerifyError::CoefficientOutOfRange { index: len, len }));
+    let mut tampered = proof.clone();
+    tampered.new_column[0] += BinaryField32b::ONE;
+    assert_eq!(verify_coefficient_diff(&old_commit, &new_commit, changed_index, &tampered), Err(VerifyError::MerklePathMismatch { query: changed_index%cols }));
+
+    //Codes of different shapes are refused by both sides.
+    let (short_commit, short_tree, short_code) = commit(&poly[..len/2], &ntt);
+    assert_eq!(prove_coefficient_diff(&old_code, &short_code, changed_index, (&old_tree, &short_tree)).unwrap_err(),
+        ProveError::ShapeMismatch { old: (old_code.rows, old_code.cols), new: (short_code.rows, short_code.cols) });
+    assert!(matches!(verify_coefficient_diff(&old_commit, &short_commit, changed_index, &proof), Err(VerifyError::ShapeMismatch { .. })));
+}
+
+#[test]
+fn query_out_of_range_test(){
+    use rand::thread_rng;
+    use crate::prover::{commit, prove_with_queries};
+
+    let mut rng = thread_rng();
+    let l = 8;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let (commitment, merkle_tree, encoded_poly) = commit(&poly, &ntt);
+
+    let max = encoded_poly.cols;
+    assert_eq!(check_queries(&commitment, &[0, max - 1]), Ok(()));
+    assert_eq!(check_queries(&commitment, &[0, max, max + 1]), Err(VerifyError::QueryOutOfRange { query: max, max }));
+
+    let point:Vec<BinaryField128b> = (0..l+5).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &vec![1]).unwrap();
+    assert_eq!(verify_with_queries(commitment, BinaryField128b::ZERO, proof, point, vec![max], &ntt), Err(VerifyError::QueryOutOfRange { query: max, max }));
+}
+
+#[test]
+fn unsupported_rate_test(){
+    use rand::thread_rng;
+    use crate::{prover::prove, test::{random_opening, Opening, MIN_PROVABLE_LOG_LEN}};
+
+    let mut rng = thread_rng();
+    let l = MIN_PROVABLE_LOG_LEN;
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+    assert_eq!(commitment.log_inv_rate, encoded_poly.log_inv_rate());
+    assert_eq!(encoded_poly.codeword_len(), commitment.encoding_params().rate*encoded_poly.message_len());
+
+    let proof = prove(&poly, &encoded_poly, &merkle_tree, &point).unwrap();
+    let cols = commitment.cols;
+
+    let mut no_parity = commitment.clone();
+    no_parity.log_inv_rate = 0;
+    assert_eq!(verify(no_parity, eval, proof.clone(), point.clone(), &ntt), Err(VerifyError::UnsupportedRate { log_inv_rate: 0, cols }));
+
+    let mut too_long = commitment.clone();
+    too_long.log_inv_rate = 33 - cols;
+    assert_eq!(verify(too_long, eval, proof.clone(), point.clone(), &ntt), Err(VerifyError::UnsupportedRate { log_inv_rate: 33 - cols, cols }));
+
+    //The rate is observed before the queries are drawn, so claiming another supported rate opens other columns than the proof carries.
+    let mut other_rate = commitment;
+    other_rate.log_inv_rate += 1;
+    assert!(matches!(verify(other_rate, eval, proof, point, &ntt), Err(VerifyError::MerklePathMismatch { .. })));
+}
+
+#[test]
+fn leaf_fn_test(){
+    use rand::thread_rng;
+    use crate::{prover::{commit_with_leaf_fn, prove_with_queries}, utils::{merkle::hash, packed_arithmetic::evaluate_unpacked}};
+
+    //Binds the column index into its leaf.
+    let indexed_leaf = |index:usize, column:&[BinaryField32b]|{
+        let mut bytes = (index as u64).to_le_bytes().to_vec();
+        column.iter().for_each(|v| bytes.extend(v.val().to_le_bytes()));
+        hash(&bytes)
+    };
+
+    let mut rng = thread_rng();
+    let l = 10;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let (commitment, merkle_tree, encoded_poly) = commit_with_leaf_fn(&poly, &ntt, indexed_leaf);
+
+    let point:Vec<BinaryField128b> = (0..l+5).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let eval = evaluate_unpacked(&poly, &compute_fourier_bases(&point));
+    let queries = vec![0, 3, 17, encoded_poly.cols - 1];
+    let proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+
+    assert_eq!(verify_with_leaf_fn(commitment.clone(), eval, proof.clone(), point.clone(), queries.clone(), &ntt, indexed_leaf), Ok(()));
+
+    //Default leaf hashing doesn't match the indexed leaves.
+    assert_eq!(verify_with_queries(commitment, eval, proof, point, queries, &ntt), Err(VerifyError::MerklePathMismatch { query: 0 }));
+}
+
+#[test]
+fn verify_query_test(){
+    use rand::thread_rng;
+    use crate::{prover::prove_with_queries, test::{random_opening, Opening}, utils::encoding::eval_folded_at_column};
+
+    let mut rng = thread_rng();
+    let l = 10;
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+    let queries = vec![0, 3, 17, encoded_poly.cols - 1];
+    let proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+    let scalars = derive_fold_scalars(&commitment, &point).unwrap();
+
+    //Checks the queries of proof one at a time, accepting only if all of them pass.
+    let verify_queries = |proof:&EvalProof|{
+        queries.iter().enumerate().all(|(i, &query)|{
+            let encoded_column = eval_folded_at_column(&proof.folded_poly, query, &ntt, commitment.encoding_params());
+            verify_query(&commitment, &scalars, encoded_column, query, &proof.queried_columns[i], &proof.merkle_paths[i]).is_ok()
+        })
+    };
+
+    let mut tampered_column = proof.clone();
+    tampered_column.queried_columns[1][0] += BinaryField32b::ONE;
+    let mut tampered_path = proof.clone();
+    tampered_path.merkle_paths[2][0] = tampered_path.merkle_paths[3][0].clone();
+    let mut tampered_fold = proof.clone();
+    tampered_fold.folded_poly[5] += BinaryField128b::ONE;
+    let encoded_column = eval_folded_at_column(&proof.folded_poly, 0, &ntt, commitment.encoding_params());
+
+    for proof in [proof, tampered_column, tampered_path, tampered_fold]{
+        let accepted = verify_with_queries(commitment.clone(), eval, proof.clone(), point.clone(), queries.clone(), &ntt).is_ok();
+        //The evaluation isn't checked per query, but the tampered proofs are all caught by their columns already.
+        assert_eq!(verify_queries(&proof), accepted);
+    }
+
+    let (column, path) = (encoded_poly.col(0), merkle_tree.get_merkle_path(0));
+    assert_eq!(verify_query(&commitment, &scalars, encoded_column, 0, &column, &path), Ok(()));
+    assert_eq!(verify_query(&commitment, &scalars, encoded_column, 1<<commitment.cols, &column, &path), Err(VerifyError::QueryOutOfRange { query: 1<<commitment.cols, max: 1<<commitment.cols }));
+    assert!(matches!(verify_query(&commitment, &scalars, encoded_column, 0, &column[1..], &path), Err(VerifyError::MalformedProof { .. })));
+    assert!(matches!(verify_query(&commitment, &scalars[1..], encoded_column, 0, &column, &path), Err(VerifyError::MalformedProof { .. })));
+    assert!(matches!(verify_query(&commitment, &scalars, encoded_column, 0, &column, &path[1..]), Err(VerifyError::MalformedProof { .. })));
+}
+
+#[test]
+fn verify_no_alloc_test(){
+    use rand::thread_rng;
+    use crate::{prover::prove_with_queries, test::{random_opening, Opening}, utils::encoding::{encode_extension, EncodingParams}};
+
+    let mut rng = thread_rng();
+    for l in [6, 9, 12]{
+        let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+        let queries:Vec<usize> = (0..encoded_poly.cols).step_by(3).collect();
+        let proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+
+        let (eq_len, codeword_len) = no_alloc_scratch_lens(&commitment);
+        let mut eq_scratch = vec![BinaryField128b::ZERO; eq_len];
+        let mut codeword_scratch = vec![PackedAlgebra32::new([BinaryField128b::ZERO; 32]); codeword_len];
+
+        let mut eq = vec![BinaryField128b::ZERO; 1<<point.len()];
+        compute_fourier_bases_into(&point, &mut eq);
+        assert_eq!(eq, compute_fourier_bases(&point));
+        encode_extension_into(&proof.folded_poly, &mut codeword_scratch, &ntt, commitment.encoding_params());
+        assert_eq!(codeword_scratch, encode_extension(&proof.folded_poly, &ntt, EncodingParams::default()));
+
+        assert_eq!(verify_with_queries(commitment.clone(), eval, proof.clone(), point.clone(), queries.clone(), &ntt), Ok(()));
+        assert_eq!(verify_no_alloc(&commitment, eval, &proof, &point, &mut queries.clone(), &ntt, &mut eq_scratch, &mut codeword_scratch), Ok(()));
+
+        //Both reject a wrong evaluation and a tampered column.
+        let wrong_eval = eval + BinaryField128b::ONE;
+        let mut bad_proof = proof.clone();
+        bad_proof.queried_columns[0][0] += BinaryField32b::ONE;
+        for (eval, proof, err) in [(wrong_eval, proof.clone(), VerifyError::EvaluationMismatch), (eval, bad_proof, VerifyError::MerklePathMismatch { query: queries[0] })]{
+            assert_eq!(verify_with_queries(commitment.clone(), eval, proof.clone(), point.clone(), queries.clone(), &ntt), Err(err.clone()));
+            assert_eq!(verify_no_alloc(&commitment, eval, &proof, &point, &mut queries.clone(), &ntt, &mut eq_scratch, &mut codeword_scratch), Err(err));
+        }
+
+        //Repeated and unsorted queries are accepted by both, the buffer being canonicalized in place.
+        let mut repeated = vec![encoded_poly.cols - 1, 2, 0, 2, encoded_poly.cols - 1, 1];
+        let proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &repeated).unwrap();
+        assert_eq!(verify_with_queries(commitment.clone(), eval, proof.clone(), point.clone(), repeated.clone(), &ntt), Ok(()));
+        assert_eq!(verify_no_alloc(&commitment, eval, &proof, &point, &mut repeated, &ntt, &mut eq_scratch, &mut codeword_scratch), Ok(()));
+        assert_eq!(repeated[..4], [0, 1, 2, encoded_poly.cols - 1]);
+    }
+}
+
+#[test]
+fn recorded_challenges_test(){
+    use rand::thread_rng;
+    use crate::{prover::prove_recording_challenges, test::{random_opening, Opening, MIN_PROVABLE_LOG_LEN}};
+
+    let mut rng = thread_rng();
+    let l = MIN_PROVABLE_LOG_LEN;
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+    let (proof, recorded) = prove_recording_challenges(&poly, &encoded_poly, &merkle_tree, &point).unwrap();
+
+    assert_eq!(recorded.queries, fiat_shamir_queries(&commitment.commit, commitment.log_inv_rate, &point, eval, &proof.folded_poly, NUM_QUERIES, encoded_poly.cols).unwrap());
+    assert_eq!(recorded, derive_challenges(&commitment, eval, &point, &proof).unwrap());
+    assert_eq!(verify_with_recorded_challenges(commitment.clone(), eval, proof.clone(), point.clone(), &ntt, &recorded), Ok(()));
+
+    //A recorded query the transcript doesn't produce is caught, even though the proof itself is valid.
+    let mut tampered = recorded.clone();
+    tampered.queries[2] ^= 1;
+    assert_eq!(check_recorded_challenges(&commitment, eval, &point, &proof, &tampered), Err(VerifyError::QueryChallengeMismatch { position: 2 }));
+    assert_eq!(verify_with_recorded_challenges(commitment.clone(), eval, proof.clone(), point.clone(), &ntt, &tampered), Err(VerifyError::QueryChallengeMismatch { position: 2 }));
+
+    let mut tampered = recorded.clone();
+    tampered.fold_scalars[3] += BinaryField128b::ONE;
+    assert_eq!(check_recorded_challenges(&commitment, eval, &point, &proof, &tampered), Err(VerifyError::FoldScalarMismatch { position: 3 }));
+
+    let mut tampered = recorded.clone();
+    tampered.fold_scalars.pop();
+    assert_eq!(check_recorded_challenges(&commitment, eval, &point, &proof, &tampered), Err(VerifyError::FoldScalarMismatch { position: recorded.fold_scalars.len() - 1 }));
+
+    assert!(verify_with_recorded_challenges(commitment, eval, proof, point, &ntt, &tampered).is_err());
+}
+
+#[test]
+fn fold_eval_test(){
+    use rand::thread_rng;
+    use crate::{prover::prove_with_fold_eval, test::{random_opening, Opening}};
+
+    let mut rng = thread_rng();
+    let l = 10;
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+    let queries = vec![0, 7, encoded_poly.cols - 1];
+
+    for log_chunk_len in [0, 3, 5]{
+        let proof = prove_with_fold_eval(&poly, &encoded_poly, &merkle_tree, &point, &queries, log_chunk_len).unwrap();
+        let chunks = proof.fold_eval.as_ref().unwrap().chunk_sums.len();
+        assert_eq!(chunks, proof.folded_poly.len()>>log_chunk_len);
+
+        let all_chunks:Vec<usize> = (0..chunks).collect();
+        assert_eq!(verify_with_fold_eval(commitment.clone(), eval, proof.clone(), point.clone(), queries.clone(), &all_chunks, &ntt), Ok(()));
+
+        //A wrong evaluation with consistent chunk sums is caught once the altered chunk is sampled.
+        let wrong_eval = eval + BinaryField128b::ONE;
+        let mut wrong_proof = proof.clone();
+        let fold_eval = wrong_proof.fold_eval.as_mut().unwrap();
+        fold_eval.eval = wrong_eval;
+        fold_eval.chunk_sums[3] += BinaryField128b::ONE;
+
+        assert_eq!(verify_with_fold_eval(commitment.clone(), wrong_eval, wrong_proof.clone(), point.clone(), queries.clone(), &[0, 1, 2], &ntt), Ok(()));
+        assert_eq!(verify_with_fold_eval(commitment.clone(), wrong_eval, wrong_proof.clone(), point.clone(), queries.clone(), &[1, 3], &ntt), Err(VerifyError::ChunkSumMismatch { chunk: 3 }));
+
+        assert_eq!(verify_with_fold_eval(commitment.clone(), wrong_eval, proof.clone(), point.clone(), queries.clone(), &all_chunks, &ntt), Err(VerifyError::EvaluationMismatch));
+    }
+}
+
+#[test]
+fn heterogeneous_test(){
+    use rand::thread_rng;
+    use crate::{prover::{commit_heterogeneous, prove_heterogeneous}, utils::packed_arithmetic::evaluate_unpacked};
+
+    let mut rng = thread_rng();
+    let polys:Vec<Vec<BinaryField32b>> = [4, 6, 1].iter().map(|&l| (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect()).collect();
+    let ntt = AdditiveNTT::new(6);
+    let (commitment, merkle_tree, encoded_polys) = commit_heterogeneous(&polys, &ntt);
+
+    assert_eq!(commitment.shapes.iter().map(|shape| shape.log_len).collect::<Vec<_>>(), vec![4, 6, 1]);
+    assert_eq!(commitment.total_rows(), encoded_polys.rows);
+
+    //Every column, in reverse and with a repeat, both sides open and check canonical_queries(queries).
+    let queries:Vec<usize> = (0..1<<commitment.cols).rev().chain([0]).collect();
+    for (index, poly) in polys.iter().enumerate(){
+        let point:Vec<BinaryField128b> = (0..commitment.shapes[index].log_len + 5).map(|_| BinaryField128b::random(&mut rng)).collect();
+        let eval = evaluate_unpacked(poly, &compute_fourier_bases(&point));
+
+        let proof = prove_heterogeneous(&commitment, index, poly, &encoded_polys, &merkle_tree, &point, &queries).unwrap();
+        assert_eq!(verify_heterogeneous(&commitment, index, eval, &proof, &point, &queries, &ntt), Ok(()));
+        assert_eq!(verify_heterogeneous(&commitment, index, eval + BinaryField128b::ONE, &proof, &point, &queries, &ntt), Err(VerifyError::EvaluationMismatch));
+    }
+}
+
+#[test]
+fn linear_check_test(){
+    use rand::thread_rng;
+    use crate::{prover::{prove_with_linear_check, ProveError}, test::{random_opening, Opening}};
+
+    let mut rng = thread_rng();
+    let l = 10;
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+    let queries = vec![2, 11, encoded_poly.cols - 1];
+
+    //Sum of a few coefficients, two of them in the same column.
+    let relation:Vec<(usize, BinaryField128b)> = [3, 40, 40 + encoded_poly.cols, 1001].iter().map(|&index| (index, BinaryField128b::ONE)).collect();
+    let proof = prove_with_linear_check(&poly, &encoded_poly, &merkle_tree, &point, &queries, &relation).unwrap();
+
+    let linear_check = proof.linear_check.clone().unwrap();
+    assert_eq!(linear_check.claimed_sum, relation.iter().fold(BinaryField128b::ZERO, |acc, &(index, _)| acc + BinaryField128b::from(poly[index])));
+    assert_eq!(linear_check.column_indices.len(), 3);
+
+    assert_eq!(verify_with_linear_check(commitment.clone(), eval, proof.clone(), point.clone(), queries.clone(), &relation, &ntt), Ok(()));
+
+    let mut wrong_sum = proof.clone();
+    wrong_sum.linear_check.as_mut().unwrap().claimed_sum += BinaryField128b::ONE;
+    assert_eq!(verify_with_linear_check(commitment.clone(), eval, wrong_sum, point.clone(), queries.clone(), &relation, &ntt), Err(VerifyError::LinearRelationMismatch));
+
+    let mut wrong_column = proof.clone();
+    wrong_column.linear_check.as_mut().unwrap().columns[0][0] += BinaryField32b::ONE;
+    let first_column = wrong_column.linear_check.as_ref().unwrap().column_indices[0];
+    assert_eq!(verify_with_linear_check(commitment, eval, wrong_column, point.clone(), queries.clone(), &relation, &ntt), Err(VerifyError::MerklePathMismatch { query: first_column }));
+
+    let out_of_range = [(3, BinaryField128b::ONE), (poly.len(), BinaryField128b::ONE)];
+    assert_eq!(prove_with_linear_check(&poly, &encoded_poly, &merkle_tree, &point, &queries, &out_of_range).unwrap_err(), ProveError::CoefficientOutOfRange { index: poly.len(), len: poly.len() });
+}
+
+#[test]
+fn malformed_proof_test(){
+    use rand::thread_rng;
+    use crate::{prover::prove_with_queries, test::{random_opening, Opening}};
+
+    let mut rng = thread_rng();
+    let l = 8;
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+    let queries = vec![1, 2];
+    let proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+
+    let mut short_fold = proof.clone();
+    short_fold.folded_poly.pop();
+    let mut missing_path = proof.clone();
+    missing_path.merkle_paths.pop();
+    let mut short_column = proof.clone();
+    short_column.queried_columns[1].pop();
+    let mut missing_column = proof.clone();
+    missing_column.queried_columns.pop();
+    let mut short_path = proof.clone();
+    short_path.merkle_paths[0].pop();
+    let mut long_path = proof.clone();
+    long_path.merkle_paths[1].push(proof.merkle_paths[1][0].clone());
+
+    for bad_proof in [short_fold, missing_path, short_column, missing_column, short_path, long_path]{
+        let result = verify_with_queries(commitment.clone(), eval, bad_proof, point.clone(), queries.clone(), &ntt);
+        assert!(matches!(result, Err(VerifyError::MalformedProof { .. })), "{result:?}");
+    }
+
+    let result = verify_with_queries(commitment, eval, proof, point[1..].to_vec(), queries, &ntt);
+    assert_eq!(result, Err(VerifyError::PointLengthMismatch { expected: l + 5, got: l + 4 }));
+}
+
+#[test]
+fn claimed_evaluation_test(){
+    use rand::thread_rng;
+    use crate::{prover::prove, test::{random_opening, Opening, MIN_PROVABLE_LOG_LEN}, utils::packed_arithmetic::evaluate_unpacked};
+
+    let mut rng = thread_rng();
+    let l = MIN_PROVABLE_LOG_LEN;
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+    let proof = prove(&poly, &encoded_poly, &merkle_tree, &point).unwrap();
+
+    let claimed = proof.claimed_evaluation(&point, PACKING_DEGREE, commitment.cols);
+    assert_eq!(verify(commitment.clone(), eval, proof.clone(), point.clone(), &ntt), Ok(()));
+    assert_eq!(claimed, eval);
+
+    //A proof made at another point claims the evaluation there, which is not the eval verify accepts at this point.
+    let other_point:Vec<BinaryField128b> = (0..l+5).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let other_claimed = prove(&poly, &encoded_poly, &merkle_tree, &other_point).unwrap().claimed_evaluation(&other_point, PACKING_DEGREE, commitment.cols);
+    assert_eq!(other_claimed, evaluate_unpacked(&poly, &compute_fourier_bases(&other_point)));
+    assert_eq!(verify(commitment, other_claimed, proof, point, &ntt), Err(VerifyError::EvaluationMismatch));
+}
+
+#[test]
+fn short_point_test(){
+    use rand::thread_rng;
+    use crate::{prover::prove, test::{random_opening, Opening, MIN_PROVABLE_LOG_LEN}};
+
+    let mut rng = thread_rng();
+    let l = MIN_PROVABLE_LOG_LEN;
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+    assert_eq!(commitment.col_point_len(), encoded_poly.col_point_len());
+
+    let proof = prove(&poly, &encoded_poly, &merkle_tree, &point).unwrap();
+    let (row_point, col_point) = split_point(&commitment, &point).unwrap();
+    assert_eq!((row_point.len(), col_point.len()), (commitment.rows, commitment.col_point_len()));
+
+    //One variable short, the shape check rejects the point before any of it is split.
+    let short_point = point[1..].to_vec();
+    let error = VerifyError::PointLengthMismatch { expected: l + 5, got: l + 4 };
+    assert_eq!(verify(commitment.clone(), eval, proof.clone(), short_point.clone(), &ntt), Err(error.clone()));
+
+    let (eq_len, codeword_len) = no_alloc_scratch_lens(&commitment);
+    let mut eq_scratch = vec![BinaryField128b::ZERO; eq_len];
+    let mut codeword_scratch = vec![PackedAlgebra32::new([BinaryField128b::ZERO; 32]); codeword_len];
+    let queries = fiat_shamir_queries(&commitment.commit, commitment.log_inv_rate, &point, eval, &proof.folded_poly, NUM_QUERIES, encoded_poly.cols).unwrap();
+    assert_eq!(verify_no_alloc(&commitment, eval, &proof, &short_point, &mut queries.clone(), &ntt, &mut eq_scratch, &mut codeword_scratch), Err(error));
+
+    //Too short to even hold the column part, the split itself fails instead of underflowing.
+    let error = VerifyError::PointLengthMismatch { expected: l + 5, got: commitment.col_point_len() - 1 };
+    let tiny_point = &point[..commitment.col_point_len() - 1];
+    assert_eq!(split_point(&commitment, tiny_point), Err(error.clone()));
+    assert_eq!(derive_fold_scalars(&commitment, tiny_point), Err(error));
+}
+
+#[test]
+fn batched_test(){
+    use rand::{thread_rng, Rng};
+    use crate::{prover::{prove_with_queries, prove_batched}, test::{random_opening, Opening}};
+
+    let mut rng = thread_rng();
+    let l = 10;
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+
+    //Random queries with repeats, as drawn by the verifier.
+    let queries:Vec<usize> = (0..40).map(|_| rng.gen_range(0..encoded_poly.cols)).chain([3, 3, 0]).collect();
+    let batch_proof = prove_batched(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+    let proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+    assert_eq!(batch_proof.folded_poly, proof.folded_poly);
+    //The repeats are opened once, in increasing order, as in the single path.
+    let opened = canonical_queries(&queries);
+    assert_eq!(batch_proof.queried_columns, opened.iter().map(|&query| encoded_poly.col(query)).collect::<Vec<_>>());
+    assert_eq!(batch_proof.queried_columns, proof.queried_columns);
+    assert!(batch_proof.multi_path.siblings.len() < proof.merkle_paths.iter().map(|path| path.len()).sum());
+
+    assert_eq!(verify_with_queries(commitment.clone(), eval, proof, point.clone(), queries.clone(), &ntt), Ok(()));
+    assert_eq!(verify_batched(commitment.clone(), eval, batch_proof.clone(), point.clone(), queries.clone(), &ntt), Ok(()));
+    assert_eq!(verify_batched(commitment.clone(), eval + BinaryField128b::ONE, batch_proof.clone(), point.clone(), queries.clone(), &ntt), Err(VerifyError::EvaluationMismatch));
+
+    let mut wrong_column = batch_proof.clone();
+    wrong_column.queried_columns[0][0] += BinaryField32b::ONE;
+    assert_eq!(verify_batched(commitment.clone(), eval, wrong_column, point.clone(), queries.clone(), &ntt), Err(VerifyError::MultiPathMismatch));
+
+    let mut wrong_fold = batch_proof;
+    wrong_fold.folded_poly[0] += BinaryField128b::ONE;
+    assert!(matches!(verify_batched(commitment, eval, wrong_fold, point, queries, &ntt), Err(VerifyError::ColumnCheckFailed { .. })));
+}
+
+#[test]
+fn too_many_queries_test(){
+    use rand::thread_rng;
+    use crate::{prover::{prove, prove_with_queries, ProveError}, test::{random_opening, Opening, MIN_PROVABLE_LOG_LEN}, utils::{encoding::{log_dimensions, RATE}, transcript::Transcript}};
+
+    let mut rng = thread_rng();
+    let l = 4;
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+
+    let cols = encoded_poly.cols;
+    assert_eq!(check_query_count(&commitment, cols), Ok(()));
+    assert_eq!(check_query_count(&commitment, 241), Err(VerifyError::TooManyQueries { requested: 241, available: cols }));
+
+    let mut transcript = Transcript::new();
+    transcript.observe(&commitment.commit.0);
+    assert_eq!(transcript.clone().draw_distinct_queries(cols + 1, cols), Err(VerifyError::TooManyQueries { requested: cols + 1, available: cols }));
+
+    //Asking for every column gives each one exactly once.
+    let queries = transcript.draw_distinct_queries(cols, cols).unwrap();
+    let mut sorted = queries.clone();
+    sorted.sort();
+    assert_eq!(sorted, (0..cols).collect::<Vec<_>>());
+
+    //The 2^4 coefficients of the polynomial only fill 4 columns, fewer than the NUM_QUERIES prove and verify draw.
+    assert_eq!(prove(&poly, &encoded_poly, &merkle_tree, &point).unwrap_err(), ProveError::TooManyQueries { requested: NUM_QUERIES, available: cols });
+
+    let proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+    assert_eq!(verify(commitment.clone(), eval, proof.clone(), point.clone(), &ntt), Err(VerifyError::TooManyQueries { requested: NUM_QUERIES, available: cols }));
+    assert_eq!(verify_with_queries(commitment, eval, proof, point, queries, &ntt), Ok(()));
+
+    //MIN_PROVABLE_LOG_LEN is the first length with enough columns.
+    let cols_at = |l| 1<<log_dimensions(l, RATE, PACKING_DEGREE).1;
+    assert!(cols_at(MIN_PROVABLE_LOG_LEN) >= NUM_QUERIES && cols_at(MIN_PROVABLE_LOG_LEN - 1) < NUM_QUERIES);
+}
+
+#[test]
+fn num_queries_for_soundness_test(){
+    use crate::utils::encoding::RATE;
+
+    let log_inv_rate = RATE.trailing_zeros() as usize;
+    assert_eq!(num_queries_for_soundness(log_inv_rate, 0.75, 100), 241);
+    assert_eq!(num_queries_for_soundness(log_inv_rate, 0.75, 128), 309);
+    assert_eq!(num_queries_for_soundness(1, 0.5, 100), 381);
+    assert_eq!(num_queries_for_soundness(3, 0.875, 128), 258);
+
+    //A code below the Singleton bound needs more queries for the same security.
+    assert!(num_queries_for_soundness(log_inv_rate, 0.5, 100) > num_queries_for_soundness(log_inv_rate, 0.75, 100));
+    assert!(std::panic::catch_unwind(|| num_queries_for_soundness(1, 0.75, 100)).is_err());
+}
+
+#[test]
+fn duplicate_queries_test(){
+    use crate::{prover::prove_with_queries, test::{random_opening, Opening}};
+    use rand::thread_rng;
+
+    let mut rng = thread_rng();
+    let l = 10;
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+
+    let queries = vec![7, 3, 7, 0, 3, 3, encoded_poly.cols - 1];
+    let proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+    assert_eq!(proof.num_queries(), 4);
+
+    //Opening every requested position as is, repeats included.
+    let naive = EvalProof::new(
+        proof.folded_poly.clone(),
+        queries.iter().map(|&query| encoded_poly.col(query)).collect(),
+        queries.iter().map(|&query| merkle_tree.get_merkle_path(query)).collect()
+    );
+    assert!(proof.size_in_bytes() < naive.size_in_bytes());
+
+    assert_eq!(verify_with_queries(commitment.clone(), eval, proof.clone(), point.clone(), queries, &ntt), Ok(()));
+    assert_eq!(verify_with_queries(commitment, eval, proof, point, vec![0, 3, 7, encoded_poly.cols - 1], &ntt), Ok(()));
+}
+
+#[test]
+fn multi_consistency_test(){
+    use rand::thread_rng;
+    use crate::{prover::{commit, prove_multi}, utils::packed_arithmetic::evaluate_unpacked};
+
+    let mut rng = thread_rng();
+    let l = 10;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let (commitment, merkle_tree, encoded_poly) = commit(&poly, &ntt);
+
+    let points:Vec<Vec<BinaryField128b>> = (0..3).map(|_| (0..l+5).map(|_| BinaryField128b::random(&mut rng)).collect()).collect();
+    let evals:Vec<BinaryField128b> = points.iter().map(|point| evaluate_unpacked(&poly, &compute_fourier_bases(point))).collect();
+    let queries = vec![1, 7, encoded_poly.cols - 1];
+
+    let proof = prove_multi(&poly, &encoded_poly, &merkle_tree, &points, &queries).unwrap();
+    assert_eq!(proof.folded_polys.len(), 3);
+    assert_eq!(verify_multi_consistency(commitment.clone(), &evals, proof.clone(), &points, queries.clone(), &ntt), Ok(()));
+
+    //A fold of point 1 not made from the committed rows no longer matches the shared columns. The code is systematic,
+    //so the change has to land in the 32 entries of a queried column to be caught by the column check.
+    let mut tampered = proof.clone();
+    tampered.folded_polys[1][7*32] += BinaryField128b::ONE;
+    let result = verify_multi_consistency(commitment.clone(), &evals, tampered.clone(), &points, queries.clone(), &ntt);
+    assert!(matches!(result, Err(VerifyError::PointCheckFailed { point: 1, error }) if matches!(*error, VerifyError::ColumnCheckFailed { .. })));
+
+    //The other points still verify on their own.
+    for i in [0, 2]{
+        let mut single = tampered.clone();
+        single.folded_polys = vec![tampered.folded_polys[i].clone()];
+        assert_eq!(verify_multi_consistency(commitment.clone(), &evals[i..i + 1], single, &points[i..i + 1], queries.clone(), &ntt), Ok(()));
+    }
+
+    let mut wrong_evals = evals.clone();
+    wrong_evals[2] += BinaryField128b::ONE;
+    assert_eq!(verify_multi_consistency(commitment, &wrong_evals, proof, &points, queries, &ntt),
+        Err(VerifyError::PointCheckFailed { point: 2, error: Box::new(VerifyError::EvaluationMismatch) }));
+}
+
+#[test]
+fn recompute_root_test(){
+    use rand::thread_rng;
+    use crate::prover::{commit, prove_with_queries};
+
+    let mut rng = thread_rng();
+    let l = 10;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let (commitment, merkle_tree, encoded_poly) = commit(&poly, &ntt);
+
+    let point:Vec<BinaryField128b> = (0..l+5).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let queries = vec![4, 0, 17, encoded_poly.cols - 1];
+    let proof = prove_with_queries(&poly, &encoded_poly, &merkle_tree, &point, &queries).unwrap();
+    assert_eq!(recompute_root_from_queries(&proof, &queries), Ok(commitment.commit));
+
+    let mut wrong_column = proof.clone();
+    wrong_column.queried_columns[2][0] += BinaryField32b::ONE;
+    assert_eq!(recompute_root_from_queries(&wrong_column, &queries), Err(MerkleError::RootDisagreement { index: 17 }));
+
+    //The root is taken from the path of the smallest query, so a wrong one there shows up as the next query disagreeing with it.
+    let mut wrong_first = proof.clone();
+    wrong_first.merkle_paths[0][0] = wrong_first.merkle_paths[1][0].clone();
+    assert_eq!(recompute_root_from_queries(&wrong_first, &queries), Err(MerkleError::RootDisagreement { index: 4 }));
+
+    assert_eq!(recompute_root_from_queries(&proof, &[]), Err(MerkleError::NoOpenings));
+}
+
+#[test]
+fn fiat_shamir_test(){
+    use rand::thread_rng;
+    use crate::{prover::prove, test::{random_opening, Opening, MIN_PROVABLE_LOG_LEN}};
+
+    let mut rng = thread_rng();
+    let l = MIN_PROVABLE_LOG_LEN;
+    let Opening{ poly, ntt, commitment, merkle_tree, encoded_poly, point, eval } = random_opening(l, &mut rng);
+    let proof = prove(&poly, &encoded_poly, &merkle_tree, &point).unwrap();
+
+    //The opened columns are the ones the verifier derives, NUM_QUERIES distinct ones.
+    let queries = fiat_shamir_queries(&commitment.commit, commitment.log_inv_rate, &point, eval, &proof.folded_poly, NUM_QUERIES, encoded_poly.cols).unwrap();
+    assert_eq!(proof.num_queries(), NUM_QUERIES);
+    assert_eq!(canonical_queries(&queries), queries);
+    assert!(queries.iter().zip(&proof.queried_columns).all(|(&query, column)| encoded_poly.col(query) == *column));
+    assert_eq!(verify(commitment.clone(), eval, proof.clone(), point.clone(), &ntt), Ok(()));
+
+    //The queries are bound to the statement, another evaluation, point or rate gives other queries.
+    assert_ne!(fiat_shamir_queries(&commitment.commit, commitment.log_inv_rate, &point, eval + BinaryField128b::ONE, &proof.folded_poly, NUM_QUERIES, encoded_poly.cols).unwrap(), queries);
+    let mut other_point = point.clone();
+    other_point[0] += BinaryField128b::ONE;
+    assert_ne!(fiat_shamir_queries(&commitment.commit, commitment.log_inv_rate, &other_point, eval, &proof.folded_poly, NUM_QUERIES, encoded_poly.cols).unwrap(), queries);
+    assert_ne!(fiat_shamir_queries(&commitment.commit, commitment.log_inv_rate + 1, &point, eval, &proof.folded_poly, NUM_QUERIES, encoded_poly.cols).unwrap(), queries);
+
+    //Changing the folded polynomial changes the derived queries, so the opened columns no longer sit where the verifier looks.
+    let mut tampered = proof;
+    tampered.folded_poly[0] += BinaryField128b::ONE;
+    assert_ne!(fiat_shamir_queries(&commitment.commit, commitment.log_inv_rate, &point, eval, &tampered.folded_poly, NUM_QUERIES, encoded_poly.cols).unwrap(), queries);
+    assert!(verify(commitment, eval, tampered, point, &ntt).is_err());
+}
+
+#[test]
+fn hiding_test(){
+    use rand::thread_rng;
+    use crate::{prover::{commit_hiding, prove_hiding}, utils::packed_arithmetic::evaluate_unpacked};
+
+    let mut rng = thread_rng();
+    let l = 15;
+    let poly:Vec<BinaryField32b> = (0..1<<l).map(|_| BinaryField32b::random(&mut rng)).collect();
+    let ntt = AdditiveNTT::new(l);
+    let (commitment, merkle_tree, encoded_poly) = commit_hiding(&poly, &ntt, &mut rng).unwrap();
+
+    let point:Vec<BinaryField128b> = (0..l+5).map(|_| BinaryField128b::random(&mut rng)).collect();
+    let eval = evaluate_unpacked(&poly, &compute_fourier_bases(&point));
+    let proof = prove_hiding(&encoded_poly, &merkle_tree, &point).unwrap();
+
+    //Only the parity part of the codeword is opened.
+    assert!(proof.queried_columns.len() > 1);
+    let hiding_queries = canonical_queries(&hiding_queries(&commitment.commit, &proof.folded_poly, NUM_QUERIES, encoded_poly.cols, commitment.encoding_params().rate));
+    assert!(hiding_queries.iter().all(|&query| query >= encoded_poly.cols));
+
+    assert_eq!(verify_hiding(commitment.clone(), eval, proof.clone(), point.clone(), &ntt), Ok(()));
+    assert_eq!(verify_hiding(commitment.clone(), eval + BinaryField128b::ONE, proof.clone(), point.clone(), &ntt), Err(VerifyError::EvaluationMismatch));
+
+    //Shifting the claimed evaluation onto the mask changes gamma, so it is caught all the same.
+    let mut shifted = proof.clone();
+    shifted.mask_eval += BinaryField128b::ONE;
+    assert!(verify_hiding(commitment.clone(), eval, shifted, point.clone(), &ntt).is_err());
+
+    //The parity columns are opened, so claiming another rate than the one the rows were encoded at leaves the merkle paths too short.
+    let mut other_rate = commitment.clone();
+    other_rate.log_inv_rate += 1;
+    assert!(matches!(verify_hiding(other_rate, eval, proof.clone(), point.clone(), &ntt), Err(VerifyError::MalformedProof { .. })));
+
+    let mut tampered = proof.clone();
+    tampered.queried_columns[0][1] += BinaryField32b::ONE;
+    assert_eq!(verify_hiding(commitment.clone(), eval, tampered, point.clone(), &ntt), Err(VerifyError::MerklePathMismatch { query: hiding_queries[0] }));
+
+    let mut short_column = proof;
+    short_column.queried_columns[0].pop();
+    assert!(matches!(verify_hiding(commitment, eval, short_column, point, &ntt), Err(VerifyError::MalformedProof { .. })));
+}
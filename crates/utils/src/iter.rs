@@ -1,6 +1,6 @@
 // Copyright 2024 Ulvetanna Inc.
 
-use std::iter::FusedIterator;
+use core::iter::FusedIterator;
 
 pub trait IterExtensions: Iterator + Sized {
 	fn map_skippable<R, F>(self, f: F) -> SkippableMap<Self, F>
@@ -1,4 +1,5 @@
 // Copyright 2024 Ulvetanna Inc.
+#![cfg_attr(not(test), no_std)]
 
 pub mod checked_arithmetics;
 pub mod iter;